@@ -0,0 +1,302 @@
+/* Generated by cbindgen from the sources of sportident-ffi, do not edit. */
+
+#ifndef SPORTIDENT_H
+#define SPORTIDENT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of every fallible function. On failure, `si_last_error_message` describes the
+// error.
+typedef enum SiStatus {
+  SI_STATUS_OK = 0,
+  // A required pointer argument was null.
+  SI_STATUS_NULL_POINTER = 1,
+  // An argument was out of range, e.g. a path which is not UTF-8.
+  SI_STATUS_INVALID_ARGUMENT = 2,
+  // The runtime driving the reader could not be started.
+  SI_STATUS_RUNTIME = 3,
+  // An error of a crate feature these bindings don't use.
+  SI_STATUS_OTHER = 9,
+  SI_STATUS_SERIAL_PORT = 10,
+  SI_STATUS_IO = 11,
+  SI_STATUS_ENCODER = 12,
+  SI_STATUS_INVALID_RESPONSE_RECEIVED = 13,
+  SI_STATUS_INPUT_IS_NOT_EMPTY = 14,
+  SI_STATUS_RECEIVED_INVALID_COMMAND = 15,
+  SI_STATUS_NOT_EXTENDED_PROTOCOL_MODE = 16,
+  SI_STATUS_NOT_READOUT_MODE = 17,
+  SI_STATUS_PORT_CLOSED = 18,
+  SI_STATUS_CARD_REMOVED_WHILE_READING_DATA = 19,
+  SI_STATUS_NOT_AUTO_SEND_MODE = 20,
+  SI_STATUS_NO_READER_DETECTED = 21,
+  SI_STATUS_INVALID_BACKUP_CURSOR_FILE = 22,
+  SI_STATUS_BACKUP_NOT_SYNCHRONIZED = 23,
+  SI_STATUS_REMOTE_STATION_ALREADY_COUPLED = 24,
+  SI_STATUS_NOT_SRR_STATION = 25,
+  SI_STATUS_INVALID_BACKUP_ADDRESS = 26,
+  SI_STATUS_INVALID_STATION_CODE = 27,
+  SI_STATUS_DECODER_IO = 100,
+  SI_STATUS_INVALID_COMMAND_SENT = 101,
+  SI_STATUS_INVALID_START_BYTE = 102,
+  SI_STATUS_INVALID_LENGTH = 103,
+  SI_STATUS_INVALID_END_BYTE = 104,
+  SI_STATUS_INVALID_CHECKSUM = 105,
+  SI_STATUS_INVALID_COMMAND = 106,
+  SI_STATUS_INVALID_SYSTEM_CONFIGURATION = 107,
+  SI_STATUS_INVALID_DATE = 108,
+  SI_STATUS_UNKNOWN_MODEL_ID = 109,
+  SI_STATUS_UNKNOWN_SRR_CHANNEL = 110,
+  SI_STATUS_UNKNOWN_STATION_MODE = 111,
+  SI_STATUS_INVALID_CARD_INSERTED_LENGTH = 112,
+  SI_STATUS_INVALID_CARD_NUMBER = 113,
+  SI_STATUS_INVALID_BLOCK_SIZE = 114,
+  SI_STATUS_INVALID_READOUT_DATA_LENGTH = 115,
+  SI_STATUS_INVALID_PUNCH_TIME = 116,
+  SI_STATUS_INVALID_OWNER_DATA = 117,
+  SI_STATUS_INVALID_BACKUP_DATA_LENGTH = 118,
+  SI_STATUS_INVALID_SYSTEM_VALUE_RESPONSE = 119,
+  SI_STATUS_INVALID_TIME_LENGTH = 120,
+  SI_STATUS_INVALID_PUNCH_LENGTH = 121,
+} SiStatus;
+
+typedef enum SiCardType {
+  SI_CARD_TYPE_SI8,
+  SI_CARD_TYPE_SI9,
+  SI_CARD_TYPE_SI10,
+  SI_CARD_TYPE_SI11,
+  SI_CARD_TYPE_SIAC,
+  SI_CARD_TYPE_PUNCH_CARD,
+} SiCardType;
+
+typedef enum SiPunchSource {
+  // Punched on the connected station.
+  SI_PUNCH_SOURCE_DIRECT,
+  // Received over the air by an SRR dongle.
+  SI_PUNCH_SOURCE_RADIO,
+  // Read back from the backup memory of the connected station.
+  SI_PUNCH_SOURCE_BACKUP,
+} SiPunchSource;
+
+// A connected reader. Every call on it blocks until the station answers.
+typedef struct SiReader SiReader;
+
+#if defined(SPORTIDENT_SIMULATOR)
+// A station in memory, for testing code built on the reader without hardware.
+typedef struct SiSimulator SiSimulator;
+#endif
+
+typedef struct SiPunch {
+  // The control code, 0 for sub-second start and finish punches, which don't record it.
+  uint16_t code;
+  // Milliseconds since midnight.
+  uint32_t time_ms;
+  // 0 for Monday to 6 for Sunday.
+  uint8_t day_of_week;
+  // 0 to 3, the week of the punch in a four week cycle.
+  uint8_t week_counter;
+} SiPunch;
+
+// A decoded card. The punches are owned by the readout, free them with
+// `si_card_readout_free`.
+typedef struct SiCardReadout {
+  uint32_t card_number;
+  enum SiCardType card_type;
+  bool has_start;
+  struct SiPunch start;
+  bool has_finish;
+  struct SiPunch finish;
+  bool has_check;
+  struct SiPunch check;
+  struct SiPunch *punches;
+  size_t punch_count;
+} SiCardReadout;
+
+// The owner data stored on a card, as UTF-8 strings. Optional fields which are not set are
+// null. The strings are owned by the struct, free them with `si_card_owner_data_free`.
+typedef struct SiCardOwnerData {
+  char *first_name;
+  char *last_name;
+  char *gender;
+  char *birthday;
+  char *club;
+  char *email;
+  char *phone;
+  char *city;
+  char *street;
+  char *zip;
+  char *country;
+} SiCardOwnerData;
+
+typedef struct SiCardPunch {
+  uint32_t card_number;
+  enum SiCardType card_type;
+  // The code of the station the card was punched at, also set as the code of `punch`.
+  uint16_t station_code;
+  struct SiPunch punch;
+  enum SiPunchSource source;
+} SiCardPunch;
+
+// Called for every card read by `si_reader_listen_cards`. The readout is only valid during
+// the call. Return false to stop listening.
+typedef bool (*SiCardCallback)(struct SiReader *reader,
+                               const struct SiCardReadout *readout,
+                               void *user_data);
+
+// Called for every punch received by `si_reader_listen_punches`. Return false to stop
+// listening.
+typedef bool (*SiPunchCallback)(struct SiReader *reader,
+                                const struct SiCardPunch *punch,
+                                void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The message of the last error on the calling thread, or null if there was none. The string
+// is valid until the next failing call on the same thread.
+const char *si_last_error_message(void);
+
+// A static description of a status, such as "Card removed while reading data".
+const char *si_status_description(enum SiStatus status);
+
+// Connects to the station on a serial port, such as "/dev/ttyUSB0" or "COM3".
+//
+// # Safety
+// `path` must be a NUL-terminated string, `out` a valid pointer.
+enum SiStatus si_reader_open(const char *path, struct SiReader **out);
+
+// Connects to the first station found on any serial port.
+//
+// # Safety
+// `out` must be a valid pointer.
+enum SiStatus si_reader_auto_connect(struct SiReader **out);
+
+// Closes a reader.
+//
+// # Safety
+// `reader` must be null or a reader returned by this library, and not used afterwards.
+void si_reader_free(struct SiReader *reader);
+
+// Waits for a card to be inserted and reads it. Free the readout with `si_card_readout_free`.
+//
+// # Safety
+// `reader` must be a live reader, `out` a valid pointer.
+enum SiStatus si_reader_poll_card(struct SiReader *reader, struct SiCardReadout *out);
+
+// Waits for a card to be inserted and reads it along with its owner data. Free the results
+// with `si_card_readout_free` and `si_card_owner_data_free`.
+//
+// # Safety
+// `reader` must be a live reader, `readout` and `owner_data` valid pointers.
+enum SiStatus si_reader_poll_card_with_owner_data(struct SiReader *reader,
+                                                  struct SiCardReadout *readout,
+                                                  struct SiCardOwnerData *owner_data);
+
+// Waits for the next punch, the station must be in auto send mode.
+//
+// # Safety
+// `reader` must be a live reader, `out` a valid pointer.
+enum SiStatus si_reader_poll_punch(struct SiReader *reader, struct SiCardPunch *out);
+
+// Beeps and waits for the card to be removed.
+//
+// # Safety
+// `reader` must be a live reader.
+enum SiStatus si_reader_beep_until_card_removed(struct SiReader *reader);
+
+// Reads cards until `callback` returns false or reading fails, returning `SI_STATUS_OK` in
+// the first case. The callback may call other functions on the reader, e.g. to beep.
+//
+// # Safety
+// `reader` must be a live reader, `callback` a valid function.
+enum SiStatus si_reader_listen_cards(struct SiReader *reader,
+                                     SiCardCallback callback,
+                                     void *user_data);
+
+// Receives punches until `callback` returns false or receiving fails, returning
+// `SI_STATUS_OK` in the first case.
+//
+// # Safety
+// `reader` must be a live reader, `callback` a valid function.
+enum SiStatus si_reader_listen_punches(struct SiReader *reader,
+                                       SiPunchCallback callback,
+                                       void *user_data);
+
+#if defined(SPORTIDENT_SIMULATOR)
+// Starts a simulated station in `mode` (the station mode byte, e.g. 0x05 for readout or 0x02
+// for control) and connects a reader to it. Free both when done.
+//
+// # Safety
+// `station` and `reader` must be valid pointers.
+enum SiStatus si_simulator_connect(uint8_t mode,
+                                   uint16_t station_code,
+                                   struct SiSimulator **station,
+                                   struct SiReader **reader);
+#endif
+
+#if defined(SPORTIDENT_SIMULATOR)
+// Stops a simulated station.
+//
+// # Safety
+// `station` must be null or a station returned by this library, and not used afterwards.
+void si_simulator_free(struct SiSimulator *station);
+#endif
+
+#if defined(SPORTIDENT_SIMULATOR)
+// Inserts a card, whose blocks are read from the raw card image `data`.
+//
+// # Safety
+// `station` must be a live station, `data` point to `length` bytes.
+enum SiStatus si_simulator_insert_card(const struct SiSimulator *station,
+                                       uint32_t card_number,
+                                       const uint8_t *data,
+                                       size_t length);
+#endif
+
+#if defined(SPORTIDENT_SIMULATOR)
+// Removes the inserted card.
+//
+// # Safety
+// `station` must be a live station.
+enum SiStatus si_simulator_remove_card(const struct SiSimulator *station);
+#endif
+
+#if defined(SPORTIDENT_SIMULATOR)
+// Sends a punch, as a station in auto send mode does. The card type and source of `punch`
+// are ignored, it is sent as a direct punch.
+//
+// # Safety
+// `station` must be a live station, `punch` a valid pointer.
+enum SiStatus si_simulator_punch(const struct SiSimulator *station,
+                                 const struct SiCardPunch *punch);
+#endif
+
+#if defined(SPORTIDENT_SIMULATOR)
+// The number of beep commands the station received.
+//
+// # Safety
+// `station` must be null or a live station.
+size_t si_simulator_beeps(const struct SiSimulator *station);
+#endif
+
+// Frees the punches of a readout filled in by this library, leaving it empty.
+//
+// # Safety
+// `readout` must be null or point to a readout filled in by this library.
+void si_card_readout_free(struct SiCardReadout *readout);
+
+// Frees the strings of owner data filled in by this library, setting them to null.
+//
+// # Safety
+// `owner_data` must be null or point to owner data filled in by this library.
+void si_card_owner_data_free(struct SiCardOwnerData *owner_data);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SPORTIDENT_H */
@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use sportident::{DecoderError, EncoderError, Error};
+
+/// The result of every fallible function. On failure, `si_last_error_message` describes the
+/// error.
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SiStatus {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// An argument was out of range, e.g. a path which is not UTF-8.
+    InvalidArgument = 2,
+    /// The runtime driving the reader could not be started.
+    Runtime = 3,
+    /// An error of a crate feature these bindings don't use.
+    Other = 9,
+    SerialPort = 10,
+    Io = 11,
+    Encoder = 12,
+    InvalidResponseReceived = 13,
+    InputIsNotEmpty = 14,
+    ReceivedInvalidCommand = 15,
+    NotExtendedProtocolMode = 16,
+    NotReadoutMode = 17,
+    PortClosed = 18,
+    CardRemovedWhileReadingData = 19,
+    NotAutoSendMode = 20,
+    NoReaderDetected = 21,
+    InvalidBackupCursorFile = 22,
+    BackupNotSynchronized = 23,
+    RemoteStationAlreadyCoupled = 24,
+    NotSrrStation = 25,
+    InvalidBackupAddress = 26,
+    InvalidStationCode = 27,
+    DecoderIo = 100,
+    InvalidCommandSent = 101,
+    InvalidStartByte = 102,
+    InvalidLength = 103,
+    InvalidEndByte = 104,
+    InvalidChecksum = 105,
+    InvalidCommand = 106,
+    InvalidSystemConfiguration = 107,
+    InvalidDate = 108,
+    UnknownModelId = 109,
+    UnknownSrrChannel = 110,
+    UnknownStationMode = 111,
+    InvalidCardInsertedLength = 112,
+    InvalidCardNumber = 113,
+    InvalidBlockSize = 114,
+    InvalidReadoutDataLength = 115,
+    InvalidPunchTime = 116,
+    InvalidOwnerData = 117,
+    InvalidBackupDataLength = 118,
+    InvalidSystemValueResponse = 119,
+    InvalidTimeLength = 120,
+    InvalidPunchLength = 121,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+impl From<&Error> for SiStatus {
+    fn from(error: &Error) -> Self {
+        // Variants of crate features which are not enabled here are unreachable.
+        #[allow(unreachable_patterns)]
+        match error {
+            Error::SerialPortError(_) => Self::SerialPort,
+            Error::IoError(_) | Error::EncoderError(EncoderError::IoError(_)) => Self::Io,
+            Error::DecoderError(error) => error.into(),
+            Error::EncoderError(_) => Self::Encoder,
+            Error::InvalidResponseReceived => Self::InvalidResponseReceived,
+            Error::InputIsNotEmpty(_) => Self::InputIsNotEmpty,
+            Error::ReceivedInvalidCommand(..) => Self::ReceivedInvalidCommand,
+            Error::NotExtendedProtocolMode => Self::NotExtendedProtocolMode,
+            Error::NotReadoutMode => Self::NotReadoutMode,
+            Error::PortClosed => Self::PortClosed,
+            Error::CardRemovedWhileReadingData => Self::CardRemovedWhileReadingData,
+            Error::NotAutoSendMode => Self::NotAutoSendMode,
+            Error::NoReaderDetected => Self::NoReaderDetected,
+            Error::InvalidBackupCursorFile(_) => Self::InvalidBackupCursorFile,
+            Error::BackupNotSynchronized(_) => Self::BackupNotSynchronized,
+            Error::RemoteStationAlreadyCoupled => Self::RemoteStationAlreadyCoupled,
+            Error::NotSRRStation => Self::NotSrrStation,
+            Error::InvalidBackupAddress(_) => Self::InvalidBackupAddress,
+            Error::InvalidStationCode(_) => Self::InvalidStationCode,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl From<&DecoderError> for SiStatus {
+    fn from(error: &DecoderError) -> Self {
+        match error {
+            DecoderError::IoError(_) => Self::DecoderIo,
+            DecoderError::InvalidCommandSent => Self::InvalidCommandSent,
+            DecoderError::InvalidStartByte(_) => Self::InvalidStartByte,
+            DecoderError::InvalidLength(_) => Self::InvalidLength,
+            DecoderError::InvalidEndByte(_) => Self::InvalidEndByte,
+            DecoderError::InvalidChecksum(..) => Self::InvalidChecksum,
+            DecoderError::InvalidCommand(_) => Self::InvalidCommand,
+            DecoderError::InvalidSystemConfiguration(..) => Self::InvalidSystemConfiguration,
+            DecoderError::InvalidDate(..) => Self::InvalidDate,
+            DecoderError::UnknownModelId(_) => Self::UnknownModelId,
+            DecoderError::UnknownSRRChannel(_) => Self::UnknownSrrChannel,
+            DecoderError::UnknownStationMode(_) => Self::UnknownStationMode,
+            DecoderError::InvalidCardInsertedLength(..) => Self::InvalidCardInsertedLength,
+            DecoderError::InvalidCardNumber(_) => Self::InvalidCardNumber,
+            DecoderError::InvalidBlockSize(..) => Self::InvalidBlockSize,
+            DecoderError::InvalidReadoutDataLength => Self::InvalidReadoutDataLength,
+            DecoderError::InvalidPunchTime => Self::InvalidPunchTime,
+            DecoderError::InvalidOwnerData => Self::InvalidOwnerData,
+            DecoderError::InvalidBackupDataLength(_) => Self::InvalidBackupDataLength,
+            DecoderError::InvalidSystemValueResponse => Self::InvalidSystemValueResponse,
+            DecoderError::InvalidTimeLength(..) => Self::InvalidTimeLength,
+            DecoderError::InvalidPunchLength(_) => Self::InvalidPunchLength,
+        }
+    }
+}
+
+/// Records `message` as the last error of this thread, returning `status`.
+pub fn fail(status: SiStatus, message: &impl ToString) -> SiStatus {
+    let message = CString::new(message.to_string().replace('\0', "")).ok();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+/// The message of the last error on the calling thread, or null if there was none. The string
+/// is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn si_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// A static description of a status, such as "Card removed while reading data".
+#[no_mangle]
+pub const extern "C" fn si_status_description(status: SiStatus) -> *const c_char {
+    let description: &'static std::ffi::CStr = match status {
+        SiStatus::Ok => c"Ok",
+        SiStatus::NullPointer => c"Null pointer argument",
+        SiStatus::InvalidArgument => c"Invalid argument",
+        SiStatus::Runtime => c"Failed to start the runtime",
+        SiStatus::Other => c"Other error",
+        SiStatus::SerialPort => c"Serial port error",
+        SiStatus::Io | SiStatus::DecoderIo => c"IO error",
+        SiStatus::Encoder => c"Encoder error",
+        SiStatus::InvalidResponseReceived => c"Invalid response received",
+        SiStatus::InputIsNotEmpty => c"Input buffer is not empty",
+        SiStatus::ReceivedInvalidCommand => c"Received invalid command",
+        SiStatus::NotExtendedProtocolMode => c"Station is not in extended protocol mode",
+        SiStatus::NotReadoutMode => c"Station is not in readout mode",
+        SiStatus::PortClosed => c"Port closed",
+        SiStatus::CardRemovedWhileReadingData => c"Card removed while reading data",
+        SiStatus::NotAutoSendMode => c"Station is not in auto send mode",
+        SiStatus::NoReaderDetected => c"No reader detected",
+        SiStatus::InvalidBackupCursorFile => c"Invalid backup cursor file",
+        SiStatus::BackupNotSynchronized => c"Backup memory is not synchronized",
+        SiStatus::RemoteStationAlreadyCoupled => c"A remote station is already coupled",
+        SiStatus::NotSrrStation => c"Not an SRR station",
+        SiStatus::InvalidBackupAddress => c"Invalid backup memory address",
+        SiStatus::InvalidStationCode => c"Invalid station code",
+        SiStatus::InvalidCommandSent => c"Invalid command sent",
+        SiStatus::InvalidStartByte => c"Invalid start byte",
+        SiStatus::InvalidLength => c"Invalid length",
+        SiStatus::InvalidEndByte => c"Invalid end byte",
+        SiStatus::InvalidChecksum => c"Invalid checksum",
+        SiStatus::InvalidCommand => c"Invalid command",
+        SiStatus::InvalidSystemConfiguration => c"Invalid system configuration",
+        SiStatus::InvalidDate => c"Invalid date",
+        SiStatus::UnknownModelId => c"Unknown model id",
+        SiStatus::UnknownSrrChannel => c"Unknown SRR channel",
+        SiStatus::UnknownStationMode => c"Unknown station mode",
+        SiStatus::InvalidCardInsertedLength => c"Invalid card inserted response",
+        SiStatus::InvalidCardNumber => c"Invalid card number",
+        SiStatus::InvalidBlockSize => c"Invalid block size",
+        SiStatus::InvalidReadoutDataLength => c"Invalid readout data length",
+        SiStatus::InvalidPunchTime => c"Invalid punch time",
+        SiStatus::InvalidOwnerData => c"Invalid owner data",
+        SiStatus::InvalidBackupDataLength => c"Invalid backup data length",
+        SiStatus::InvalidSystemValueResponse => c"Invalid set system value response",
+        SiStatus::InvalidTimeLength => c"Invalid time length",
+        SiStatus::InvalidPunchLength => c"Invalid punch length",
+    };
+    description.as_ptr()
+}
@@ -0,0 +1,49 @@
+//! C bindings for the `sportident` crate, see `include/sportident.h`.
+//!
+//! Every function blocks until it completes, driving the reader on a runtime shared by the
+//! library. Fallible functions return an `SiStatus` and fill in out parameters on success.
+//! Structs filled in by the library own their strings and arrays, which are freed with the
+//! matching `si_*_free` function.
+#![warn(clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::multiple_crate_versions)]
+pub use error::{si_last_error_message, si_status_description, SiStatus};
+pub use reader::{
+    si_reader_auto_connect, si_reader_beep_until_card_removed, si_reader_free,
+    si_reader_listen_cards, si_reader_listen_punches, si_reader_open, si_reader_poll_card,
+    si_reader_poll_card_with_owner_data, si_reader_poll_punch, SiCardCallback, SiPunchCallback,
+    SiReader,
+};
+#[cfg(feature = "simulator")]
+pub use simulator::{
+    si_simulator_beeps, si_simulator_connect, si_simulator_free, si_simulator_insert_card,
+    si_simulator_punch, si_simulator_remove_card, SiSimulator,
+};
+pub use types::{
+    si_card_owner_data_free, si_card_readout_free, SiCardOwnerData, SiCardPunch, SiCardReadout,
+    SiCardType, SiPunch, SiPunchSource,
+};
+
+use std::sync::OnceLock;
+
+use tokio::runtime::Runtime;
+
+mod error;
+mod reader;
+#[cfg(feature = "simulator")]
+mod simulator;
+mod types;
+
+/// The runtime driving every reader and simulated station.
+fn runtime() -> Result<&'static Runtime, SiStatus> {
+    static RUNTIME: OnceLock<std::io::Result<Runtime>> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .enable_all()
+                .build()
+        })
+        .as_ref()
+        .map_err(|error| error::fail(SiStatus::Runtime, error))
+}
@@ -0,0 +1,236 @@
+use std::ffi::{c_char, c_void, CStr};
+
+use sportident::Reader;
+
+use crate::error::{fail, SiStatus};
+use crate::runtime;
+use crate::types::{si_card_readout_free, SiCardOwnerData, SiCardPunch, SiCardReadout};
+
+/// A connected reader. Every call on it blocks until the station answers.
+#[allow(missing_debug_implementations)]
+pub struct SiReader {
+    pub(crate) reader: Reader,
+}
+
+/// Called for every card read by `si_reader_listen_cards`. The readout is only valid during
+/// the call. Return false to stop listening.
+pub type SiCardCallback = Option<
+    unsafe extern "C" fn(
+        reader: *mut SiReader,
+        readout: *const SiCardReadout,
+        user_data: *mut c_void,
+    ) -> bool,
+>;
+
+/// Called for every punch received by `si_reader_listen_punches`. Return false to stop
+/// listening.
+pub type SiPunchCallback = Option<
+    unsafe extern "C" fn(
+        reader: *mut SiReader,
+        punch: *const SiCardPunch,
+        user_data: *mut c_void,
+    ) -> bool,
+>;
+
+impl SiReader {
+    /// Runs a call into the crate to completion and stores the reader in `out`.
+    pub(crate) fn create(
+        out: *mut *mut Self,
+        connect: impl std::future::Future<Output = sportident::Result<Reader>>,
+    ) -> SiStatus {
+        if out.is_null() {
+            return SiStatus::NullPointer;
+        }
+        let runtime = match runtime() {
+            Ok(runtime) => runtime,
+            Err(status) => return status,
+        };
+        match runtime.block_on(connect) {
+            Ok(reader) => {
+                // SAFETY: `out` was checked to be non-null, the caller guarantees it is valid.
+                unsafe { *out = Box::into_raw(Box::new(Self { reader })) };
+                SiStatus::Ok
+            }
+            Err(error) => fail((&error).into(), &error),
+        }
+    }
+}
+
+/// Runs `call` on the reader behind `reader`, blocking until it completes.
+///
+/// # Safety
+/// `reader` must be null or a live reader returned by this library.
+unsafe fn block_on<T>(
+    reader: *mut SiReader,
+    call: impl AsyncFnOnce(&mut Reader) -> sportident::Result<T>,
+) -> Result<T, SiStatus> {
+    let Some(reader) = reader.as_mut() else {
+        return Err(SiStatus::NullPointer);
+    };
+    runtime()?
+        .block_on(call(&mut reader.reader))
+        .map_err(|error| fail((&error).into(), &error))
+}
+
+/// Connects to the station on a serial port, such as "/dev/ttyUSB0" or "COM3".
+///
+/// # Safety
+/// `path` must be a NUL-terminated string, `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_open(path: *const c_char, out: *mut *mut SiReader) -> SiStatus {
+    if path.is_null() {
+        return SiStatus::NullPointer;
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return fail(SiStatus::InvalidArgument, &"Path is not UTF-8");
+    };
+    SiReader::create(out, Reader::connect(path))
+}
+
+/// Connects to the first station found on any serial port.
+///
+/// # Safety
+/// `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_auto_connect(out: *mut *mut SiReader) -> SiStatus {
+    SiReader::create(out, Reader::auto_connect())
+}
+
+/// Closes a reader.
+///
+/// # Safety
+/// `reader` must be null or a reader returned by this library, and not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_free(reader: *mut SiReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
+
+/// Waits for a card to be inserted and reads it. Free the readout with `si_card_readout_free`.
+///
+/// # Safety
+/// `reader` must be a live reader, `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_poll_card(
+    reader: *mut SiReader,
+    out: *mut SiCardReadout,
+) -> SiStatus {
+    if out.is_null() {
+        return SiStatus::NullPointer;
+    }
+    match block_on(reader, Reader::poll_card) {
+        Ok(readout) => {
+            out.write((&readout).into());
+            SiStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Waits for a card to be inserted and reads it along with its owner data. Free the results
+/// with `si_card_readout_free` and `si_card_owner_data_free`.
+///
+/// # Safety
+/// `reader` must be a live reader, `readout` and `owner_data` valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_poll_card_with_owner_data(
+    reader: *mut SiReader,
+    readout: *mut SiCardReadout,
+    owner_data: *mut SiCardOwnerData,
+) -> SiStatus {
+    if readout.is_null() || owner_data.is_null() {
+        return SiStatus::NullPointer;
+    }
+    match block_on(reader, Reader::poll_card_with_owner_data) {
+        Ok((card, owner)) => {
+            readout.write((&card).into());
+            owner_data.write((&owner).into());
+            SiStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Waits for the next punch, the station must be in auto send mode.
+///
+/// # Safety
+/// `reader` must be a live reader, `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_poll_punch(
+    reader: *mut SiReader,
+    out: *mut SiCardPunch,
+) -> SiStatus {
+    if out.is_null() {
+        return SiStatus::NullPointer;
+    }
+    match block_on(reader, Reader::poll_punch) {
+        Ok(punch) => {
+            out.write((&punch).into());
+            SiStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Beeps and waits for the card to be removed.
+///
+/// # Safety
+/// `reader` must be a live reader.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_beep_until_card_removed(reader: *mut SiReader) -> SiStatus {
+    block_on(reader, Reader::beep_until_card_removed)
+        .map_or_else(|status| status, |()| SiStatus::Ok)
+}
+
+/// Reads cards until `callback` returns false or reading fails, returning `SI_STATUS_OK` in
+/// the first case. The callback may call other functions on the reader, e.g. to beep.
+///
+/// # Safety
+/// `reader` must be a live reader, `callback` a valid function.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_listen_cards(
+    reader: *mut SiReader,
+    callback: SiCardCallback,
+    user_data: *mut c_void,
+) -> SiStatus {
+    let Some(callback) = callback else {
+        return SiStatus::NullPointer;
+    };
+    loop {
+        let mut readout = match block_on(reader, Reader::poll_card) {
+            Ok(readout) => SiCardReadout::from(&readout),
+            Err(status) => return status,
+        };
+        let listen = callback(reader, &raw const readout, user_data);
+        si_card_readout_free(&raw mut readout);
+        if !listen {
+            return SiStatus::Ok;
+        }
+    }
+}
+
+/// Receives punches until `callback` returns false or receiving fails, returning
+/// `SI_STATUS_OK` in the first case.
+///
+/// # Safety
+/// `reader` must be a live reader, `callback` a valid function.
+#[no_mangle]
+pub unsafe extern "C" fn si_reader_listen_punches(
+    reader: *mut SiReader,
+    callback: SiPunchCallback,
+    user_data: *mut c_void,
+) -> SiStatus {
+    let Some(callback) = callback else {
+        return SiStatus::NullPointer;
+    };
+    loop {
+        let punch = match block_on(reader, Reader::poll_punch) {
+            Ok(punch) => SiCardPunch::from(&punch),
+            Err(status) => return status,
+        };
+        if !callback(reader, &raw const punch, user_data) {
+            return SiStatus::Ok;
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use sportident::simulator::SimulatedStation;
+use sportident::{
+    Card, CardImage, CardPunch, DayOfWeek, PunchSource, StationMode, SubSecondPunch, WeekCounter,
+};
+
+use chrono::NaiveTime;
+
+use crate::error::{fail, SiStatus};
+use crate::reader::SiReader;
+use crate::types::SiCardPunch;
+
+/// A station in memory, for testing code built on the reader without hardware.
+#[derive(Debug)]
+pub struct SiSimulator {
+    station: SimulatedStation,
+}
+
+/// Starts a simulated station in `mode` (the station mode byte, e.g. 0x05 for readout or 0x02
+/// for control) and connects a reader to it. Free both when done.
+///
+/// # Safety
+/// `station` and `reader` must be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn si_simulator_connect(
+    mode: u8,
+    station_code: u16,
+    station: *mut *mut SiSimulator,
+    reader: *mut *mut SiReader,
+) -> SiStatus {
+    if station.is_null() {
+        return SiStatus::NullPointer;
+    }
+    let Some(mode) = StationMode::from_repr(mode) else {
+        return fail(
+            SiStatus::InvalidArgument,
+            &format!("Unknown station mode {mode}"),
+        );
+    };
+    let mut simulated = None;
+    let status = SiReader::create(reader, async {
+        let (station, reader) = SimulatedStation::connect(mode, station_code).await?;
+        simulated = Some(station);
+        Ok(reader)
+    });
+    if let Some(simulated) = simulated {
+        station.write(Box::into_raw(Box::new(SiSimulator { station: simulated })));
+    }
+    status
+}
+
+/// Stops a simulated station.
+///
+/// # Safety
+/// `station` must be null or a station returned by this library, and not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn si_simulator_free(station: *mut SiSimulator) {
+    if !station.is_null() {
+        drop(Box::from_raw(station));
+    }
+}
+
+/// Inserts a card, whose blocks are read from the raw card image `data`.
+///
+/// # Safety
+/// `station` must be a live station, `data` point to `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn si_simulator_insert_card(
+    station: *const SiSimulator,
+    card_number: u32,
+    data: *const u8,
+    length: usize,
+) -> SiStatus {
+    let (Some(station), false) = (station.as_ref(), data.is_null()) else {
+        return SiStatus::NullPointer;
+    };
+    let card = match Card::new(card_number) {
+        Ok(card) => card,
+        Err(error) => return fail((&error).into(), &error),
+    };
+    let image = CardImage {
+        card_type: card.card_type,
+        data: std::slice::from_raw_parts(data, length).to_vec(),
+    };
+    station.station.insert_card(card_number, image);
+    SiStatus::Ok
+}
+
+/// Removes the inserted card.
+///
+/// # Safety
+/// `station` must be a live station.
+#[no_mangle]
+pub unsafe extern "C" fn si_simulator_remove_card(station: *const SiSimulator) -> SiStatus {
+    let Some(station) = station.as_ref() else {
+        return SiStatus::NullPointer;
+    };
+    station.station.remove_card();
+    SiStatus::Ok
+}
+
+/// Sends a punch, as a station in auto send mode does. The card type and source of `punch`
+/// are ignored, it is sent as a direct punch.
+///
+/// # Safety
+/// `station` must be a live station, `punch` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn si_simulator_punch(
+    station: *const SiSimulator,
+    punch: *const SiCardPunch,
+) -> SiStatus {
+    let (Some(station), Some(punch)) = (station.as_ref(), punch.as_ref()) else {
+        return SiStatus::NullPointer;
+    };
+    let card = match Card::new(punch.card_number) {
+        Ok(card) => card,
+        Err(error) => return fail((&error).into(), &error),
+    };
+    let (Some(time), Some(day_of_week), Some(week_counter)) = (
+        NaiveTime::from_num_seconds_from_midnight_opt(
+            punch.punch.time_ms / 1000,
+            punch.punch.time_ms % 1000 * 1_000_000,
+        ),
+        DayOfWeek::from_repr(punch.punch.day_of_week),
+        WeekCounter::from_repr(punch.punch.week_counter),
+    ) else {
+        return fail(SiStatus::InvalidArgument, &"Invalid punch time");
+    };
+    station.station.punch(CardPunch {
+        punch: SubSecondPunch {
+            time,
+            day_of_week,
+            week_counter,
+        },
+        card,
+        station_code: punch.station_code,
+        source: PunchSource::Direct,
+        backup_address: None,
+    });
+    SiStatus::Ok
+}
+
+/// The number of beep commands the station received.
+///
+/// # Safety
+/// `station` must be null or a live station.
+#[no_mangle]
+pub unsafe extern "C" fn si_simulator_beeps(station: *const SiSimulator) -> usize {
+    station
+        .as_ref()
+        .map_or(0, |station| station.station.beeps())
+}
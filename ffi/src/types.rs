@@ -0,0 +1,271 @@
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use chrono::{NaiveTime, Timelike};
+use sportident::{
+    CardOwnerData, CardPunch, CardReadout, CardType, Punch, PunchSource, StartOrFinishPunch,
+    SubSecondPunch,
+};
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SiCardType {
+    Si8,
+    Si9,
+    Si10,
+    Si11,
+    Siac,
+    PunchCard,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SiPunchSource {
+    /// Punched on the connected station.
+    Direct,
+    /// Received over the air by an SRR dongle.
+    Radio,
+    /// Read back from the backup memory of the connected station.
+    Backup,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct SiPunch {
+    /// The control code, 0 for sub-second start and finish punches, which don't record it.
+    pub code: u16,
+    /// Milliseconds since midnight.
+    pub time_ms: u32,
+    /// 0 for Monday to 6 for Sunday.
+    pub day_of_week: u8,
+    /// 0 to 3, the week of the punch in a four week cycle.
+    pub week_counter: u8,
+}
+
+/// A decoded card. The punches are owned by the readout, free them with
+/// `si_card_readout_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SiCardReadout {
+    pub card_number: u32,
+    pub card_type: SiCardType,
+    pub has_start: bool,
+    pub start: SiPunch,
+    pub has_finish: bool,
+    pub finish: SiPunch,
+    pub has_check: bool,
+    pub check: SiPunch,
+    pub punches: *mut SiPunch,
+    pub punch_count: usize,
+}
+
+/// The owner data stored on a card, as UTF-8 strings. Optional fields which are not set are
+/// null. The strings are owned by the struct, free them with `si_card_owner_data_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SiCardOwnerData {
+    pub first_name: *mut c_char,
+    pub last_name: *mut c_char,
+    pub gender: *mut c_char,
+    pub birthday: *mut c_char,
+    pub club: *mut c_char,
+    pub email: *mut c_char,
+    pub phone: *mut c_char,
+    pub city: *mut c_char,
+    pub street: *mut c_char,
+    pub zip: *mut c_char,
+    pub country: *mut c_char,
+}
+
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct SiCardPunch {
+    pub card_number: u32,
+    pub card_type: SiCardType,
+    /// The code of the station the card was punched at, also set as the code of `punch`.
+    pub station_code: u16,
+    pub punch: SiPunch,
+    pub source: SiPunchSource,
+}
+
+impl From<CardType> for SiCardType {
+    fn from(card_type: CardType) -> Self {
+        match card_type {
+            CardType::Si8 => Self::Si8,
+            CardType::Si9 => Self::Si9,
+            CardType::Si10 => Self::Si10,
+            CardType::Si11 => Self::Si11,
+            CardType::Siac => Self::Siac,
+            CardType::PunchCard => Self::PunchCard,
+        }
+    }
+}
+
+impl From<&Punch> for SiPunch {
+    fn from(punch: &Punch) -> Self {
+        Self {
+            code: punch.code,
+            time_ms: time_ms(punch.time),
+            day_of_week: punch.day_of_week as u8,
+            week_counter: punch.week_counter as u8,
+        }
+    }
+}
+
+impl From<&SubSecondPunch> for SiPunch {
+    fn from(punch: &SubSecondPunch) -> Self {
+        Self {
+            code: 0,
+            time_ms: time_ms(punch.time),
+            day_of_week: punch.day_of_week as u8,
+            week_counter: punch.week_counter as u8,
+        }
+    }
+}
+
+impl From<&StartOrFinishPunch> for SiPunch {
+    fn from(punch: &StartOrFinishPunch) -> Self {
+        match punch {
+            StartOrFinishPunch::Normal(punch) => punch.into(),
+            StartOrFinishPunch::SubSecond(punch) => punch.into(),
+        }
+    }
+}
+
+impl From<&CardReadout> for SiCardReadout {
+    fn from(readout: &CardReadout) -> Self {
+        let punches = readout
+            .punches
+            .iter()
+            .map(SiPunch::from)
+            .collect::<Box<[_]>>();
+        let punch_count = punches.len();
+        Self {
+            card_number: readout.card_number,
+            card_type: readout.card_type.into(),
+            has_start: readout.start.is_some(),
+            start: readout
+                .start
+                .as_ref()
+                .map(SiPunch::from)
+                .unwrap_or_default(),
+            has_finish: readout.finish.is_some(),
+            finish: readout
+                .finish
+                .as_ref()
+                .map(SiPunch::from)
+                .unwrap_or_default(),
+            has_check: readout.check.is_some(),
+            check: readout
+                .check
+                .as_ref()
+                .map(SiPunch::from)
+                .unwrap_or_default(),
+            punches: Box::into_raw(punches).cast(),
+            punch_count,
+        }
+    }
+}
+
+impl From<&CardOwnerData> for SiCardOwnerData {
+    fn from(owner_data: &CardOwnerData) -> Self {
+        let optional = |value: &Option<String>| value.as_deref().map_or(ptr::null_mut(), c_string);
+        Self {
+            first_name: c_string(&owner_data.first_name),
+            last_name: c_string(&owner_data.last_name),
+            gender: optional(&owner_data.gender),
+            birthday: optional(&owner_data.birthday),
+            club: optional(&owner_data.club),
+            email: optional(&owner_data.email),
+            phone: optional(&owner_data.phone),
+            city: optional(&owner_data.city),
+            street: optional(&owner_data.street),
+            zip: optional(&owner_data.zip),
+            country: optional(&owner_data.country),
+        }
+    }
+}
+
+impl From<&CardPunch> for SiCardPunch {
+    fn from(punch: &CardPunch) -> Self {
+        Self {
+            card_number: punch.card.number,
+            card_type: punch.card.card_type.into(),
+            station_code: punch.station_code,
+            punch: SiPunch {
+                code: punch.station_code,
+                ..(&punch.punch).into()
+            },
+            source: match punch.source {
+                PunchSource::Direct => SiPunchSource::Direct,
+                PunchSource::Radio(_) => SiPunchSource::Radio,
+                PunchSource::Backup => SiPunchSource::Backup,
+            },
+        }
+    }
+}
+
+fn time_ms(time: NaiveTime) -> u32 {
+    time.num_seconds_from_midnight() * 1000 + time.nanosecond() / 1_000_000
+}
+
+/// A C string, without the NUL characters `value` may contain.
+fn c_string(value: &str) -> *mut c_char {
+    CString::new(value.replace('\0', ""))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// # Safety
+/// `value` must be null or returned by [`c_string`], and not used afterwards.
+unsafe fn free_c_string(value: &mut *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(*value));
+        *value = ptr::null_mut();
+    }
+}
+
+/// Frees the punches of a readout filled in by this library, leaving it empty.
+///
+/// # Safety
+/// `readout` must be null or point to a readout filled in by this library.
+#[no_mangle]
+pub unsafe extern "C" fn si_card_readout_free(readout: *mut SiCardReadout) {
+    let Some(readout) = readout.as_mut() else {
+        return;
+    };
+    if !readout.punches.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            readout.punches,
+            readout.punch_count,
+        )));
+    }
+    readout.punches = ptr::null_mut();
+    readout.punch_count = 0;
+}
+
+/// Frees the strings of owner data filled in by this library, setting them to null.
+///
+/// # Safety
+/// `owner_data` must be null or point to owner data filled in by this library.
+#[no_mangle]
+pub unsafe extern "C" fn si_card_owner_data_free(owner_data: *mut SiCardOwnerData) {
+    let Some(owner_data) = owner_data.as_mut() else {
+        return;
+    };
+    for value in [
+        &mut owner_data.first_name,
+        &mut owner_data.last_name,
+        &mut owner_data.gender,
+        &mut owner_data.birthday,
+        &mut owner_data.club,
+        &mut owner_data.email,
+        &mut owner_data.phone,
+        &mut owner_data.city,
+        &mut owner_data.street,
+        &mut owner_data.zip,
+        &mut owner_data.country,
+    ] {
+        free_c_string(value);
+    }
+}
@@ -0,0 +1,185 @@
+/* Reads cards and punches from simulated stations through the C API. */
+#define _POSIX_C_SOURCE 199309L
+/* The library is built with the `simulator` feature. */
+#define SPORTIDENT_SIMULATOR
+
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+
+#include "sportident.h"
+
+#define CHECK(condition)                                                     \
+    do {                                                                     \
+        if (!(condition)) {                                                  \
+            fprintf(stderr, "%s:%d: check failed: %s (last error: %s)\n",    \
+                    __FILE__, __LINE__, #condition, last_error());           \
+            return 1;                                                        \
+        }                                                                    \
+    } while (0)
+
+#define SI8_CARD_NUMBER 2071338
+#define STATION_MODE_CONTROL 0x02
+#define STATION_MODE_READOUT 0x05
+
+static const uint8_t SI8_IMAGE[] = {
+    0x05, 0x76, 0x0c, 0x87, 0xea, 0xea, 0xea, 0xea, 0x1a, 0x01, 0xa2, 0x3a, 0x2a, 0x01, 0xa2, 0x33,
+    0x1a, 0x01, 0xa2, 0x44, 0x00, 0x00, 0x00, 0xb3, 0x02, 0x1f, 0x9b, 0x2a, 0x0c, 0xff, 0xcb, 0xfe,
+    0x44, 0x61, 0x66, 0x6e, 0x61, 0x3b, 0x59, 0x6f, 0x67, 0x65, 0x76, 0x3b, 0x41, 0x50, 0x48, 0x4e,
+    0x41, 0x3b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+    0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee, 0xee,
+};
+
+static const char *last_error(void) {
+    const char *message = si_last_error_message();
+    return message ? message : "none";
+}
+
+/* Waits up to a second for the station to receive `beeps` beep commands in total. */
+static bool wait_for_beeps(const SiSimulator *station, size_t beeps) {
+    const struct timespec millisecond = {.tv_sec = 0, .tv_nsec = 1000000};
+    for (int i = 0; i < 1000 && si_simulator_beeps(station) < beeps; ++i) {
+        nanosleep(&millisecond, NULL);
+    }
+    return si_simulator_beeps(station) == beeps;
+}
+
+struct Listener {
+    SiSimulator *station;
+    int cards;
+};
+
+static bool on_card(SiReader *reader, const SiCardReadout *readout, void *user_data) {
+    struct Listener *listener = user_data;
+    if (readout->card_number != SI8_CARD_NUMBER ||
+        si_reader_beep_until_card_removed(reader) != SI_STATUS_OK) {
+        return false;
+    }
+    if (!wait_for_beeps(listener->station, (size_t)++listener->cards + 1) ||
+        listener->cards == 2) {
+        return false;
+    }
+    return si_simulator_insert_card(listener->station, SI8_CARD_NUMBER, SI8_IMAGE,
+                                    sizeof SI8_IMAGE) == SI_STATUS_OK;
+}
+
+static bool on_punch(SiReader *reader, const SiCardPunch *punch, void *user_data) {
+    SiCardPunch *punches = user_data;
+    (void)reader;
+    punches[punch->card_number == SI8_CARD_NUMBER ? 0 : 1] = *punch;
+    return punch->card_number == SI8_CARD_NUMBER;
+}
+
+static int readout(void) {
+    SiSimulator *station = NULL;
+    SiReader *reader = NULL;
+    CHECK(si_simulator_connect(STATION_MODE_READOUT, 10, &station, &reader) == SI_STATUS_OK);
+
+    CHECK(si_simulator_insert_card(station, SI8_CARD_NUMBER, SI8_IMAGE, sizeof SI8_IMAGE) ==
+          SI_STATUS_OK);
+    SiCardReadout card;
+    SiCardOwnerData owner_data;
+    CHECK(si_reader_poll_card_with_owner_data(reader, &card, &owner_data) == SI_STATUS_OK);
+    CHECK(card.card_number == SI8_CARD_NUMBER);
+    CHECK(card.card_type == SI_CARD_TYPE_SI8);
+    CHECK(card.punch_count == 0 || card.punches != NULL);
+    for (size_t i = 0; i < card.punch_count; ++i) {
+        CHECK(card.punches[i].time_ms < 24 * 60 * 60 * 1000);
+        CHECK(card.punches[i].day_of_week < 7);
+    }
+    CHECK(owner_data.first_name != NULL && owner_data.last_name != NULL);
+    CHECK(strlen(owner_data.first_name) > 0);
+    si_card_readout_free(&card);
+    CHECK(card.punches == NULL && card.punch_count == 0);
+    si_card_owner_data_free(&owner_data);
+    CHECK(owner_data.first_name == NULL);
+
+    CHECK(si_reader_beep_until_card_removed(reader) == SI_STATUS_OK);
+    CHECK(wait_for_beeps(station, 1));
+
+    struct Listener listener = {.station = station, .cards = 0};
+    CHECK(si_simulator_insert_card(station, SI8_CARD_NUMBER, SI8_IMAGE, sizeof SI8_IMAGE) ==
+          SI_STATUS_OK);
+    CHECK(si_reader_listen_cards(reader, on_card, &listener) == SI_STATUS_OK);
+    CHECK(listener.cards == 2);
+    CHECK(wait_for_beeps(station, 3));
+
+    si_reader_free(reader);
+    si_simulator_free(station);
+    return 0;
+}
+
+static int punches(void) {
+    SiSimulator *station = NULL;
+    SiReader *reader = NULL;
+    CHECK(si_simulator_connect(STATION_MODE_CONTROL, 31, &station, &reader) == SI_STATUS_OK);
+
+    SiCardPunch sent = {
+        .card_number = 8001234,
+        .station_code = 31,
+        .punch = {.time_ms = (10 * 60 + 4) * 60 * 1000 + 500, .day_of_week = 5},
+    };
+    CHECK(si_simulator_punch(station, &sent) == SI_STATUS_OK);
+    CHECK(si_reader_poll_punch(reader, &sent) == SI_STATUS_OK);
+    CHECK(sent.card_number == 8001234);
+    CHECK(sent.card_type == SI_CARD_TYPE_SIAC);
+    CHECK(sent.station_code == 31 && sent.punch.code == 31);
+    /* Sub-second punch times have a resolution of 1/256 seconds. */
+    CHECK(sent.punch.time_ms >= (10 * 60 + 4) * 60 * 1000 + 500);
+    CHECK(sent.punch.time_ms < (10 * 60 + 4) * 60 * 1000 + 504);
+    CHECK(sent.punch.day_of_week == 5 && sent.punch.week_counter == 0);
+    CHECK(sent.source == SI_PUNCH_SOURCE_DIRECT);
+
+    SiCardPunch received[2];
+    sent.card_number = SI8_CARD_NUMBER;
+    CHECK(si_simulator_punch(station, &sent) == SI_STATUS_OK);
+    sent.card_number = 8001234;
+    CHECK(si_simulator_punch(station, &sent) == SI_STATUS_OK);
+    CHECK(si_reader_listen_punches(reader, on_punch, received) == SI_STATUS_OK);
+    CHECK(received[1].card_number == 8001234);
+    CHECK(received[0].card_number == SI8_CARD_NUMBER);
+
+    si_reader_free(reader);
+    si_simulator_free(station);
+    return 0;
+}
+
+static int errors(void) {
+    SiSimulator *station = NULL;
+    SiReader *reader = NULL;
+    CHECK(si_simulator_connect(STATION_MODE_READOUT, 10, &station, NULL) ==
+          SI_STATUS_NULL_POINTER);
+    CHECK(si_simulator_connect(0xff, 10, &station, &reader) == SI_STATUS_INVALID_ARGUMENT);
+    CHECK(si_reader_poll_card(NULL, NULL) == SI_STATUS_NULL_POINTER);
+    CHECK(si_reader_open("/nonexistent/port", &reader) != SI_STATUS_OK);
+    CHECK(reader == NULL);
+    CHECK(si_last_error_message() != NULL);
+
+    CHECK(si_simulator_connect(STATION_MODE_READOUT, 10, &station, &reader) == SI_STATUS_OK);
+    CHECK(si_simulator_insert_card(station, 42, SI8_IMAGE, sizeof SI8_IMAGE) ==
+          SI_STATUS_INVALID_CARD_NUMBER);
+    CHECK(strcmp(si_status_description(SI_STATUS_INVALID_CARD_NUMBER), "Invalid card number") ==
+          0);
+    si_reader_free(reader);
+    si_simulator_free(station);
+    return 0;
+}
+
+int main(void) {
+    if (readout() || punches() || errors()) {
+        return 1;
+    }
+    printf("ok\n");
+    return 0;
+}
@@ -0,0 +1,52 @@
+#![allow(clippy::pedantic)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The directory the library was built to along with this test. Unlike the copy in its parent
+/// directory, it was built with the features of the test.
+fn library_directory() -> PathBuf {
+    let executable = std::env::current_exe().unwrap();
+    executable.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn simulator() {
+    let manifest_directory = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library_directory = library_directory();
+    let executable =
+        std::env::temp_dir().join(format!("sportident-ffi-simulator-{}", std::process::id()));
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_directory.join("include"))
+        .arg(manifest_directory.join("tests/c/simulator.c"))
+        .arg("-L")
+        .arg(&library_directory)
+        .arg(format!("-Wl,-rpath,{}", library_directory.display()))
+        .arg("-lsportident_ffi")
+        .arg("-o")
+        .arg(&executable)
+        .status()
+        .unwrap();
+    assert!(status.success(), "Compiling the C program failed");
+
+    // Cargo puts its own target directory on the library path, which takes precedence over the
+    // rpath, so the copy built with other features would be loaded.
+    let output = Command::new(&executable)
+        .env("LD_LIBRARY_PATH", &library_directory)
+        .env("DYLD_LIBRARY_PATH", &library_directory)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&executable).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
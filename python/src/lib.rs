@@ -0,0 +1,45 @@
+//! Python bindings for the `sportident` crate, built with maturin.
+//!
+//! The reader's methods return awaitables, driven by a tokio runtime shared by the module, so
+//! scripts read cards from asyncio. Card images read by the reader or loaded from an archive
+//! are decoded offline through `CardImage`.
+#![warn(clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::multiple_crate_versions)]
+#![allow(clippy::missing_errors_doc)]
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+mod reader;
+mod simulator;
+mod types;
+
+create_exception!(
+    sportident,
+    SportIdentError,
+    PyException,
+    "An error reading from a station or decoding a card."
+);
+
+#[allow(clippy::needless_pass_by_value)]
+fn to_py_err(error: sportident::Error) -> PyErr {
+    SportIdentError::new_err(error.to_string())
+}
+
+#[pymodule]
+#[pyo3(name = "sportident")]
+fn sportident_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("SportIdentError", m.py().get_type::<SportIdentError>())?;
+    m.add_class::<reader::Reader>()?;
+    m.add_class::<simulator::SimulatedStation>()?;
+    m.add_class::<types::CardImage>()?;
+    m.add_class::<types::CardOwnerData>()?;
+    m.add_class::<types::CardPunch>()?;
+    m.add_class::<types::CardReadout>()?;
+    m.add_class::<types::CardType>()?;
+    m.add_class::<types::Punch>()?;
+    m.add_class::<types::StationMode>()?;
+    m.add_class::<types::SystemConfiguration>()?;
+    Ok(())
+}
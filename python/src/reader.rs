@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+
+use crate::to_py_err;
+use crate::types::{CardImage, CardOwnerData, CardPunch, CardReadout, SystemConfiguration};
+
+/// A connected station. The polling methods are coroutines, to be awaited from asyncio.
+#[pyclass(frozen, module = "sportident")]
+pub struct Reader {
+    reader: Arc<tokio::sync::Mutex<sportident::Reader>>,
+    system_configuration: Arc<Mutex<SystemConfiguration>>,
+}
+
+impl Reader {
+    pub fn new(reader: sportident::Reader) -> Self {
+        let system_configuration = reader.system_configuration().into();
+        Self {
+            reader: Arc::new(tokio::sync::Mutex::new(reader)),
+            system_configuration: Arc::new(Mutex::new(system_configuration)),
+        }
+    }
+}
+
+/// Runs `call` on the reader once previous calls completed, as an awaitable.
+macro_rules! with_reader {
+    ($self:ident, $py:ident, |$reader:ident| $call:expr) => {{
+        let reader = Arc::clone(&$self.reader);
+        future_into_py($py, async move {
+            let mut guard = reader.lock().await;
+            let $reader = &mut *guard;
+            let result = $call.await.map_err(to_py_err);
+            drop(guard);
+            result
+        })
+    }};
+}
+
+#[pymethods]
+impl Reader {
+    /// Connects to the station on a serial port, such as "/dev/ttyUSB0" or "COM3".
+    #[staticmethod]
+    fn connect(py: Python<'_>, path: String) -> PyResult<Bound<'_, PyAny>> {
+        future_into_py(py, async move {
+            sportident::Reader::connect(path)
+                .await
+                .map(Self::new)
+                .map_err(to_py_err)
+        })
+    }
+
+    /// Connects to the first station found on any serial port.
+    #[staticmethod]
+    fn auto_connect(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+        future_into_py(py, async move {
+            sportident::Reader::auto_connect()
+                .await
+                .map(Self::new)
+                .map_err(to_py_err)
+        })
+    }
+
+    #[staticmethod]
+    fn available_ports() -> PyResult<Vec<String>> {
+        sportident::Reader::available_ports().map_err(to_py_err)
+    }
+
+    /// The system configuration read when connecting or last refreshing.
+    #[getter]
+    fn system_configuration(&self) -> SystemConfiguration {
+        self.system_configuration
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn refresh_system_configuration<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let system_configuration = Arc::clone(&self.system_configuration);
+        with_reader!(self, py, |reader| async move {
+            let configuration =
+                SystemConfiguration::from(reader.refresh_system_configuration().await?);
+            *system_configuration
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = configuration.clone();
+            Ok(configuration)
+        })
+    }
+
+    /// Waits for a card to be inserted and reads its punches.
+    fn poll_card<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_reader!(self, py, |reader| async move {
+            reader
+                .poll_card()
+                .await
+                .map(|readout| CardReadout::from(&readout))
+        })
+    }
+
+    /// Waits for a card to be inserted and reads its owner data.
+    fn poll_owner_data<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_reader!(self, py, |reader| async move {
+            reader
+                .poll_owner_data()
+                .await
+                .map(|owner_data| CardOwnerData::from(&owner_data))
+        })
+    }
+
+    /// Waits for a card to be inserted and reads its punches and owner data, as a tuple.
+    fn poll_card_with_owner_data<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_reader!(self, py, |reader| async move {
+            reader
+                .poll_card_with_owner_data()
+                .await
+                .map(|(readout, owner_data)| {
+                    (
+                        CardReadout::from(&readout),
+                        CardOwnerData::from(&owner_data),
+                    )
+                })
+        })
+    }
+
+    /// Waits for a card to be inserted and reads its raw blocks, to be archived or decoded.
+    fn poll_image<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_reader!(self, py, |reader| async move {
+            reader
+                .poll_readout::<sportident::CardImage>()
+                .await
+                .map(|readout| CardImage::from(readout.data))
+        })
+    }
+
+    /// Waits for the next punch, the station must be in auto send mode.
+    fn poll_punch<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_reader!(self, py, |reader| async move {
+            reader
+                .poll_punch()
+                .await
+                .map(|punch| CardPunch::from(&punch))
+        })
+    }
+
+    fn beep_until_card_removed<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_reader!(self, py, |reader| reader.beep_until_card_removed())
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveTime;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3_async_runtimes::tokio::future_into_py;
+use sportident::{Card, DayOfWeek, PunchSource, SubSecondPunch, WeekCounter};
+
+use crate::reader::Reader;
+use crate::to_py_err;
+use crate::types::{CardImage, StationMode};
+
+/// A station in memory, for trying out and testing scripts without hardware.
+#[pyclass(frozen, module = "sportident")]
+pub struct SimulatedStation {
+    station: sportident::simulator::SimulatedStation,
+}
+
+#[pymethods]
+impl SimulatedStation {
+    /// Starts a station and connects a reader to it, returning both as a tuple.
+    #[staticmethod]
+    fn connect(py: Python<'_>, mode: StationMode, station_code: u16) -> PyResult<Bound<'_, PyAny>> {
+        future_into_py(py, async move {
+            let (station, reader) =
+                sportident::simulator::SimulatedStation::connect(mode.into(), station_code)
+                    .await
+                    .map_err(to_py_err)?;
+            Ok((Self { station }, Reader::new(reader)))
+        })
+    }
+
+    /// Inserts a card, whose blocks are read from `image`.
+    fn insert_card(&self, card_number: u32, image: CardImage) {
+        self.station.insert_card(card_number, image.into());
+    }
+
+    fn remove_card(&self) {
+        self.station.remove_card();
+    }
+
+    /// Sends a punch, as a station in auto send mode does.
+    #[pyo3(signature = (card_number, station_code, time, day_of_week = 0, week_counter = 0))]
+    fn punch(
+        &self,
+        card_number: u32,
+        station_code: u16,
+        time: NaiveTime,
+        day_of_week: u8,
+        week_counter: u8,
+    ) -> PyResult<()> {
+        let card = Card::new(card_number).map_err(|error| to_py_err(error.into()))?;
+        let day_of_week = DayOfWeek::from_repr(day_of_week)
+            .ok_or_else(|| PyValueError::new_err("day_of_week must be 0 to 6"))?;
+        let week_counter = WeekCounter::from_repr(week_counter)
+            .ok_or_else(|| PyValueError::new_err("week_counter must be 0 to 3"))?;
+        self.station.punch(sportident::CardPunch {
+            punch: SubSecondPunch {
+                time,
+                day_of_week,
+                week_counter,
+            },
+            card,
+            station_code,
+            source: PunchSource::Direct,
+            backup_address: None,
+        });
+        Ok(())
+    }
+
+    /// The number of beep commands received.
+    #[getter]
+    fn beeps(&self) -> usize {
+        self.station.beeps()
+    }
+}
@@ -0,0 +1,386 @@
+use std::borrow::Cow;
+
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use futures::executor::block_on;
+use pyo3::prelude::*;
+use sportident::{FromCardBlocks, StartOrFinishPunch};
+
+use crate::to_py_err;
+
+#[pyclass(eq, eq_int, frozen, module = "sportident")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CardType {
+    #[pyo3(name = "SI8")]
+    Si8,
+    #[pyo3(name = "SI9")]
+    Si9,
+    #[pyo3(name = "SI10")]
+    Si10,
+    #[pyo3(name = "SI11")]
+    Si11,
+    #[pyo3(name = "SIAC")]
+    Siac,
+    #[pyo3(name = "PUNCH_CARD")]
+    PunchCard,
+}
+
+#[pyclass(eq, eq_int, frozen, module = "sportident")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StationMode {
+    #[pyo3(name = "SIAC_SPECIAL")]
+    SiacSpecial,
+    #[pyo3(name = "CONTROL")]
+    Control,
+    #[pyo3(name = "START")]
+    Start,
+    #[pyo3(name = "FINISH")]
+    Finish,
+    #[pyo3(name = "READOUT")]
+    Readout,
+    #[pyo3(name = "CLEAR_OLD")]
+    ClearOld,
+    #[pyo3(name = "CLEAR")]
+    Clear,
+    #[pyo3(name = "CHECK")]
+    Check,
+    #[pyo3(name = "PRINT_OUT")]
+    PrintOut,
+    #[pyo3(name = "START_TRIGGER")]
+    StartTrigger,
+    #[pyo3(name = "FINISH_TRIGGER")]
+    FinishTrigger,
+    #[pyo3(name = "BEACON_CONTROL")]
+    BeaconControl,
+    #[pyo3(name = "BEACON_START")]
+    BeaconStart,
+    #[pyo3(name = "BEACON_FINISH")]
+    BeaconFinish,
+    #[pyo3(name = "BEACON_READOUT")]
+    BeaconReadout,
+}
+
+/// A punch stored on a card. `code` is `None` for sub-second start and finish punches, which
+/// don't record it. `day_of_week` is 0 for Monday to 6 for Sunday, like `date.weekday()`.
+#[pyclass(eq, frozen, get_all, module = "sportident")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Punch {
+    pub code: Option<u16>,
+    pub time: NaiveTime,
+    pub day_of_week: u8,
+    pub week_counter: u8,
+}
+
+#[pyclass(eq, frozen, get_all, module = "sportident")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CardReadout {
+    pub card_number: u32,
+    pub card_type: CardType,
+    pub start: Option<Punch>,
+    pub finish: Option<Punch>,
+    pub check: Option<Punch>,
+    pub punches: Vec<Punch>,
+}
+
+#[pyclass(eq, frozen, get_all, module = "sportident")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CardOwnerData {
+    pub first_name: String,
+    pub last_name: String,
+    pub gender: Option<String>,
+    pub birthday: Option<String>,
+    pub club: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub city: Option<String>,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub country: Option<String>,
+}
+
+/// A punch sent by a station in auto send mode or received by an SRR dongle. `source` is
+/// "direct", "radio" or "backup".
+#[pyclass(eq, frozen, get_all, module = "sportident")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CardPunch {
+    pub card_number: u32,
+    pub card_type: CardType,
+    pub station_code: u16,
+    pub time: NaiveTime,
+    pub day_of_week: u8,
+    pub week_counter: u8,
+    pub source: &'static str,
+}
+
+/// The raw blocks of a card, which can be archived and decoded later.
+#[pyclass(frozen, module = "sportident")]
+#[derive(Debug, Clone)]
+pub struct CardImage {
+    image: sportident::CardImage,
+}
+
+/// The system configuration of a station. `model`, `mode` and `srr_channel` are the names of
+/// the values, e.g. "BSM7RS232", "Readout" and "Red".
+#[pyclass(frozen, get_all, module = "sportident")]
+#[derive(Debug, Clone)]
+pub struct SystemConfiguration {
+    pub serial_number: u32,
+    pub firmware: String,
+    pub build_date: NaiveDate,
+    pub model: String,
+    pub mem_kilobytes: u8,
+    pub battery_date: NaiveDate,
+    pub battery_capacity_milliampere_hour: u16,
+    pub used_battery_capacity_percentage: f64,
+    pub battery_voltage: f64,
+    pub memory_overflow: bool,
+    pub srr_channel: String,
+    pub mode: String,
+    pub station_code: u16,
+    pub extended_protocol: bool,
+    pub auto_send: bool,
+    pub wakeup_date: NaiveDate,
+    pub active_duration: TimeDelta,
+}
+
+#[pymethods]
+impl Punch {
+    fn __repr__(&self) -> String {
+        format!(
+            "Punch(code={}, time={})",
+            self.code
+                .map_or_else(|| "None".to_string(), |code| code.to_string()),
+            self.time
+        )
+    }
+}
+
+#[pymethods]
+impl CardReadout {
+    fn __repr__(&self) -> String {
+        format!(
+            "CardReadout(card_number={}, card_type={:?}, punches={})",
+            self.card_number,
+            self.card_type,
+            self.punches.len()
+        )
+    }
+}
+
+#[pymethods]
+impl CardPunch {
+    fn __repr__(&self) -> String {
+        format!(
+            "CardPunch(card_number={}, station_code={}, time={})",
+            self.card_number, self.station_code, self.time
+        )
+    }
+}
+
+#[pymethods]
+impl CardImage {
+    #[new]
+    fn new(card_type: CardType, data: Vec<u8>) -> Self {
+        Self {
+            image: sportident::CardImage {
+                card_type: card_type.into(),
+                data,
+            },
+        }
+    }
+
+    #[getter]
+    fn card_type(&self) -> CardType {
+        self.image.card_type.into()
+    }
+
+    #[getter]
+    fn data(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.image.data)
+    }
+
+    /// Decodes the punches of the card.
+    fn readout(&self) -> PyResult<CardReadout> {
+        self.decode::<sportident::CardReadout>()
+            .map(|readout| (&readout).into())
+    }
+
+    /// Decodes the owner data of the card.
+    fn owner_data(&self) -> PyResult<CardOwnerData> {
+        self.decode::<sportident::CardOwnerData>()
+            .map(|owner_data| (&owner_data).into())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "CardImage(card_type={:?}, {} bytes)",
+            self.image.card_type,
+            self.image.data.len()
+        )
+    }
+}
+
+impl CardImage {
+    fn decode<T: FromCardBlocks>(&self) -> PyResult<T> {
+        let mut image = self.image.clone();
+        block_on(T::from_card_blocks(&mut image, self.image.card_type)).map_err(to_py_err)
+    }
+}
+
+impl From<sportident::CardImage> for CardImage {
+    fn from(image: sportident::CardImage) -> Self {
+        Self { image }
+    }
+}
+
+impl From<CardImage> for sportident::CardImage {
+    fn from(image: CardImage) -> Self {
+        image.image
+    }
+}
+
+impl From<sportident::CardType> for CardType {
+    fn from(card_type: sportident::CardType) -> Self {
+        match card_type {
+            sportident::CardType::Si8 => Self::Si8,
+            sportident::CardType::Si9 => Self::Si9,
+            sportident::CardType::Si10 => Self::Si10,
+            sportident::CardType::Si11 => Self::Si11,
+            sportident::CardType::Siac => Self::Siac,
+            sportident::CardType::PunchCard => Self::PunchCard,
+        }
+    }
+}
+
+impl From<CardType> for sportident::CardType {
+    fn from(card_type: CardType) -> Self {
+        match card_type {
+            CardType::Si8 => Self::Si8,
+            CardType::Si9 => Self::Si9,
+            CardType::Si10 => Self::Si10,
+            CardType::Si11 => Self::Si11,
+            CardType::Siac => Self::Siac,
+            CardType::PunchCard => Self::PunchCard,
+        }
+    }
+}
+
+impl From<StationMode> for sportident::StationMode {
+    fn from(mode: StationMode) -> Self {
+        match mode {
+            StationMode::SiacSpecial => Self::SIACSpecial,
+            StationMode::Control => Self::Control,
+            StationMode::Start => Self::Start,
+            StationMode::Finish => Self::Finish,
+            StationMode::Readout => Self::Readout,
+            StationMode::ClearOld => Self::ClearOld,
+            StationMode::Clear => Self::Clear,
+            StationMode::Check => Self::Check,
+            StationMode::PrintOut => Self::PrintOut,
+            StationMode::StartTrigger => Self::StartTrigger,
+            StationMode::FinishTrigger => Self::FinishTrigger,
+            StationMode::BeaconControl => Self::BeaconControl,
+            StationMode::BeaconStart => Self::BeaconStart,
+            StationMode::BeaconFinish => Self::BeaconFinish,
+            StationMode::BeaconReadout => Self::BeaconReadout,
+        }
+    }
+}
+
+impl From<&sportident::Punch> for Punch {
+    fn from(punch: &sportident::Punch) -> Self {
+        Self {
+            code: Some(punch.code),
+            time: punch.time,
+            day_of_week: punch.day_of_week as u8,
+            week_counter: punch.week_counter as u8,
+        }
+    }
+}
+
+impl From<&StartOrFinishPunch> for Punch {
+    fn from(punch: &StartOrFinishPunch) -> Self {
+        match punch {
+            StartOrFinishPunch::Normal(punch) => punch.into(),
+            StartOrFinishPunch::SubSecond(punch) => Self {
+                code: None,
+                time: punch.time,
+                day_of_week: punch.day_of_week as u8,
+                week_counter: punch.week_counter as u8,
+            },
+        }
+    }
+}
+
+impl From<&sportident::CardReadout> for CardReadout {
+    fn from(readout: &sportident::CardReadout) -> Self {
+        Self {
+            card_number: readout.card_number,
+            card_type: readout.card_type.into(),
+            start: readout.start.as_ref().map(Punch::from),
+            finish: readout.finish.as_ref().map(Punch::from),
+            check: readout.check.as_ref().map(Punch::from),
+            punches: readout.punches.iter().map(Punch::from).collect(),
+        }
+    }
+}
+
+impl From<&sportident::CardOwnerData> for CardOwnerData {
+    fn from(owner_data: &sportident::CardOwnerData) -> Self {
+        let owner_data = owner_data.clone();
+        Self {
+            first_name: owner_data.first_name,
+            last_name: owner_data.last_name,
+            gender: owner_data.gender,
+            birthday: owner_data.birthday,
+            club: owner_data.club,
+            email: owner_data.email,
+            phone: owner_data.phone,
+            city: owner_data.city,
+            street: owner_data.street,
+            zip: owner_data.zip,
+            country: owner_data.country,
+        }
+    }
+}
+
+impl From<&sportident::CardPunch> for CardPunch {
+    fn from(punch: &sportident::CardPunch) -> Self {
+        Self {
+            card_number: punch.card.number,
+            card_type: punch.card.card_type.into(),
+            station_code: punch.station_code,
+            time: punch.punch.time,
+            day_of_week: punch.punch.day_of_week as u8,
+            week_counter: punch.punch.week_counter as u8,
+            source: match punch.source {
+                sportident::PunchSource::Direct => "direct",
+                sportident::PunchSource::Radio(_) => "radio",
+                sportident::PunchSource::Backup => "backup",
+            },
+        }
+    }
+}
+
+impl From<&sportident::SystemConfiguration> for SystemConfiguration {
+    fn from(configuration: &sportident::SystemConfiguration) -> Self {
+        Self {
+            serial_number: configuration.serial_number,
+            firmware: String::from_utf8_lossy(&configuration.firmware).into_owned(),
+            build_date: configuration.build_date,
+            model: format!("{:?}", configuration.model),
+            mem_kilobytes: configuration.mem_kilobytes,
+            battery_date: configuration.battery_date,
+            battery_capacity_milliampere_hour: configuration.battery_capacity_milliampere_hour,
+            used_battery_capacity_percentage: configuration.used_battery_capacity_percentage,
+            battery_voltage: configuration.battery_voltage,
+            memory_overflow: configuration.memory_overflow,
+            srr_channel: format!("{:?}", configuration.srr_channel),
+            mode: format!("{:?}", configuration.mode),
+            station_code: configuration.station_code,
+            extended_protocol: configuration.protocol_configuration.is_extended_protocol(),
+            auto_send: configuration.protocol_configuration.is_auto_send(),
+            wakeup_date: configuration.wakeup_date,
+            active_duration: configuration.active_duration,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+"""Card images shared by the tests."""
+import sportident
+
+SI8_CARD_NUMBER = 2071338
+
+SI8_IMAGE = bytes.fromhex(
+    "05760c87eaeaeaea1a01a23a2a01a2331a01a244000000b3021f9b2a0cffcbfe"
+    "4461666e613b596f6765763b4150484e413b0000000000000000000000000000"
+    "0000000000000000000000000000000000000000000000000000000000000000"
+    "0000000000000000000000000000000000000000000000000000000000000000"
+    "0000000000000000eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+    "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+    "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+    "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"
+)
+
+
+def si8_image():
+    return sportident.CardImage(sportident.CardType.SI8, SI8_IMAGE)
@@ -0,0 +1,35 @@
+import datetime
+
+import pytest
+
+import sportident
+from cards import SI8_CARD_NUMBER, SI8_IMAGE, si8_image
+
+
+def test_image():
+    image = si8_image()
+    assert image.card_type == sportident.CardType.SI8
+    assert image.data == SI8_IMAGE
+
+
+def test_readout():
+    readout = si8_image().readout()
+    assert readout.card_number == SI8_CARD_NUMBER
+    assert readout.card_type == sportident.CardType.SI8
+    for punch in readout.punches:
+        assert isinstance(punch.code, int)
+        assert isinstance(punch.time, datetime.time)
+        assert 0 <= punch.day_of_week <= 6
+        assert 0 <= punch.week_counter <= 3
+
+
+def test_owner_data():
+    owner_data = si8_image().owner_data()
+    assert owner_data.first_name == "Dafna"
+    assert owner_data.last_name == "Yogev"
+
+
+def test_invalid_image():
+    image = sportident.CardImage(sportident.CardType.SI10, SI8_IMAGE)
+    with pytest.raises(sportident.SportIdentError):
+        image.readout()
@@ -0,0 +1,92 @@
+import asyncio
+import datetime
+
+import sportident
+from cards import SI8_CARD_NUMBER, si8_image
+
+
+async def connect(mode, station_code):
+    return await sportident.SimulatedStation.connect(mode, station_code)
+
+
+async def wait_for_beeps(station, beeps):
+    for _ in range(1000):
+        if station.beeps >= beeps:
+            break
+        await asyncio.sleep(0.001)
+    return station.beeps
+
+
+def test_system_configuration():
+    async def run():
+        _, reader = await connect(sportident.StationMode.READOUT, 300)
+        configuration = reader.system_configuration
+        assert configuration.mode == "Readout"
+        assert configuration.station_code == 300
+        assert configuration.model == "BSM7RS232"
+        assert configuration.extended_protocol
+        assert isinstance(configuration.build_date, datetime.date)
+        assert isinstance(configuration.active_duration, datetime.timedelta)
+
+        refreshed = await reader.refresh_system_configuration()
+        assert refreshed.serial_number == configuration.serial_number
+
+    asyncio.run(run())
+
+
+def test_readout():
+    async def run():
+        station, reader = await connect(sportident.StationMode.READOUT, 10)
+
+        station.insert_card(SI8_CARD_NUMBER, si8_image())
+        readout = await reader.poll_card()
+        assert readout == si8_image().readout()
+        await reader.beep_until_card_removed()
+        assert await wait_for_beeps(station, 1) == 1
+
+        station.insert_card(SI8_CARD_NUMBER, si8_image())
+        readout, owner_data = await reader.poll_card_with_owner_data()
+        assert readout.card_number == SI8_CARD_NUMBER
+        assert owner_data.first_name == "Dafna"
+        await reader.beep_until_card_removed()
+        assert await wait_for_beeps(station, 2) == 2
+
+        station.insert_card(SI8_CARD_NUMBER, si8_image())
+        image = await reader.poll_image()
+        assert image.card_type == sportident.CardType.SI8
+        assert image.data == si8_image().data
+
+    asyncio.run(run())
+
+
+def test_punches():
+    async def run():
+        station, reader = await connect(sportident.StationMode.CONTROL, 31)
+
+        station.punch(8001234, 31, datetime.time(10, 4, 10), day_of_week=5)
+        station.punch(SI8_CARD_NUMBER, 31, datetime.time(10, 5))
+        first = await reader.poll_punch()
+        second = await reader.poll_punch()
+
+        assert first.card_number == 8001234
+        assert first.card_type == sportident.CardType.SIAC
+        assert first.station_code == 31
+        assert first.time == datetime.time(10, 4, 10)
+        assert first.day_of_week == 5
+        assert first.source == "direct"
+        assert second.card_number == SI8_CARD_NUMBER
+
+    asyncio.run(run())
+
+
+def test_polls_run_in_order():
+    async def run():
+        station, reader = await connect(sportident.StationMode.CONTROL, 31)
+        polls = asyncio.gather(reader.poll_punch(), reader.poll_punch())
+
+        station.punch(8001234, 31, datetime.time(10, 4))
+        station.punch(8001235, 31, datetime.time(10, 5))
+        first, second = await polls
+        assert {first.card_number, second.card_number} == {8001234, 8001235}
+
+    asyncio.run(run())
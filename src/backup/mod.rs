@@ -0,0 +1,464 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use crate::protocol::{
+    BackupRecord, Commands, EraseBackupData, Responses, BACKUP_POINTER_HIGH_OFFSET,
+    BACKUP_POINTER_LOW_OFFSET, BACKUP_RECORD_SIZE, MAX_BACKUP_READ_LENGTH, MEMORY_OVERFLOW_OFFSET,
+};
+use crate::{Error, Reader, Result};
+
+/// The address of the first record in the backup memory of a station.
+pub const BACKUP_MEMORY_START: u32 = 0x100;
+
+#[allow(clippy::cast_possible_truncation)]
+pub const RECORD_SIZE: u8 = BACKUP_RECORD_SIZE as u8;
+
+/// The position up to which the backup memory of a station was already read.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BackupCursor {
+    /// The address following the last record that was read.
+    pub address: u32,
+    /// The raw bytes of the last record that was read, used to detect a cleared memory.
+    pub last_record: Option<[u8; BACKUP_RECORD_SIZE]>,
+}
+
+/// Backup cursors of multiple stations, keyed by station serial number.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct BackupCursors {
+    cursors: BTreeMap<u32, BackupCursor>,
+}
+
+/// How the backup memory changed since the previous synchronization.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum BackupSyncKind {
+    /// The station was never synchronized before, the whole memory is read.
+    Initial,
+    /// Only the records written since the previous synchronization are read.
+    Incremental,
+    /// The memory was cleared since the previous synchronization, the whole memory is read.
+    Cleared,
+    /// The memory wrapped around since the previous synchronization.
+    RolledOver,
+}
+
+impl BackupCursors {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load cursors from a file written by [`BackupCursors::save`].
+    ///
+    /// A missing file results in an empty set of cursors.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut cursors = BTreeMap::new();
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (serial_number, cursor) =
+                parse_cursor_line(line).ok_or(Error::InvalidBackupCursorFile(index + 1))?;
+            cursors.insert(serial_number, cursor);
+        }
+
+        Ok(Self { cursors })
+    }
+
+    /// Atomically write the cursors to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        for (serial_number, cursor) in &self.cursors {
+            let _ = write!(content, "{serial_number} {:06x} ", cursor.address);
+            match cursor.last_record {
+                Some(last_record) => last_record.iter().for_each(|byte| {
+                    let _ = write!(content, "{byte:02x}");
+                }),
+                None => content.push('-'),
+            }
+            content.push('\n');
+        }
+
+        let temporary_path = path.with_extension("tmp");
+        std::fs::write(&temporary_path, content)?;
+        std::fs::rename(temporary_path, path)?;
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get(&self, serial_number: u32) -> Option<&BackupCursor> {
+        self.cursors.get(&serial_number)
+    }
+
+    pub fn set(&mut self, serial_number: u32, cursor: BackupCursor) {
+        self.cursors.insert(serial_number, cursor);
+    }
+
+    pub fn remove(&mut self, serial_number: u32) -> Option<BackupCursor> {
+        self.cursors.remove(&serial_number)
+    }
+}
+
+fn parse_cursor_line(line: &str) -> Option<(u32, BackupCursor)> {
+    let mut parts = line.split_whitespace();
+    let serial_number = parts.next()?.parse().ok()?;
+    let address = u32::from_str_radix(parts.next()?, 16).ok()?;
+    let last_record = match parts.next()? {
+        "-" => None,
+        hex if hex.len() == BACKUP_RECORD_SIZE * 2 => {
+            let mut last_record = [0; BACKUP_RECORD_SIZE];
+            for (i, byte) in last_record.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+            }
+            Some(last_record)
+        }
+        _ => return None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some((
+        serial_number,
+        BackupCursor {
+            address,
+            last_record,
+        },
+    ))
+}
+
+/// The address ranges that have to be read to bring a cursor up to date.
+#[allow(clippy::single_range_in_vec_init)]
+fn plan(
+    cursor: Option<&BackupCursor>,
+    last_record_matches: bool,
+    pointer: u32,
+    overflow: bool,
+    end: u32,
+) -> (BackupSyncKind, Vec<Range<u32>>) {
+    let whole_memory = if overflow {
+        vec![pointer..end, BACKUP_MEMORY_START..pointer]
+    } else {
+        vec![BACKUP_MEMORY_START..pointer]
+    };
+
+    let (kind, ranges) = match cursor {
+        None => (BackupSyncKind::Initial, whole_memory),
+        Some(_) if !last_record_matches && overflow => (BackupSyncKind::RolledOver, whole_memory),
+        Some(_) if !last_record_matches => (BackupSyncKind::Cleared, whole_memory),
+        Some(cursor) if cursor.address <= pointer => {
+            (BackupSyncKind::Incremental, vec![cursor.address..pointer])
+        }
+        Some(cursor) if overflow => (
+            BackupSyncKind::RolledOver,
+            vec![cursor.address..end, BACKUP_MEMORY_START..pointer],
+        ),
+        Some(_) => (BackupSyncKind::Cleared, whole_memory),
+    };
+
+    (
+        kind,
+        ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect(),
+    )
+}
+
+/// The records written to the backup memory since the previous synchronization.
+///
+/// The cursor of the station is advanced as records are yielded, so a partially consumed
+/// synchronization resumes where it stopped.
+pub struct BackupSync<'a> {
+    kind: BackupSyncKind,
+    records: Pin<Box<dyn Stream<Item = Result<BackupRecord>> + Send + 'a>>,
+}
+
+impl BackupSync<'_> {
+    #[must_use]
+    pub const fn kind(&self) -> BackupSyncKind {
+        self.kind
+    }
+}
+
+impl Stream for BackupSync<'_> {
+    type Item = Result<BackupRecord>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.records.as_mut().poll_next(cx)
+    }
+}
+
+struct SyncState<'a> {
+    reader: &'a mut Reader,
+    cursors: &'a mut BackupCursors,
+    serial_number: u32,
+    end: u32,
+    ranges: VecDeque<Range<u32>>,
+    pending: VecDeque<(u32, [u8; BACKUP_RECORD_SIZE])>,
+}
+
+impl SyncState<'_> {
+    async fn next(&mut self) -> Result<Option<(u32, [u8; BACKUP_RECORD_SIZE])>> {
+        while self.pending.is_empty() {
+            let Some(range) = self.ranges.front_mut() else {
+                return Ok(None);
+            };
+            let length = (range.end - range.start).min(u32::from(MAX_BACKUP_READ_LENGTH));
+            let data = self
+                .reader
+                .read_backup(range.start, u8::try_from(length).unwrap_or(u8::MAX))
+                .await?;
+            if data.len() != length as usize {
+                return Err(Error::InvalidResponseReceived);
+            }
+            let mut address = range.start;
+            for record in data.chunks_exact(BACKUP_RECORD_SIZE) {
+                self.pending
+                    .push_back((address, record.try_into().expect("exact chunk")));
+                address += u32::from(RECORD_SIZE);
+            }
+            range.start += length;
+            if range.start == range.end {
+                self.ranges.pop_front();
+            }
+        }
+
+        let (address, record) = self.pending.pop_front().expect("not empty");
+        let mut next_address = address + u32::from(RECORD_SIZE);
+        if next_address >= self.end {
+            next_address = BACKUP_MEMORY_START;
+        }
+        self.cursors.set(
+            self.serial_number,
+            BackupCursor {
+                address: next_address,
+                last_record: Some(record),
+            },
+        );
+
+        Ok(Some((address, record)))
+    }
+}
+
+/// How [`Reader::clear_backup`] treats records that were not synchronized yet.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ClearBackupMode {
+    /// Only report how many records would be lost, without clearing the memory.
+    DryRun,
+    /// Clear the memory, unless it holds records that were not synchronized yet.
+    Clear,
+    /// Clear the memory even if it holds records that were not synchronized yet.
+    Force,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ClearBackupReport {
+    /// The number of records that were not synchronized yet, and are lost by clearing.
+    pub unsynchronized_records: u32,
+    pub cleared: bool,
+}
+
+struct BackupPlan {
+    serial_number: u32,
+    end: u32,
+    kind: BackupSyncKind,
+    ranges: Vec<Range<u32>>,
+}
+
+impl Reader {
+    /// Read the backup records written since the cursor of the station stored in `cursors`.
+    ///
+    /// Persist `cursors` with [`BackupCursors::save`] once the returned stream is consumed.
+    pub async fn sync_backup<'a>(
+        &'a mut self,
+        cursors: &'a mut BackupCursors,
+    ) -> Result<BackupSync<'a>> {
+        let BackupPlan {
+            serial_number,
+            end,
+            kind,
+            ranges,
+        } = self.plan_backup_sync(cursors).await?;
+
+        let state = SyncState {
+            reader: self,
+            cursors,
+            serial_number,
+            end,
+            ranges: ranges.into(),
+            pending: VecDeque::new(),
+        };
+        let records = futures::stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+            match state.next().await {
+                Ok(Some((address, record))) => Some((
+                    BackupRecord::decode(address, &record).map_err(Error::from),
+                    Some(state),
+                )),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+
+        Ok(BackupSync {
+            kind,
+            records: Box::pin(records),
+        })
+    }
+
+    /// Read and decode the backup records stored at `addresses`.
+    pub async fn read_backup_records(
+        &mut self,
+        addresses: Range<u32>,
+    ) -> Result<Vec<BackupRecord>> {
+        let mut records = Vec::new();
+        let mut address = addresses.start;
+        while address < addresses.end {
+            let length = (addresses.end - address).min(u32::from(MAX_BACKUP_READ_LENGTH));
+            let data = self
+                .read_backup(address, u8::try_from(length).unwrap_or(u8::MAX))
+                .await?;
+            if data.len() != length as usize {
+                return Err(Error::InvalidResponseReceived);
+            }
+            for record in data.chunks_exact(BACKUP_RECORD_SIZE) {
+                records.push(BackupRecord::decode(address, record)?);
+                address += u32::from(RECORD_SIZE);
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Erase the backup memory of the station.
+    ///
+    /// Unless `mode` is [`ClearBackupMode::Force`], clearing is refused while the memory holds
+    /// records that were not synchronized into `cursors` yet.
+    pub async fn clear_backup(
+        &mut self,
+        cursors: &mut BackupCursors,
+        mode: ClearBackupMode,
+    ) -> Result<ClearBackupReport> {
+        let BackupPlan {
+            serial_number,
+            ranges,
+            ..
+        } = self.plan_backup_sync(cursors).await?;
+        let unsynchronized_records = ranges
+            .iter()
+            .map(|range| (range.end - range.start) / u32::from(RECORD_SIZE))
+            .sum();
+
+        match mode {
+            ClearBackupMode::DryRun => {
+                return Ok(ClearBackupReport {
+                    unsynchronized_records,
+                    cleared: false,
+                })
+            }
+            ClearBackupMode::Clear if unsynchronized_records > 0 => {
+                return Err(Error::BackupNotSynchronized(unsynchronized_records))
+            }
+            ClearBackupMode::Clear | ClearBackupMode::Force => {}
+        }
+
+        let Responses::EraseBackupDataResponse(_) = self
+            .send_and_receive(Commands::EraseBackupData(EraseBackupData))
+            .await?
+        else {
+            return Err(Error::InvalidResponseReceived);
+        };
+        self.refresh_system_configuration().await?;
+        cursors.set(
+            serial_number,
+            BackupCursor {
+                address: BACKUP_MEMORY_START,
+                last_record: None,
+            },
+        );
+
+        Ok(ClearBackupReport {
+            unsynchronized_records,
+            cleared: true,
+        })
+    }
+
+    /// Move the address the station writes its next backup record to.
+    ///
+    /// The address must be the address of a record inside the backup memory.
+    pub async fn set_backup_pointer(&mut self, address: u32) -> Result<()> {
+        let end = self.system_configuration().backup_memory_end();
+        if !(BACKUP_MEMORY_START..end).contains(&address)
+            || !(address - BACKUP_MEMORY_START).is_multiple_of(u32::from(RECORD_SIZE))
+        {
+            return Err(Error::InvalidBackupAddress(address));
+        }
+        let [high_1, high_0, low_1, low_0] = address.to_be_bytes();
+        self.write_system_value(BACKUP_POINTER_HIGH_OFFSET, vec![high_1, high_0])
+            .await?;
+        self.write_system_value(BACKUP_POINTER_LOW_OFFSET, vec![low_1, low_0])
+            .await?;
+        self.refresh_system_configuration().await?;
+
+        Ok(())
+    }
+
+    /// Point the station back to the start of its backup memory and clear the overflow flag,
+    /// without erasing the stored records.
+    pub async fn reset_backup_pointer(&mut self) -> Result<()> {
+        self.write_system_value(MEMORY_OVERFLOW_OFFSET, vec![0])
+            .await?;
+        self.set_backup_pointer(BACKUP_MEMORY_START).await
+    }
+
+    async fn plan_backup_sync(&mut self, cursors: &BackupCursors) -> Result<BackupPlan> {
+        let system_configuration = self.refresh_system_configuration().await?;
+        let serial_number = system_configuration.serial_number;
+        let pointer = system_configuration.backup_pointer();
+        let overflow = system_configuration.memory_overflow;
+        let end = system_configuration.backup_memory_end();
+
+        let cursor = cursors.get(serial_number);
+        let last_record_matches = match cursor {
+            Some(cursor) if cursor.address < BACKUP_MEMORY_START || cursor.address >= end => false,
+            Some(BackupCursor {
+                address,
+                last_record: Some(last_record),
+            }) => {
+                let previous = if *address == BACKUP_MEMORY_START {
+                    end
+                } else {
+                    *address
+                } - u32::from(RECORD_SIZE);
+                self.read_backup(previous, RECORD_SIZE).await? == last_record
+            }
+            _ => true,
+        };
+
+        let (kind, ranges) = plan(cursor, last_record_matches, pointer, overflow, end);
+
+        Ok(BackupPlan {
+            serial_number,
+            end,
+            kind,
+            ranges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
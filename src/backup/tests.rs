@@ -0,0 +1,265 @@
+#![allow(clippy::single_range_in_vec_init)]
+
+use crate::backup::{plan, BackupCursor, BackupCursors, BackupSyncKind, BACKUP_MEMORY_START};
+
+const END: u32 = 128 * 1024;
+
+fn cursor(address: u32) -> BackupCursor {
+    BackupCursor {
+        address,
+        last_record: Some([0x00, 0x1c, 0x46, 0x4c, 0x0d, 0x9c, 0x40, 0x00]),
+    }
+}
+
+#[test]
+fn initial() {
+    assert_eq!(
+        plan(None, true, 0x200, false, END),
+        (BackupSyncKind::Initial, vec![BACKUP_MEMORY_START..0x200])
+    );
+}
+
+#[test]
+fn initial_overflow() {
+    assert_eq!(
+        plan(None, true, 0x200, true, END),
+        (
+            BackupSyncKind::Initial,
+            vec![0x200..END, BACKUP_MEMORY_START..0x200]
+        )
+    );
+}
+
+#[test]
+fn incremental() {
+    assert_eq!(
+        plan(Some(&cursor(0x180)), true, 0x200, false, END),
+        (BackupSyncKind::Incremental, vec![0x180..0x200])
+    );
+}
+
+#[test]
+fn nothing_new() {
+    assert_eq!(
+        plan(Some(&cursor(0x200)), true, 0x200, false, END),
+        (BackupSyncKind::Incremental, vec![])
+    );
+}
+
+#[test]
+fn cleared() {
+    assert_eq!(
+        plan(Some(&cursor(0x400)), true, 0x180, false, END),
+        (BackupSyncKind::Cleared, vec![BACKUP_MEMORY_START..0x180])
+    );
+    assert_eq!(
+        plan(Some(&cursor(0x180)), false, 0x400, false, END),
+        (BackupSyncKind::Cleared, vec![BACKUP_MEMORY_START..0x400])
+    );
+}
+
+#[test]
+fn rolled_over() {
+    assert_eq!(
+        plan(Some(&cursor(END - 0x40)), true, 0x180, true, END),
+        (
+            BackupSyncKind::RolledOver,
+            vec![END - 0x40..END, BACKUP_MEMORY_START..0x180]
+        )
+    );
+}
+
+#[test]
+fn cursors_round_trip() {
+    let path = std::env::temp_dir().join(format!("sportident-cursors-{}", std::process::id()));
+    let mut cursors = BackupCursors::new();
+    cursors.set(501_234, cursor(0x1f8));
+    cursors.set(
+        7,
+        BackupCursor {
+            address: BACKUP_MEMORY_START,
+            last_record: None,
+        },
+    );
+
+    cursors.save(&path).unwrap();
+    let loaded = BackupCursors::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, cursors);
+}
+
+#[test]
+fn missing_cursors_file() {
+    let path = std::env::temp_dir().join("sportident-cursors-missing");
+
+    assert_eq!(BackupCursors::load(path).unwrap(), BackupCursors::new());
+}
+
+#[cfg(feature = "simulator")]
+mod station {
+    use chrono::NaiveTime;
+
+    use crate::backup::{BackupCursors, ClearBackupMode, ClearBackupReport, BACKUP_MEMORY_START};
+    use crate::simulator::tests::direct_punch;
+    use crate::simulator::SimulatedStation;
+    use crate::{Error, Reader, StationMode};
+
+    async fn station_with_punches(count: u32) -> (SimulatedStation, Reader) {
+        let (station, reader) = SimulatedStation::connect(StationMode::Control, 31)
+            .await
+            .unwrap();
+        for i in 0..count {
+            station.store_punch(&direct_punch(
+                7_001_234 + i,
+                31,
+                NaiveTime::from_hms_opt(10, 0, i).unwrap(),
+            ));
+        }
+        (station, reader)
+    }
+
+    #[tokio::test]
+    async fn clear_dry_run() {
+        let (_station, mut reader) = station_with_punches(3).await;
+        let mut cursors = BackupCursors::new();
+
+        let report = reader
+            .clear_backup(&mut cursors, ClearBackupMode::DryRun)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ClearBackupReport {
+                unsynchronized_records: 3,
+                cleared: false,
+            }
+        );
+        let configuration = reader.refresh_system_configuration().await.unwrap();
+        assert_eq!(configuration.backup_pointer(), BACKUP_MEMORY_START + 3 * 8);
+        assert_eq!(cursors, BackupCursors::new());
+    }
+
+    #[tokio::test]
+    async fn clear_refused() {
+        let (_station, mut reader) = station_with_punches(3).await;
+        let mut cursors = BackupCursors::new();
+
+        let result = reader
+            .clear_backup(&mut cursors, ClearBackupMode::Clear)
+            .await;
+        assert!(matches!(result, Err(Error::BackupNotSynchronized(3))));
+        let configuration = reader.refresh_system_configuration().await.unwrap();
+        assert_eq!(configuration.backup_pointer(), BACKUP_MEMORY_START + 3 * 8);
+    }
+
+    #[tokio::test]
+    async fn clear_synchronized() {
+        use futures::TryStreamExt;
+
+        let (_station, mut reader) = station_with_punches(3).await;
+        let mut cursors = BackupCursors::new();
+        let records: Vec<_> = reader
+            .sync_backup(&mut cursors)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 3);
+
+        let report = reader
+            .clear_backup(&mut cursors, ClearBackupMode::Clear)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ClearBackupReport {
+                unsynchronized_records: 0,
+                cleared: true,
+            }
+        );
+        assert_eq!(
+            reader.system_configuration().backup_pointer(),
+            BACKUP_MEMORY_START
+        );
+    }
+
+    #[tokio::test]
+    async fn clear_forced() {
+        let (_station, mut reader) = station_with_punches(3).await;
+        let mut cursors = BackupCursors::new();
+
+        let report = reader
+            .clear_backup(&mut cursors, ClearBackupMode::Force)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            ClearBackupReport {
+                unsynchronized_records: 3,
+                cleared: true,
+            }
+        );
+        let configuration = reader.system_configuration();
+        assert_eq!(configuration.backup_pointer(), BACKUP_MEMORY_START);
+        let cursor = cursors.get(configuration.serial_number).unwrap();
+        assert_eq!(cursor.address, BACKUP_MEMORY_START);
+        assert_eq!(cursor.last_record, None);
+    }
+
+    #[tokio::test]
+    async fn set_backup_pointer() {
+        let (_station, mut reader) = station_with_punches(0).await;
+
+        reader.set_backup_pointer(0x0001_2340).await.unwrap();
+        let configuration = reader.system_configuration();
+        assert_eq!(configuration.backup_pointer_high, 0x0001);
+        assert_eq!(configuration.backup_pointer_low, 0x2340);
+        assert_eq!(configuration.backup_pointer(), 0x0001_2340);
+    }
+
+    #[tokio::test]
+    async fn invalid_backup_pointer() {
+        let (_station, mut reader) = station_with_punches(0).await;
+        let end = reader.system_configuration().backup_memory_end();
+
+        for address in [
+            0,
+            BACKUP_MEMORY_START - 8,
+            BACKUP_MEMORY_START + 4,
+            end,
+            u32::MAX,
+        ] {
+            let result = reader.set_backup_pointer(address).await;
+            assert!(
+                matches!(result, Err(Error::InvalidBackupAddress(a)) if a == address),
+                "{address:#x}"
+            );
+        }
+        assert_eq!(
+            reader.system_configuration().backup_pointer(),
+            BACKUP_MEMORY_START
+        );
+    }
+
+    #[tokio::test]
+    async fn reset_backup_pointer() {
+        let (station, mut reader) = station_with_punches(2).await;
+        let end = reader.system_configuration().backup_memory_end();
+        reader.set_backup_pointer(end - 8).await.unwrap();
+        station.store_punch(&direct_punch(
+            7_001_234,
+            31,
+            NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+        ));
+        let configuration = reader.refresh_system_configuration().await.unwrap();
+        assert!(configuration.memory_overflow);
+        assert_eq!(configuration.backup_pointer(), BACKUP_MEMORY_START);
+
+        reader.reset_backup_pointer().await.unwrap();
+        let configuration = reader.system_configuration();
+        assert!(!configuration.memory_overflow);
+        assert_eq!(configuration.backup_pointer(), BACKUP_MEMORY_START);
+    }
+}
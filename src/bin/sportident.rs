@@ -0,0 +1,299 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use sportident::csv::{ReadoutRecord, ReadoutWriter};
+use sportident::{
+    CardImage, CardOwnerData, CardPunch, CardReadout, Error, PunchSource, PunchTime, Reader,
+    Readout, SRRChannel, StartOrFinishPunch,
+};
+
+#[cfg(test)]
+#[path = "sportident/tests.rs"]
+mod tests;
+
+const PUNCH_COLUMNS: [&str; 4] = ["Station", "SIID", "Time", "Source"];
+const OWNER_COLUMNS: [&str; 4] = ["SIID", "First name", "Last name", "Club"];
+
+/// Everyday station and card work with SportIdent readers.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// The serial port of the reader, the first detected reader is used by default.
+    #[arg(short, long, global = true)]
+    port: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List detected readers with their model and serial number.
+    Ports,
+    /// Print the system configuration of the reader.
+    Info {
+        #[arg(short, long, value_enum, default_value_t = InfoFormat::Text)]
+        format: InfoFormat,
+    },
+    /// Read cards continuously.
+    Readout {
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Follow the punches sent by a station in auto send mode.
+    Punches {
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Read the owner data of cards continuously.
+    Owner {
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Write raw card images to a directory, one `<card number>.bin` file per card.
+    Dump {
+        #[arg(default_value = ".")]
+        directory: PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+/// The configuration is a single nested record, which has no CSV layout.
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq)]
+enum InfoFormat {
+    Text,
+    Json,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> sportident::Result<()> {
+    if let Command::Ports = cli.command {
+        return ports().await;
+    }
+
+    let mut reader = match &cli.port {
+        Some(port) => Reader::connect(port.as_str()).await?,
+        None => Reader::auto_connect().await?,
+    };
+
+    match cli.command {
+        Command::Ports => unreachable!("handled before connecting"),
+        Command::Info { format } => info(&reader, format),
+        Command::Readout { format } => readout(&mut reader, format).await,
+        Command::Punches { format } => punches(&mut reader, format).await,
+        Command::Owner { format } => owner(&mut reader, format).await,
+        Command::Dump { directory } => dump(&mut reader, &directory).await,
+    }
+}
+
+async fn ports() -> sportident::Result<()> {
+    for port in Reader::available_ports()? {
+        match Reader::connect(port.as_str()).await {
+            Ok(reader) => {
+                let configuration = reader.system_configuration();
+                println!(
+                    "{port}\t{:?}\t{}",
+                    configuration.model, configuration.serial_number
+                );
+            }
+            Err(error) => println!("{port}\tunavailable: {error}"),
+        }
+    }
+    Ok(())
+}
+
+fn info(reader: &Reader, format: InfoFormat) -> sportident::Result<()> {
+    match format {
+        InfoFormat::Json => print_json(reader.system_configuration()),
+        InfoFormat::Text => println!("{:#?}", reader.system_configuration()),
+    }
+    Ok(())
+}
+
+async fn readout(reader: &mut Reader, format: Format) -> sportident::Result<()> {
+    let mut csv = match format {
+        Format::Csv => Some(ReadoutWriter::new(std::io::stdout())?),
+        Format::Text | Format::Json => None,
+    };
+
+    loop {
+        let Some(readout) = skip_removed_card(reader.poll_readout::<CardReadout>().await)? else {
+            continue;
+        };
+        match &mut csv {
+            Some(csv) => {
+                csv.write(&ReadoutRecord {
+                    read_at: Some(chrono::Local::now().naive_local()),
+                    readout: readout.data,
+                    owner_data: None,
+                })?;
+                csv.flush()?;
+            }
+            None if format == Format::Json => print_json(&readout),
+            None => print_readout(&readout),
+        }
+        reader.beep_until_card_removed().await?;
+    }
+}
+
+async fn punches(reader: &mut Reader, format: Format) -> sportident::Result<()> {
+    let mut csv = match format {
+        Format::Csv => Some(csv_writer(std::io::stdout(), &PUNCH_COLUMNS)?),
+        Format::Text | Format::Json => None,
+    };
+
+    loop {
+        let punch = reader.poll_punch().await?;
+        match &mut csv {
+            Some(csv) => {
+                csv.write_record(punch_row(&punch))?;
+                csv.flush()?;
+            }
+            None if format == Format::Json => print_json(&punch),
+            None => print_punch(&punch),
+        }
+    }
+}
+
+async fn owner(reader: &mut Reader, format: Format) -> sportident::Result<()> {
+    let mut csv = match format {
+        Format::Csv => Some(csv_writer(std::io::stdout(), &OWNER_COLUMNS)?),
+        Format::Text | Format::Json => None,
+    };
+
+    loop {
+        let Some(readout) = skip_removed_card(reader.poll_readout::<CardOwnerData>().await)? else {
+            continue;
+        };
+        match &mut csv {
+            Some(csv) => {
+                csv.write_record(owner_row(&readout))?;
+                csv.flush()?;
+            }
+            None if format == Format::Json => print_json(&readout),
+            None => {
+                let owner = &readout.data;
+                println!(
+                    "{}\t{} {}\t{}",
+                    readout.card.number,
+                    owner.first_name,
+                    owner.last_name,
+                    owner.club.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        reader.beep_until_card_removed().await?;
+    }
+}
+
+async fn dump(reader: &mut Reader, directory: &std::path::Path) -> sportident::Result<()> {
+    std::fs::create_dir_all(directory)?;
+    loop {
+        let Some(readout) = skip_removed_card(reader.poll_readout::<CardImage>().await)? else {
+            continue;
+        };
+        let path = directory.join(format!("{}.bin", readout.card.number));
+        std::fs::File::create(&path)?.write_all(&readout.data.data)?;
+        println!("{}", path.display());
+        reader.beep_until_card_removed().await?;
+    }
+}
+
+/// Cards removed too early are reported and read again on the next insertion.
+fn skip_removed_card<T>(result: sportident::Result<T>) -> sportident::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::CardRemovedWhileReadingData) => {
+            eprintln!("card removed while reading, insert it again");
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// A semicolon separated writer like the readout CSV files, with the header already written.
+fn csv_writer<W: Write>(writer: W, header: &[&str]) -> sportident::Result<csv::Writer<W>> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b';')
+        .from_writer(writer);
+    writer.write_record(header)?;
+    Ok(writer)
+}
+
+fn punch_row(punch: &CardPunch) -> [String; 4] {
+    let source = match punch.source {
+        PunchSource::Direct => "direct",
+        PunchSource::Radio(SRRChannel::Red) => "radio red",
+        PunchSource::Radio(SRRChannel::Blue) => "radio blue",
+        PunchSource::Backup => "backup",
+    };
+    [
+        punch.station_code.to_string(),
+        punch.card.number.to_string(),
+        punch.punch.time.format("%H:%M:%S%.3f").to_string(),
+        source.to_string(),
+    ]
+}
+
+fn owner_row(readout: &Readout<CardOwnerData>) -> [String; 4] {
+    let owner = &readout.data;
+    [
+        readout.card.number.to_string(),
+        owner.first_name.clone(),
+        owner.last_name.clone(),
+        owner.club.clone().unwrap_or_default(),
+    ]
+}
+
+fn print_json(value: &impl serde::Serialize) {
+    match serde_json::to_string(value) {
+        Ok(json) => println!("{json}"),
+        Err(error) => eprintln!("error: {error}"),
+    }
+}
+
+fn print_readout(readout: &Readout<CardReadout>) {
+    let card = &readout.data;
+    let time = |punch: Option<&StartOrFinishPunch>| {
+        punch.map_or_else(|| "-".to_string(), |punch| punch.time().to_string())
+    };
+    println!(
+        "{} ({:?}) start {} finish {} check {}",
+        card.card_number,
+        card.card_type,
+        time(card.start.as_ref()),
+        time(card.finish.as_ref()),
+        card.check
+            .as_ref()
+            .map_or_else(|| "-".to_string(), |check| check.time.to_string())
+    );
+    for (index, punch) in card.punches.iter().enumerate() {
+        println!("{:>4}. {:>4} {}", index + 1, punch.code, punch.time);
+    }
+}
+
+fn print_punch(punch: &CardPunch) {
+    println!(
+        "station {} card {} {} {:?}",
+        punch.station_code, punch.card.number, punch.punch.time, punch.source
+    );
+}
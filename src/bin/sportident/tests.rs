@@ -0,0 +1,137 @@
+#![allow(clippy::pedantic)]
+
+use chrono::NaiveTime;
+use clap::Parser;
+use sportident::{
+    Card, CardOwnerData, CardPunch, DayOfWeek, PunchSource, Readout, SRRChannel, SubSecondPunch,
+    WeekCounter,
+};
+
+use super::{
+    csv_writer, owner_row, punch_row, Cli, Command, Format, InfoFormat, OWNER_COLUMNS,
+    PUNCH_COLUMNS,
+};
+
+fn card_punch(source: PunchSource) -> CardPunch {
+    CardPunch {
+        punch: SubSecondPunch {
+            time: NaiveTime::from_hms_milli_opt(10, 15, 30, 250).unwrap(),
+            day_of_week: DayOfWeek::Saturday,
+            week_counter: WeekCounter::First,
+        },
+        card: Card::new(7_001_234).unwrap(),
+        station_code: 31,
+        source,
+        backup_address: None,
+    }
+}
+
+fn write_csv<const N: usize>(header: &[&str], rows: &[[String; N]]) -> String {
+    let mut writer = csv_writer(Vec::new(), header).unwrap();
+    for row in rows {
+        writer.write_record(row).unwrap();
+    }
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+#[test]
+fn parse_defaults() {
+    let cli = Cli::try_parse_from(["sportident", "readout"]).unwrap();
+
+    assert_eq!(cli.port, None);
+    assert!(matches!(
+        cli.command,
+        Command::Readout {
+            format: Format::Text
+        }
+    ));
+}
+
+#[test]
+fn parse_global_port_and_format() {
+    let cli = Cli::try_parse_from([
+        "sportident",
+        "punches",
+        "--format",
+        "csv",
+        "-p",
+        "/dev/ttyUSB0",
+    ])
+    .unwrap();
+
+    assert_eq!(cli.port.as_deref(), Some("/dev/ttyUSB0"));
+    assert!(matches!(
+        cli.command,
+        Command::Punches {
+            format: Format::Csv
+        }
+    ));
+}
+
+#[test]
+fn parse_dump_directory() {
+    let cli = Cli::try_parse_from(["sportident", "dump", "cards"]).unwrap();
+
+    assert!(matches!(cli.command, Command::Dump { directory } if directory.ends_with("cards")));
+}
+
+#[test]
+fn info_has_no_csv_format() {
+    assert!(Cli::try_parse_from(["sportident", "info", "--format", "csv"]).is_err());
+    let cli = Cli::try_parse_from(["sportident", "info", "-f", "json"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Command::Info {
+            format: InfoFormat::Json
+        }
+    ));
+}
+
+#[test]
+fn punches_csv() {
+    let output = write_csv(
+        &PUNCH_COLUMNS,
+        &[
+            punch_row(&card_punch(PunchSource::Direct)),
+            punch_row(&card_punch(PunchSource::Radio(SRRChannel::Blue))),
+            punch_row(&card_punch(PunchSource::Backup)),
+        ],
+    );
+
+    assert_eq!(
+        output,
+        "Station;SIID;Time;Source\n\
+         31;7001234;10:15:30.250;direct\n\
+         31;7001234;10:15:30.250;radio blue\n\
+         31;7001234;10:15:30.250;backup\n"
+    );
+}
+
+#[test]
+fn owner_csv_is_quoted() {
+    let readout = Readout {
+        station_code: 10,
+        card: Card::new(7_001_234).unwrap(),
+        data: CardOwnerData {
+            first_name: "Anna".to_string(),
+            last_name: "Smith; Jones".to_string(),
+            gender: None,
+            birthday: None,
+            club: Some("OK \"Nord\"".to_string()),
+            email: None,
+            phone: None,
+            city: None,
+            street: None,
+            zip: None,
+            country: None,
+        },
+    };
+
+    let output = write_csv(&OWNER_COLUMNS, &[owner_row(&readout)]);
+
+    assert_eq!(
+        output,
+        "SIID;First name;Last name;Club\n\
+         7001234;Anna;\"Smith; Jones\";\"OK \"\"Nord\"\"\"\n"
+    );
+}
@@ -0,0 +1,30 @@
+use std::process::ExitCode;
+
+use sportident::daemon::{self, Config};
+
+const USAGE: &str = "usage: sportidentd <config.toml>";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let (Some(path), None) = (args.next(), args.next()) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    if path == "-h" || path == "--help" {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match Config::load(&path) {
+        Ok(config) => daemon::run(config, |incident| eprintln!("{incident}")).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! Readouts in the semicolon separated CSV layout of SI-Config+ and SI Reader.
+//!
+//! Each row holds one readout: the card number, the clear, check, start and finish punches as
+//! code, day of week and time columns, the card owner data and then `Record N CN`,
+//! `Record N DOW` and `Record N time` columns for every punch. Week counters are not part of
+//! the layout and are imported as [`WeekCounter::First`].
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use chrono::{NaiveDateTime, NaiveTime, Timelike};
+
+use crate::{
+    Card, CardOwnerData, CardReadout, DayOfWeek, Error, Punch, StartOrFinishPunch, SubSecondPunch,
+    WeekCounter,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The number of punch columns written by default, the capacity of the largest cards.
+pub const DEFAULT_PUNCH_COLUMNS: usize = 128;
+
+const DELIMITER: u8 = b';';
+const READ_AT_FORMAT: &str = "%d.%m.%Y %H:%M:%S";
+const TIME_FORMAT: &str = "%H:%M:%S";
+const SUB_SECOND_TIME_FORMAT: &str = "%H:%M:%S%.3f";
+const PUNCH_COLUMNS: [&str; 4] = ["Clear", "Check", "Start", "Finish"];
+const OWNER_DATA_COLUMNS: [&str; 12] = [
+    "Class",
+    "First name",
+    "Last name",
+    "Club",
+    "Country",
+    "Email",
+    "Date of birth",
+    "Sex",
+    "Phone",
+    "Street",
+    "ZIP",
+    "City",
+];
+const STATION_COLUMNS: [&str; 7] = [
+    "Hardware version",
+    "Software version",
+    "Battery date",
+    "Battery voltage",
+    "Clear count",
+    "Character set",
+    "SEL_FEEDBACK",
+];
+const RECORD_COUNT_COLUMN: &str = "No. of records";
+
+/// A row of a readout CSV file.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ReadoutRecord {
+    /// When the card was read out.
+    pub read_at: Option<NaiveDateTime>,
+    pub readout: CardReadout,
+    pub owner_data: Option<CardOwnerData>,
+}
+
+pub struct ReadoutWriter<W: Write> {
+    writer: csv::Writer<W>,
+    punch_columns: usize,
+    rows: u32,
+}
+
+pub struct ReadoutReader<R: Read> {
+    reader: csv::Reader<R>,
+    columns: HashMap<String, usize>,
+}
+
+impl<W: Write> ReadoutWriter<W> {
+    /// Writes the header with [`DEFAULT_PUNCH_COLUMNS`] punch columns.
+    pub fn new(writer: W) -> crate::Result<Self> {
+        Self::with_punch_columns(writer, DEFAULT_PUNCH_COLUMNS)
+    }
+
+    /// Writes the header with `punch_columns` punch columns. Rows with more punches are longer
+    /// than the header.
+    pub fn with_punch_columns(writer: W, punch_columns: usize) -> crate::Result<Self> {
+        let mut writer = csv_writer(writer);
+        writer.write_record(header(punch_columns))?;
+
+        Ok(Self {
+            writer,
+            punch_columns,
+            rows: 0,
+        })
+    }
+
+    /// Appends rows with [`DEFAULT_PUNCH_COLUMNS`] punch columns to a file which already has a
+    /// header and `rows` rows. New rows are numbered after the existing ones.
+    pub fn appending(writer: W, rows: u32) -> Self {
+        Self {
+            writer: csv_writer(writer),
+            punch_columns: DEFAULT_PUNCH_COLUMNS,
+            rows,
+        }
+    }
+
+    pub fn write(&mut self, record: &ReadoutRecord) -> crate::Result<()> {
+        self.rows += 1;
+        let readout = &record.readout;
+        let owner_data = record.owner_data.as_ref();
+        let owner = |field: fn(&CardOwnerData) -> Option<&str>| {
+            owner_data.and_then(field).unwrap_or_default().to_string()
+        };
+
+        let mut row = vec![
+            self.rows.to_string(),
+            record
+                .read_at
+                .map(|read_at| read_at.format(READ_AT_FORMAT).to_string())
+                .unwrap_or_default(),
+            readout.card_number.to_string(),
+            String::new(),
+        ];
+        row.extend(punch_fields(None));
+        row.extend(punch_fields(readout.check.as_ref()));
+        row.extend(start_or_finish_fields(readout.start.as_ref()));
+        row.extend(start_or_finish_fields(readout.finish.as_ref()));
+        row.push(String::new());
+        row.extend([
+            owner(|owner| Some(owner.first_name.as_str())),
+            owner(|owner| Some(owner.last_name.as_str())),
+            owner(|owner| owner.club.as_deref()),
+            owner(|owner| owner.country.as_deref()),
+            owner(|owner| owner.email.as_deref()),
+            owner(|owner| owner.birthday.as_deref()),
+            owner(|owner| owner.gender.as_deref()),
+            owner(|owner| owner.phone.as_deref()),
+            owner(|owner| owner.street.as_deref()),
+            owner(|owner| owner.zip.as_deref()),
+            owner(|owner| owner.city.as_deref()),
+        ]);
+        row.extend(STATION_COLUMNS.map(|_| String::new()));
+        row.push(readout.punches.len().to_string());
+        for punch in &readout.punches {
+            row.extend(punch_fields(Some(punch)));
+        }
+        for _ in readout.punches.len()..self.punch_columns {
+            row.extend(punch_fields(None));
+        }
+
+        self.writer.write_record(row)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> crate::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> crate::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|error| Error::IoError(error.into_error()))
+    }
+}
+
+impl<R: Read> ReadoutReader<R> {
+    /// Reads the header, columns are looked up by name.
+    pub fn new(reader: R) -> crate::Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(DELIMITER)
+            .flexible(true)
+            .from_reader(reader);
+        let columns = reader
+            .headers()?
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.trim().to_string(), index))
+            .collect();
+
+        Ok(Self { reader, columns })
+    }
+
+    fn parse(&self, row: &csv::StringRecord) -> crate::Result<ReadoutRecord> {
+        let line = row.position().map_or(0, csv::Position::line);
+        let invalid = |message: String| Error::InvalidCsvRecord(line, message);
+        let field = |name: &str| {
+            self.columns
+                .get(name)
+                .and_then(|index| row.get(*index))
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+        };
+        let punch = |name: &str| {
+            parse_punch(
+                field(&format!("{name} CN")),
+                field(&format!("{name} DOW")),
+                field(&format!("{name} time")),
+            )
+            .map_err(|message| invalid(format!("{name}: {message}")))
+        };
+
+        let card_number = field("SIID").ok_or_else(|| invalid("missing SIID".to_string()))?;
+        let card = card_number
+            .parse()
+            .map_err(|_| invalid(format!("invalid SIID {card_number}")))
+            .and_then(|number| Card::new(number).map_err(Error::from))?;
+        let read_at = field("Read on")
+            .map(|read_at| {
+                NaiveDateTime::parse_from_str(read_at, READ_AT_FORMAT)
+                    .map_err(|_| invalid(format!("invalid read out time {read_at}")))
+            })
+            .transpose()?;
+
+        let record_count = field(RECORD_COUNT_COLUMN)
+            .map(|count| {
+                count
+                    .parse::<usize>()
+                    .map_err(|_| invalid(format!("invalid number of records {count}")))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        // Records are read by position, as rows may have more records than the header.
+        let first_record = self
+            .columns
+            .get("Record 1 CN")
+            .copied()
+            .unwrap_or(self.columns.len());
+        let punches = (0..record_count)
+            .map(|index| {
+                let column = |offset| {
+                    row.get(first_record + index * 3 + offset)
+                        .map(str::trim)
+                        .filter(|field| !field.is_empty())
+                };
+                parse_punch(column(0), column(1), column(2))
+                    .map_err(|message| invalid(format!("Record {}: {message}", index + 1)))?
+                    .and_then(|(code, punch)| code.map(|code| Punch { code, ..punch }))
+                    .ok_or_else(|| invalid(format!("missing record {}", index + 1)))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        let owner = |name: &str| field(name).map(str::to_string);
+        let owner_data = OWNER_DATA_COLUMNS[1..]
+            .iter()
+            .any(|name| field(name).is_some())
+            .then(|| CardOwnerData {
+                first_name: owner("First name").unwrap_or_default(),
+                last_name: owner("Last name").unwrap_or_default(),
+                gender: owner("Sex"),
+                birthday: owner("Date of birth"),
+                club: owner("Club"),
+                email: owner("Email"),
+                phone: owner("Phone"),
+                city: owner("City"),
+                street: owner("Street"),
+                zip: owner("ZIP"),
+                country: owner("Country"),
+            });
+
+        Ok(ReadoutRecord {
+            read_at,
+            readout: CardReadout {
+                card_number: card.number,
+                card_type: card.card_type,
+                start: punch("Start")?.map(start_or_finish),
+                finish: punch("Finish")?.map(start_or_finish),
+                check: punch("Check")?.map(|(code, punch)| Punch {
+                    code: code.unwrap_or_default(),
+                    ..punch
+                }),
+                punches,
+            },
+            owner_data,
+        })
+    }
+}
+
+impl<R: Read> Iterator for ReadoutReader<R> {
+    type Item = crate::Result<ReadoutRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut row = csv::StringRecord::new();
+        match self.reader.read_record(&mut row) {
+            Ok(true) => Some(self.parse(&row)),
+            Ok(false) => None,
+            Err(error) => Some(Err(error.into())),
+        }
+    }
+}
+
+fn csv_writer<W: Write>(writer: W) -> csv::Writer<W> {
+    csv::WriterBuilder::new()
+        .delimiter(DELIMITER)
+        .flexible(true)
+        .from_writer(writer)
+}
+
+fn header(punch_columns: usize) -> Vec<String> {
+    let mut header = ["No", "Read on", "SIID", "Start no"]
+        .map(String::from)
+        .to_vec();
+    for name in PUNCH_COLUMNS {
+        header.extend(punch_header(name));
+    }
+    header.extend(OWNER_DATA_COLUMNS.map(String::from));
+    header.extend(STATION_COLUMNS.map(String::from));
+    header.push(RECORD_COUNT_COLUMN.to_string());
+    for index in 1..=punch_columns {
+        header.extend(punch_header(&format!("Record {index}")));
+    }
+    header
+}
+
+fn punch_header(name: &str) -> [String; 3] {
+    [
+        format!("{name} CN"),
+        format!("{name} DOW"),
+        format!("{name} time"),
+    ]
+}
+
+fn punch_fields(punch: Option<&Punch>) -> [String; 3] {
+    punch.map_or_else(Default::default, |punch| {
+        [
+            punch.code.to_string(),
+            day_of_week_name(punch.day_of_week).to_string(),
+            punch.time.format(TIME_FORMAT).to_string(),
+        ]
+    })
+}
+
+/// Sub-second punches have no code and keep their milliseconds.
+fn start_or_finish_fields(punch: Option<&StartOrFinishPunch>) -> [String; 3] {
+    match punch {
+        Some(StartOrFinishPunch::Normal(punch)) => punch_fields(Some(punch)),
+        Some(StartOrFinishPunch::SubSecond(punch)) => [
+            String::new(),
+            day_of_week_name(punch.day_of_week).to_string(),
+            punch.time.format(SUB_SECOND_TIME_FORMAT).to_string(),
+        ],
+        None => Default::default(),
+    }
+}
+
+fn start_or_finish((code, punch): (Option<u16>, Punch)) -> StartOrFinishPunch {
+    match code {
+        Some(code) if punch.time.nanosecond() == 0 => {
+            StartOrFinishPunch::Normal(Punch { code, ..punch })
+        }
+        _ => StartOrFinishPunch::SubSecond(SubSecondPunch {
+            time: punch.time,
+            day_of_week: punch.day_of_week,
+            week_counter: punch.week_counter,
+        }),
+    }
+}
+
+/// Parses the fields of a punch, returning the code separately as sub-second punches have none.
+fn parse_punch(
+    code: Option<&str>,
+    day_of_week: Option<&str>,
+    time: Option<&str>,
+) -> Result<Option<(Option<u16>, Punch)>, String> {
+    let Some(time) = time else {
+        return Ok(None);
+    };
+
+    let code = code
+        .map(|code| code.parse().map_err(|_| format!("invalid code {code}")))
+        .transpose()?;
+    let day_of_week = day_of_week.ok_or("missing day of week")?;
+    let day_of_week = parse_day_of_week(day_of_week)
+        .ok_or_else(|| format!("invalid day of week {day_of_week}"))?;
+    let time = NaiveTime::parse_from_str(time, TIME_FORMAT)
+        .or_else(|_| NaiveTime::parse_from_str(time, SUB_SECOND_TIME_FORMAT))
+        .map_err(|_| format!("invalid time {time}"))?;
+
+    Ok(Some((
+        code,
+        Punch {
+            time,
+            day_of_week,
+            week_counter: WeekCounter::First,
+            code: 0,
+        },
+    )))
+}
+
+const fn day_of_week_name(day_of_week: DayOfWeek) -> &'static str {
+    match day_of_week {
+        DayOfWeek::Monday => "Mo",
+        DayOfWeek::Tuesday => "Tu",
+        DayOfWeek::Wednesday => "We",
+        DayOfWeek::Thursday => "Th",
+        DayOfWeek::Friday => "Fr",
+        DayOfWeek::Saturday => "Sa",
+        DayOfWeek::Sunday => "Su",
+    }
+}
+
+fn parse_day_of_week(name: &str) -> Option<DayOfWeek> {
+    [
+        DayOfWeek::Monday,
+        DayOfWeek::Tuesday,
+        DayOfWeek::Wednesday,
+        DayOfWeek::Thursday,
+        DayOfWeek::Friday,
+        DayOfWeek::Saturday,
+        DayOfWeek::Sunday,
+    ]
+    .into_iter()
+    .find(|day_of_week| day_of_week_name(*day_of_week).eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,138 @@
+#![allow(clippy::pedantic)]
+
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::csv::{ReadoutReader, ReadoutRecord, ReadoutWriter, DEFAULT_PUNCH_COLUMNS};
+use crate::test_support::punch;
+use crate::CardType::{Si10, Si8};
+use crate::DayOfWeek::{Saturday, Sunday};
+use crate::StartOrFinishPunch::{Normal, SubSecond};
+use crate::WeekCounter::First;
+use crate::{CardOwnerData, CardReadout, Punch, SubSecondPunch};
+
+fn records() -> Vec<ReadoutRecord> {
+    vec![
+        ReadoutRecord {
+            read_at: NaiveDate::from_ymd_opt(2024, 6, 1)
+                .unwrap()
+                .and_hms_opt(10, 13, 5),
+            readout: CardReadout {
+                card_number: 7_001_234,
+                card_type: Si10,
+                start: Some(Normal(punch(1, "10:00:00"))),
+                finish: Some(Normal(punch(2, "10:12:30"))),
+                check: Some(punch(3, "09:58:10")),
+                punches: vec![punch(31, "10:04:10"), punch(32, "10:10:00")],
+            },
+            owner_data: Some(CardOwnerData {
+                first_name: "Tove".to_string(),
+                last_name: "Alexandersson".to_string(),
+                gender: Some("F".to_string()),
+                birthday: None,
+                club: Some("Stora Tuna OK; Sweden".to_string()),
+                email: None,
+                phone: None,
+                city: Some("Borlänge".to_string()),
+                street: None,
+                zip: None,
+                country: Some("SWE".to_string()),
+            }),
+        },
+        ReadoutRecord {
+            read_at: None,
+            readout: CardReadout {
+                card_number: 2_071_338,
+                card_type: Si8,
+                start: Some(SubSecond(SubSecondPunch {
+                    time: NaiveTime::from_hms_milli_opt(23, 59, 58, 250).unwrap(),
+                    day_of_week: Saturday,
+                    week_counter: First,
+                })),
+                finish: None,
+                check: None,
+                punches: vec![Punch {
+                    day_of_week: Sunday,
+                    ..punch(31, "00:01:00")
+                }],
+            },
+            owner_data: None,
+        },
+    ]
+}
+
+fn write(records: &[ReadoutRecord], punch_columns: usize) -> String {
+    let mut writer = ReadoutWriter::with_punch_columns(Vec::new(), punch_columns).unwrap();
+    for record in records {
+        writer.write(record).unwrap();
+    }
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+#[test]
+fn layout() {
+    let csv = write(&records()[..1], 2);
+    let mut lines = csv.lines();
+
+    assert_eq!(
+        lines.next().unwrap(),
+        "No;Read on;SIID;Start no;Clear CN;Clear DOW;Clear time;Check CN;Check DOW;Check time;\
+         Start CN;Start DOW;Start time;Finish CN;Finish DOW;Finish time;Class;First name;\
+         Last name;Club;Country;Email;Date of birth;Sex;Phone;Street;ZIP;City;Hardware version;\
+         Software version;Battery date;Battery voltage;Clear count;Character set;SEL_FEEDBACK;\
+         No. of records;Record 1 CN;Record 1 DOW;Record 1 time;Record 2 CN;Record 2 DOW;\
+         Record 2 time"
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        "1;01.06.2024 10:13:05;7001234;;;;;3;Sa;09:58:10;1;Sa;10:00:00;2;Sa;10:12:30;;Tove;\
+         Alexandersson;\"Stora Tuna OK; Sweden\";SWE;;;F;;;;Borlänge;;;;;;;;2;31;Sa;10:04:10;\
+         32;Sa;10:10:00"
+    );
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn appending() {
+    let mut csv = write(&records()[..1], DEFAULT_PUNCH_COLUMNS).into_bytes();
+    let mut writer = ReadoutWriter::appending(&mut csv, 1);
+    writer.write(&records()[1]).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let numbers = String::from_utf8(csv)
+        .unwrap()
+        .lines()
+        .map(|line| line.split(';').next().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, ["No", "1", "2"]);
+}
+
+#[test]
+fn round_trip() {
+    let records = records();
+    let csv = write(&records, 1);
+
+    let read = ReadoutReader::new(csv.as_bytes())
+        .unwrap()
+        .collect::<crate::Result<Vec<_>>>()
+        .unwrap();
+
+    assert_eq!(read, records);
+}
+
+#[test]
+fn invalid_record() {
+    let csv = "No;SIID;No. of records;Record 1 CN;Record 1 DOW;Record 1 time\n\
+               1;7001234;1;31;Xx;10:00:00\n\
+               2;123;0\n";
+
+    let read = ReadoutReader::new(csv.as_bytes())
+        .unwrap()
+        .collect::<Vec<_>>();
+
+    assert!(matches!(
+        read[0],
+        Err(crate::Error::InvalidCsvRecord(2, ref message)) if message.contains("Xx")
+    ));
+    assert!(read[1].is_err());
+}
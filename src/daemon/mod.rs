@@ -0,0 +1,272 @@
+//! The building blocks of the `sportidentd` daemon, which owns readers and pushes every readout
+//! and punch to the configured sinks.
+//!
+//! Each sink has its own [`Outbox`] and [`SinkWorker`], so a sink that is down only delays its
+//! own events: they are kept on disk and retried until the sink accepts them.
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, Sender};
+
+use crate::{CardPunch, CardReadout, Error, Reader, Readout};
+
+pub use outbox::Outbox;
+pub use sink::{Sink, SinkWorker};
+
+mod outbox;
+mod sink;
+#[cfg(test)]
+mod tests;
+
+/// Delay before connecting again to a reader that failed or was unplugged.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The directory the outboxes of the sinks are kept in.
+    pub state_directory: PathBuf,
+    #[serde(default)]
+    pub readers: Vec<ReaderConfig>,
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ReaderConfig {
+    pub name: String,
+    /// The serial port of the reader, the first detected reader is used by default.
+    pub port: Option<String>,
+    #[serde(default)]
+    pub mode: ReaderMode,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ReaderMode {
+    /// Read cards inserted into the reader.
+    #[default]
+    Readout,
+    /// Follow the punches sent by a station in auto send mode.
+    Punches,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SinkConfig {
+    /// The name of the sink, which also names its outbox file.
+    pub name: String,
+    #[serde(flatten)]
+    pub sink: Sink,
+}
+
+/// A readout or punch received from one of the readers of the daemon.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Readout {
+        reader: String,
+        received_at: DateTime<Local>,
+        readout: Readout<CardReadout>,
+    },
+    Punch {
+        reader: String,
+        received_at: DateTime<Local>,
+        punch: CardPunch,
+    },
+}
+
+impl Config {
+    pub fn from_toml(content: &str) -> crate::Result<Self> {
+        toml::from_str(content).map_err(|e| Error::InvalidConfig(e.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    #[must_use]
+    pub fn outbox_path(&self, sink: &SinkConfig) -> PathBuf {
+        self.state_directory.join(format!("{}.outbox", sink.name))
+    }
+}
+
+/// A problem the daemon recovers from by itself, passed to the callback of [`run`].
+#[derive(Debug)]
+pub enum Incident {
+    /// A reader failed or could not be connected, it is connected again after a delay.
+    ReaderFailed { reader: String, error: Error },
+    /// A sink did not accept an event, which is retried after `retry_in`.
+    DeliveryFailed {
+        sink: String,
+        error: Error,
+        retry_in: Duration,
+    },
+    /// A sink stopped, e.g. because its CSV file is corrupt. Its events are kept in its outbox
+    /// and delivered once the daemon is restarted.
+    SinkStopped { sink: String, error: Error },
+}
+
+impl Display for Incident {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReaderFailed { reader, error } => write!(f, "reader {reader}: {error}"),
+            Self::DeliveryFailed {
+                sink,
+                error,
+                retry_in,
+            } => write!(
+                f,
+                "sink {sink}: delivery failed, retrying in {retry_in:?}: {error}"
+            ),
+            Self::SinkStopped { sink, error } => write!(f, "sink {sink}: stopped: {error}"),
+        }
+    }
+}
+
+/// The outbox of a sink and the channel waking up its worker, as seen by the readers.
+#[derive(Debug, Clone)]
+struct SinkInbox {
+    outbox: Arc<Mutex<Outbox>>,
+    wake_up: Sender<()>,
+}
+
+/// Runs the readers and sinks of the configuration until every reader task stops, which only
+/// happens when all sinks stopped.
+pub async fn run(
+    config: Config,
+    report: impl Fn(Incident) + Send + Sync + 'static,
+) -> crate::Result<()> {
+    std::fs::create_dir_all(&config.state_directory)?;
+    let report = Arc::new(report);
+
+    let mut inboxes = Vec::new();
+    let mut workers = Vec::new();
+    for sink in &config.sinks {
+        let (wake_up, wake_ups) = mpsc::channel(1);
+        let worker = SinkWorker::new(sink.sink.clone(), Outbox::open(config.outbox_path(sink))?);
+        inboxes.push(SinkInbox {
+            outbox: worker.outbox().clone(),
+            wake_up,
+        });
+        let (name, report) = (sink.name.clone(), report.clone());
+        workers.push(tokio::spawn(async move {
+            let result = worker
+                .run(wake_ups, |error, retry_in| {
+                    report(Incident::DeliveryFailed {
+                        sink: name.clone(),
+                        error,
+                        retry_in,
+                    });
+                })
+                .await;
+            if let Err(error) = result {
+                report(Incident::SinkStopped { sink: name, error });
+            }
+        }));
+    }
+
+    let readers = config
+        .readers
+        .into_iter()
+        .map(|reader| tokio::spawn(run_reader(reader, inboxes.clone(), report.clone())))
+        .collect::<Vec<_>>();
+    drop(inboxes);
+
+    for reader in readers {
+        let _ = reader.await;
+    }
+    for worker in workers {
+        let _ = worker.await;
+    }
+    Ok(())
+}
+
+async fn run_reader(
+    config: ReaderConfig,
+    sinks: Vec<SinkInbox>,
+    report: Arc<impl Fn(Incident) + Send + Sync>,
+) {
+    loop {
+        let connection = match &config.port {
+            Some(port) => Reader::connect(port.as_str()).await,
+            None => Reader::auto_connect().await,
+        };
+        let error = match connection {
+            Ok(mut reader) => poll_reader(&config, &mut reader, &sinks).await,
+            Err(error) => error,
+        };
+        if sinks.iter().all(|sink| sink.wake_up.is_closed()) {
+            return;
+        }
+        report(Incident::ReaderFailed {
+            reader: config.name.clone(),
+            error,
+        });
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Polls the reader until it fails, returning the error. Readouts are in the outbox of every
+/// sink before the reader beeps, so a runner whose readout was lost inserts their card again.
+async fn poll_reader(config: &ReaderConfig, reader: &mut Reader, sinks: &[SinkInbox]) -> Error {
+    loop {
+        let event = match config.mode {
+            ReaderMode::Readout => match reader.poll_readout::<CardReadout>().await {
+                Ok(readout) => Event::Readout {
+                    reader: config.name.clone(),
+                    received_at: Local::now(),
+                    readout,
+                },
+                Err(Error::CardRemovedWhileReadingData) => continue,
+                Err(error) => return error,
+            },
+            ReaderMode::Punches => match reader.poll_punch().await {
+                Ok(punch) => Event::Punch {
+                    reader: config.name.clone(),
+                    received_at: Local::now(),
+                    punch,
+                },
+                Err(error) => return error,
+            },
+        };
+
+        if let Err(error) = push(sinks, event).await {
+            return error;
+        }
+        if config.mode == ReaderMode::Readout {
+            if let Err(error) = reader.beep_until_card_removed().await {
+                return error;
+            }
+        }
+    }
+}
+
+/// Persists an event in the outboxes of all sinks, then wakes up their workers.
+async fn push(sinks: &[SinkInbox], event: Event) -> crate::Result<()> {
+    let outboxes = sinks
+        .iter()
+        .map(|sink| sink.outbox.clone())
+        .collect::<Vec<_>>();
+    tokio::task::spawn_blocking(move || {
+        outboxes
+            .iter()
+            .try_for_each(|outbox| lock(outbox).push(event.clone()))
+    })
+    .await
+    .map_err(std::io::Error::other)??;
+
+    for sink in sinks {
+        // A full channel already wakes the worker up.
+        let _ = sink.wake_up.try_send(());
+    }
+    Ok(())
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
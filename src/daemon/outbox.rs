@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::daemon::Event;
+use crate::Error;
+
+/// The events a sink has not accepted yet, persisted as one JSON line per event.
+///
+/// Events are written to disk before they are delivered, so a restarted daemon delivers what
+/// was pending when it stopped. The file is only appended to while events are delivered: the
+/// offset of the first pending event is kept in a separate `.ack` file, and the outbox file is
+/// truncated once it is empty and compacted when it is opened. The acknowledged offset is
+/// always reset before the outbox file shrinks, so a crash in between delivers events again
+/// rather than losing them.
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    /// The pending events, with the offset of the end of their line.
+    pending: VecDeque<(Event, u64)>,
+    length: u64,
+}
+
+impl Outbox {
+    /// Opens an outbox file, starting empty if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let content = read_optional(&path)?.unwrap_or_default();
+        let acknowledged = read_optional(&ack_path(&path))?
+            .and_then(|ack| ack.trim().parse::<usize>().ok())
+            .filter(|ack| *ack == 0 || content.get(..*ack).is_some_and(|ack| ack.ends_with('\n')))
+            .unwrap_or(0);
+
+        let mut outbox = Self {
+            path,
+            pending: VecDeque::new(),
+            length: 0,
+        };
+        let skipped_lines = content[..acknowledged].lines().count();
+        for (index, line) in content[acknowledged..].lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(line)
+                .map_err(|_| Error::InvalidOutboxFile(skipped_lines + index + 1))?;
+            outbox.length += json_line(&event)?.len() as u64;
+            outbox.pending.push_back((event, outbox.length));
+        }
+
+        if acknowledged > 0 || outbox.length != content.len() as u64 {
+            outbox.compact()?;
+        }
+        Ok(outbox)
+    }
+
+    /// Appends an event to the outbox, returning once it is on disk.
+    pub fn push(&mut self, event: Event) -> crate::Result<()> {
+        let line = json_line(&event)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        self.length += line.len() as u64;
+        self.pending.push_back((event, self.length));
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn front(&self) -> Option<&Event> {
+        self.pending.front().map(|(event, _)| event)
+    }
+
+    /// Removes the oldest event, after its sink accepted it.
+    pub fn pop(&mut self) -> crate::Result<Option<Event>> {
+        let Some((event, end)) = self.pending.pop_front() else {
+            return Ok(None);
+        };
+        if self.pending.is_empty() {
+            self.compact()?;
+        } else {
+            let temporary_path = self.path.with_extension("ack.tmp");
+            std::fs::write(&temporary_path, end.to_string())?;
+            std::fs::rename(temporary_path, ack_path(&self.path))?;
+        }
+        Ok(Some(event))
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Atomically rewrites the outbox file with the pending events only.
+    fn compact(&mut self) -> crate::Result<()> {
+        match std::fs::remove_file(ack_path(&self.path)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        let mut content = String::new();
+        for (event, end) in &mut self.pending {
+            content.push_str(&json_line(event)?);
+            *end = content.len() as u64;
+        }
+        let temporary_path = self.path.with_extension("tmp");
+        std::fs::write(&temporary_path, &content)?;
+        std::fs::rename(temporary_path, &self.path)?;
+        self.length = content.len() as u64;
+
+        Ok(())
+    }
+}
+
+pub fn json_line(event: &Event) -> crate::Result<String> {
+    let mut line = serde_json::to_string(event).map_err(std::io::Error::other)?;
+    line.push('\n');
+    Ok(line)
+}
+
+fn ack_path(path: &Path) -> PathBuf {
+    path.with_extension("ack")
+}
+
+fn read_optional(path: &Path) -> crate::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
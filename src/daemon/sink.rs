@@ -0,0 +1,263 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::Receiver;
+use tokio::time::{timeout, Instant};
+
+use crate::csv::{ReadoutReader, ReadoutRecord, ReadoutWriter};
+use crate::daemon::outbox::json_line;
+use crate::daemon::{lock, Event, Outbox};
+use crate::sirap::SirapRecord;
+use crate::{Error, PunchTimeResolver};
+
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+// `Duration::from_mins` needs Rust 1.91.
+#[allow(clippy::duration_suboptimal_units)]
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the events of the daemon are delivered to.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Sink {
+    /// Appends every event as a JSON line.
+    Jsonl { path: PathBuf },
+    /// Appends readouts in the SI-Config+ CSV layout, punches are skipped.
+    Csv { path: PathBuf },
+    /// Posts every event as JSON to an `http://` URL, any 2xx status accepts it.
+    Webhook { url: String },
+    /// Writes every event as a JSON line to a new connection on a Unix socket.
+    UnixSocket { path: PathBuf },
+    /// Sends punches and finish readouts to a SIRAP receiver at `host:port`, e.g. `MeOS`.
+    Sirap { address: String },
+    /// Prints every event as a JSON line.
+    Stdout,
+}
+
+/// Delivers the events of one sink in order, retrying with an exponential backoff while the
+/// sink is down.
+#[derive(Debug)]
+pub struct SinkWorker {
+    sink: Sink,
+    outbox: Arc<Mutex<Outbox>>,
+    /// The rows in the file of a CSV sink, counted once when the worker starts.
+    csv_rows: u32,
+}
+
+impl Sink {
+    /// Delivers an event. A CSV sink counts the rows already in its file first, to number the
+    /// appended rows after them.
+    pub async fn deliver(&self, event: &Event) -> crate::Result<()> {
+        let mut csv_rows = self.csv_rows()?;
+        self.deliver_after(event, &mut csv_rows).await
+    }
+
+    /// The rows in the file of a CSV sink, 0 for other sinks. Fails if a row can't be read.
+    fn csv_rows(&self) -> crate::Result<u32> {
+        let Self::Csv { path } = self else {
+            return Ok(0);
+        };
+        let file = match File::open(path) {
+            Ok(file) if file.metadata()?.len() > 0 => file,
+            Ok(_) => return Ok(0),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let rows = ReadoutReader::new(file)?.collect::<crate::Result<Vec<_>>>()?;
+        Ok(u32::try_from(rows.len()).unwrap_or(u32::MAX))
+    }
+
+    /// Delivers an event, appending to a CSV sink after its `csv_rows` rows.
+    async fn deliver_after(&self, event: &Event, csv_rows: &mut u32) -> crate::Result<()> {
+        match self {
+            Self::Jsonl { path } => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                file.write_all(json_line(event)?.as_bytes())?;
+                file.sync_data()?;
+            }
+            Self::Csv { path } => {
+                let Event::Readout {
+                    received_at,
+                    readout,
+                    ..
+                } = event
+                else {
+                    return Ok(());
+                };
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                let mut writer = if file.metadata()?.len() == 0 {
+                    *csv_rows = 0;
+                    ReadoutWriter::new(&file)?
+                } else {
+                    ReadoutWriter::appending(&file, *csv_rows)
+                };
+                writer.write(&ReadoutRecord {
+                    read_at: Some(received_at.naive_local()),
+                    readout: readout.data.clone(),
+                    owner_data: None,
+                })?;
+                writer.flush()?;
+                file.sync_data()?;
+                *csv_rows += 1;
+            }
+            Self::Webhook { url } => post(url, json_line(event)?.trim_end()).await?,
+            Self::UnixSocket { path } => {
+                #[cfg(unix)]
+                {
+                    let mut stream = tokio::net::UnixStream::connect(path).await?;
+                    stream.write_all(json_line(event)?.as_bytes()).await?;
+                    stream.shutdown().await?;
+                }
+                #[cfg(not(unix))]
+                return Err(Error::SinkFailed(format!(
+                    "Unix sockets are not supported on this platform ({})",
+                    path.display()
+                )));
+            }
+            Self::Sirap { address } => {
+                let record = match event {
+                    Event::Readout {
+                        received_at,
+                        readout,
+                        ..
+                    } => SirapRecord::from_readout(
+                        &readout.data,
+                        &PunchTimeResolver::new(*received_at),
+                    ),
+                    Event::Punch {
+                        received_at, punch, ..
+                    } => Some(SirapRecord::from_punch(
+                        punch,
+                        &PunchTimeResolver::new(*received_at),
+                    )),
+                };
+                if let Some(record) = record {
+                    let mut stream = TcpStream::connect(address).await?;
+                    stream.write_all(&record.encode()?).await?;
+                    stream.shutdown().await?;
+                }
+            }
+            Self::Stdout => print!("{}", json_line(event)?),
+        }
+        Ok(())
+    }
+}
+
+impl SinkWorker {
+    #[must_use]
+    pub fn new(sink: Sink, outbox: Outbox) -> Self {
+        Self {
+            sink,
+            outbox: Arc::new(Mutex::new(outbox)),
+            csv_rows: 0,
+        }
+    }
+
+    /// The outbox of the sink, events pushed to it are delivered after a wake up.
+    #[must_use]
+    pub const fn outbox(&self) -> &Arc<Mutex<Outbox>> {
+        &self.outbox
+    }
+
+    /// Delivers the pending events of the outbox, and again after every wake up, until the
+    /// wake up channel is closed. Events still pending at that point stay in the outbox.
+    /// `on_failure` is called with the error and retry delay of every failed delivery.
+    ///
+    /// Fails without delivering anything if the file of a CSV sink can't be read, as retrying
+    /// would not help.
+    pub async fn run(
+        mut self,
+        mut wake_ups: Receiver<()>,
+        on_failure: impl Fn(Error, Duration),
+    ) -> crate::Result<Self> {
+        self.csv_rows = self.sink.csv_rows()?;
+        let mut retry_delay = MIN_RETRY_DELAY;
+        let mut retry_at = None;
+
+        loop {
+            if retry_at.is_none_or(|retry_at| Instant::now() >= retry_at) {
+                retry_at = None;
+                while let Some(event) = self.front() {
+                    match self.sink.deliver_after(&event, &mut self.csv_rows).await {
+                        Ok(()) => {
+                            let outbox = self.outbox.clone();
+                            tokio::task::spawn_blocking(move || lock(&outbox).pop())
+                                .await
+                                .map_err(std::io::Error::other)??;
+                            retry_delay = MIN_RETRY_DELAY;
+                        }
+                        Err(error) => {
+                            on_failure(error, retry_delay);
+                            retry_at = Some(Instant::now() + retry_delay);
+                            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            let wake_up = match retry_at {
+                Some(retry_at) => tokio::select! {
+                    wake_up = wake_ups.recv() => wake_up,
+                    () = tokio::time::sleep_until(retry_at) => continue,
+                },
+                None => wake_ups.recv().await,
+            };
+            if wake_up.is_none() {
+                return Ok(self);
+            }
+        }
+    }
+
+    fn front(&self) -> Option<Event> {
+        lock(&self.outbox).front().cloned()
+    }
+}
+
+/// Posts a JSON body with a minimal HTTP/1.1 client, the webhooks are expected to be local.
+async fn post(url: &str, body: &str) -> crate::Result<()> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| Error::SinkFailed(format!("Unsupported webhook URL: {url}")))?;
+    let (authority, path) = rest
+        .find('/')
+        .map_or((rest, "/"), |index| rest.split_at(index));
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+
+    let request = format!(
+        "POST {path} HTTP/1.1\r\nHost: {authority}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let response = timeout(WEBHOOK_TIMEOUT, async {
+        let mut stream = TcpStream::connect(address).await?;
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    })
+    .await
+    .map_err(|_| Error::SinkFailed(format!("Webhook timed out: {url}")))??;
+
+    let status_line = String::from_utf8_lossy(&response)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match status_line.split(' ').nth(1) {
+        Some(status) if status.starts_with('2') && status.len() == 3 => Ok(()),
+        _ => Err(Error::SinkFailed(format!(
+            "Webhook rejected the event: {status_line}"
+        ))),
+    }
+}
@@ -0,0 +1,524 @@
+#![allow(clippy::pedantic)]
+
+use std::cell::Cell;
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::csv::ReadoutReader;
+use crate::daemon::{
+    Config, Event, Outbox, ReaderConfig, ReaderMode, Sink, SinkConfig, SinkWorker,
+};
+use crate::sirap::{SirapListener, SirapRecord};
+use crate::test_support::punch;
+use crate::CardType::Si10;
+use crate::DayOfWeek::Saturday;
+use crate::StartOrFinishPunch::Normal;
+use crate::WeekCounter::First;
+use crate::{Card, CardPunch, CardReadout, Error, PunchSource, Readout, SubSecondPunch};
+
+fn state_directory(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("sportident-daemon-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+fn readout_event(card_number: u32) -> Event {
+    Event::Readout {
+        reader: "finish".to_string(),
+        received_at: Local.with_ymd_and_hms(2024, 6, 1, 10, 13, 5).unwrap(),
+        readout: Readout {
+            station_code: 10,
+            card: Card {
+                card_type: Si10,
+                number: card_number,
+            },
+            data: CardReadout {
+                card_number,
+                card_type: Si10,
+                start: Some(Normal(punch(1, "10:00:00"))),
+                finish: Some(Normal(punch(2, "10:12:30"))),
+                check: None,
+                punches: vec![punch(31, "10:04:10"), punch(32, "10:10:00")],
+            },
+        },
+    }
+}
+
+fn punch_event() -> Event {
+    Event::Punch {
+        reader: "radio".to_string(),
+        received_at: Local.with_ymd_and_hms(2024, 6, 1, 10, 4, 11).unwrap(),
+        punch: CardPunch {
+            punch: SubSecondPunch {
+                time: NaiveTime::from_hms_milli_opt(10, 4, 10, 500).unwrap(),
+                day_of_week: Saturday,
+                week_counter: First,
+            },
+            card: Card {
+                card_type: Si10,
+                number: 7_001_234,
+            },
+            station_code: 31,
+            source: PunchSource::Direct,
+            backup_address: None,
+        },
+    }
+}
+
+/// A stand-in webhook answering one request with `status`, returning the request body.
+async fn webhook(status: &str) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/events", listener.local_addr().unwrap());
+    let status = status.to_string();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        let body = loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).into_owned();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .unwrap()
+                    .parse::<usize>()
+                    .unwrap();
+                assert!(head.starts_with("POST /events HTTP/1.1"));
+                if body.len() >= length {
+                    break body.to_string();
+                }
+            }
+        };
+        let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n");
+        stream.write_all(response.as_bytes()).await.unwrap();
+        body
+    });
+    (url, server)
+}
+
+#[test]
+fn parse_config() {
+    let config = Config::from_toml(
+        r#"
+        state_directory = "/var/lib/sportidentd"
+
+        [[readers]]
+        name = "finish"
+        port = "/dev/ttyUSB0"
+
+        [[readers]]
+        name = "radio"
+        mode = "punches"
+
+        [[sinks]]
+        name = "archive"
+        type = "jsonl"
+        path = "/var/lib/sportidentd/events.jsonl"
+
+        [[sinks]]
+        name = "results"
+        type = "webhook"
+        url = "http://localhost:8080/readouts"
+
+        [[sinks]]
+        name = "console"
+        type = "stdout"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.state_directory,
+        PathBuf::from("/var/lib/sportidentd")
+    );
+    assert_eq!(
+        config.readers,
+        vec![
+            ReaderConfig {
+                name: "finish".to_string(),
+                port: Some("/dev/ttyUSB0".to_string()),
+                mode: ReaderMode::Readout,
+            },
+            ReaderConfig {
+                name: "radio".to_string(),
+                port: None,
+                mode: ReaderMode::Punches,
+            },
+        ]
+    );
+    assert_eq!(
+        config.sinks[1],
+        SinkConfig {
+            name: "results".to_string(),
+            sink: Sink::Webhook {
+                url: "http://localhost:8080/readouts".to_string()
+            },
+        }
+    );
+    assert_eq!(config.sinks[2].sink, Sink::Stdout);
+    assert_eq!(
+        config.outbox_path(&config.sinks[0]),
+        PathBuf::from("/var/lib/sportidentd/archive.outbox")
+    );
+}
+
+#[test]
+fn parse_invalid_config() {
+    let result = Config::from_toml(
+        r#"
+        state_directory = "/tmp"
+
+        [[sinks]]
+        name = "archive"
+        type = "kafka"
+        "#,
+    );
+
+    assert!(matches!(result, Err(Error::InvalidConfig(_))));
+}
+
+#[test]
+fn outbox_is_persisted() {
+    let path = state_directory("outbox").join("sink.outbox");
+    let mut outbox = Outbox::open(&path).unwrap();
+    assert!(outbox.is_empty());
+
+    outbox.push(readout_event(7_001_234)).unwrap();
+    outbox.push(punch_event()).unwrap();
+    outbox.push(readout_event(7_001_235)).unwrap();
+    assert_eq!(outbox.pop().unwrap(), Some(readout_event(7_001_234)));
+
+    let mut outbox = Outbox::open(&path).unwrap();
+    assert_eq!(outbox.len(), 2);
+    assert_eq!(outbox.front(), Some(&punch_event()));
+    outbox.pop().unwrap();
+    outbox.pop().unwrap();
+    assert_eq!(outbox.pop().unwrap(), None);
+
+    assert!(Outbox::open(&path).unwrap().is_empty());
+}
+
+#[test]
+fn outbox_is_appended_while_delivering() {
+    let path = state_directory("outbox-ack").join("sink.outbox");
+    let mut outbox = Outbox::open(&path).unwrap();
+    outbox.push(readout_event(7_001_234)).unwrap();
+    outbox.push(punch_event()).unwrap();
+    let length = std::fs::metadata(&path).unwrap().len();
+
+    outbox.pop().unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), length);
+    outbox.push(readout_event(7_001_235)).unwrap();
+    let length = std::fs::metadata(&path).unwrap().len();
+
+    let mut outbox = Outbox::open(&path).unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() < length);
+    assert_eq!(outbox.pop().unwrap(), Some(punch_event()));
+    assert_eq!(outbox.pop().unwrap(), Some(readout_event(7_001_235)));
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    assert!(Outbox::open(&path).unwrap().is_empty());
+}
+
+#[test]
+fn invalid_outbox_file() {
+    let path = state_directory("invalid-outbox").join("sink.outbox");
+    std::fs::write(&path, "\n{\"type\":\"readout\"}\n").unwrap();
+
+    assert!(matches!(
+        Outbox::open(&path),
+        Err(Error::InvalidOutboxFile(2))
+    ));
+}
+
+#[tokio::test]
+async fn jsonl_sink() {
+    let path = state_directory("jsonl").join("events.jsonl");
+    let sink = Sink::Jsonl { path: path.clone() };
+
+    sink.deliver(&readout_event(7_001_234)).await.unwrap();
+    sink.deliver(&punch_event()).await.unwrap();
+
+    let events = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Event>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(events, vec![readout_event(7_001_234), punch_event()]);
+}
+
+#[tokio::test]
+async fn csv_sink() {
+    let path = state_directory("csv").join("readouts.csv");
+    let sink = Sink::Csv { path: path.clone() };
+
+    sink.deliver(&readout_event(7_001_234)).await.unwrap();
+    sink.deliver(&punch_event()).await.unwrap();
+    sink.deliver(&readout_event(7_001_235)).await.unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(content.matches("SIID").count(), 1);
+    let records = ReadoutReader::new(content.as_bytes())
+        .unwrap()
+        .collect::<crate::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        records
+            .iter()
+            .map(|record| record.readout.card_number)
+            .collect::<Vec<_>>(),
+        vec![7_001_234, 7_001_235]
+    );
+    assert_eq!(records[0].readout.punches.len(), 2);
+    let numbers = content
+        .lines()
+        .map(|line| line.split(';').next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, ["No", "1", "2"]);
+}
+
+#[tokio::test]
+async fn webhook_sink() {
+    let (url, server) = webhook("200 OK").await;
+
+    Sink::Webhook { url }.deliver(&punch_event()).await.unwrap();
+
+    let body = server.await.unwrap();
+    assert_eq!(serde_json::from_str::<Event>(&body).unwrap(), punch_event());
+}
+
+#[tokio::test]
+async fn webhook_sink_rejected() {
+    let (url, server) = webhook("500 Internal Server Error").await;
+
+    let result = Sink::Webhook { url }.deliver(&punch_event()).await;
+
+    assert!(matches!(result, Err(Error::SinkFailed(_))));
+    server.await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn unix_socket_sink() {
+    let path = state_directory("unix-socket").join("events.sock");
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+
+    Sink::UnixSocket { path }
+        .deliver(&readout_event(7_001_234))
+        .await
+        .unwrap();
+
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut line = String::new();
+    stream.read_to_string(&mut line).await.unwrap();
+    assert_eq!(
+        serde_json::from_str::<Event>(line.trim_end()).unwrap(),
+        readout_event(7_001_234)
+    );
+}
+
+#[tokio::test]
+async fn worker_keeps_events_while_sink_is_down() {
+    let outbox_path = state_directory("worker").join("results.outbox");
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/events", listener.local_addr().unwrap());
+    drop(listener);
+
+    let worker = SinkWorker::new(Sink::Webhook { url }, Outbox::open(&outbox_path).unwrap());
+    worker
+        .outbox()
+        .lock()
+        .unwrap()
+        .push(readout_event(7_001_234))
+        .unwrap();
+    let (_, wake_ups) = mpsc::channel(1);
+    let failures = Cell::new(0);
+    let worker = worker
+        .run(wake_ups, |_, _| failures.set(failures.get() + 1))
+        .await
+        .unwrap();
+    assert_eq!(failures.get(), 1);
+    assert_eq!(
+        worker.outbox().lock().unwrap().front(),
+        Some(&readout_event(7_001_234))
+    );
+
+    let (url, server) = webhook("204 No Content").await;
+    let (_, wake_ups) = mpsc::channel(1);
+    let worker = SinkWorker::new(Sink::Webhook { url }, Outbox::open(&outbox_path).unwrap());
+    let worker = worker.run(wake_ups, |_, _| unreachable!()).await.unwrap();
+
+    assert!(worker.outbox().lock().unwrap().is_empty());
+    let body = server.await.unwrap();
+    assert_eq!(
+        serde_json::from_str::<Event>(&body).unwrap(),
+        readout_event(7_001_234)
+    );
+}
+
+#[tokio::test]
+async fn worker_wakes_up() {
+    let path = state_directory("wake-up").join("events.jsonl");
+    let outbox_path = path.with_extension("outbox");
+    let worker = SinkWorker::new(
+        Sink::Jsonl { path: path.clone() },
+        Outbox::open(&outbox_path).unwrap(),
+    );
+    let outbox = worker.outbox().clone();
+    let (wake_up, wake_ups) = mpsc::channel(1);
+    let worker = tokio::spawn(worker.run(wake_ups, |_, _| unreachable!()));
+
+    outbox.lock().unwrap().push(punch_event()).unwrap();
+    wake_up.send(()).await.unwrap();
+    outbox
+        .lock()
+        .unwrap()
+        .push(readout_event(7_001_234))
+        .unwrap();
+    wake_up.send(()).await.unwrap();
+    drop(wake_up);
+    worker.await.unwrap().unwrap();
+
+    assert!(outbox.lock().unwrap().is_empty());
+    let events = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Event>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(events, vec![punch_event(), readout_event(7_001_234)]);
+}
+
+#[tokio::test]
+async fn worker_numbers_csv_rows_after_existing_ones() {
+    let path = state_directory("csv-worker").join("readouts.csv");
+    let sink = Sink::Csv { path: path.clone() };
+    sink.deliver(&readout_event(7_001_234)).await.unwrap();
+    let worker = SinkWorker::new(sink, Outbox::open(path.with_extension("outbox")).unwrap());
+    {
+        let mut outbox = worker.outbox().lock().unwrap();
+        outbox.push(readout_event(7_001_235)).unwrap();
+        outbox.push(readout_event(7_001_236)).unwrap();
+    }
+
+    let (_, wake_ups) = mpsc::channel(1);
+    worker.run(wake_ups, |_, _| unreachable!()).await.unwrap();
+
+    let numbers = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| line.split(';').next().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, ["No", "1", "2", "3"]);
+}
+
+#[tokio::test]
+async fn worker_stops_on_corrupt_csv() {
+    let path = state_directory("csv-corrupt").join("readouts.csv");
+    let sink = Sink::Csv { path: path.clone() };
+    sink.deliver(&readout_event(7_001_234)).await.unwrap();
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(b"2;not a card\n")
+        .unwrap();
+    let worker = SinkWorker::new(sink, Outbox::open(path.with_extension("outbox")).unwrap());
+    let outbox = worker.outbox().clone();
+    outbox
+        .lock()
+        .unwrap()
+        .push(readout_event(7_001_235))
+        .unwrap();
+
+    let (_, wake_ups) = mpsc::channel(1);
+    let result = worker.run(wake_ups, |_, _| unreachable!()).await;
+
+    assert!(result.is_err());
+    assert_eq!(
+        outbox.lock().unwrap().front(),
+        Some(&readout_event(7_001_235))
+    );
+}
+
+#[tokio::test]
+async fn sirap_sink() {
+    let mut listener = SirapListener::bind("127.0.0.1:0").await.unwrap();
+    let sink = Sink::Sirap {
+        address: listener.local_address().to_string(),
+    };
+
+    sink.deliver(&punch_event()).await.unwrap();
+    sink.deliver(&readout_event(7_001_234)).await.unwrap();
+
+    let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    assert_eq!(
+        listener.next_record().await,
+        Some(SirapRecord::Punch {
+            card_number: 7_001_234,
+            station_code: 31,
+            time: date.and_hms_milli_opt(10, 4, 10, 500).unwrap(),
+        })
+    );
+    let Some(SirapRecord::Readout {
+        finish, punches, ..
+    }) = listener.next_record().await
+    else {
+        panic!("expected a readout record");
+    };
+    assert_eq!(finish, date.and_hms_opt(10, 12, 30).unwrap());
+    assert_eq!(punches.len(), 2);
+}
+
+#[cfg(feature = "simulator")]
+#[tokio::test]
+async fn readout_is_persisted_before_beep() {
+    use std::sync::{Arc, Mutex};
+
+    use crate::daemon::{poll_reader, SinkInbox};
+    use crate::simulator::tests::{si8_image, si8_readout};
+    use crate::simulator::SimulatedStation;
+    use crate::StationMode;
+
+    let path = state_directory("beep").join("sink.outbox");
+    let (station, mut reader) = SimulatedStation::connect(StationMode::Readout, 10)
+        .await
+        .unwrap();
+    let expected = si8_readout().await;
+    let (wake_up, mut wake_ups) = mpsc::channel(1);
+    let sinks = [SinkInbox {
+        outbox: Arc::new(Mutex::new(Outbox::open(&path).unwrap())),
+        wake_up,
+    }];
+    let config = ReaderConfig {
+        name: "finish".to_string(),
+        port: None,
+        mode: ReaderMode::Readout,
+    };
+    let poll = tokio::spawn(async move { poll_reader(&config, &mut reader, &sinks).await });
+
+    station.insert_card(expected.card_number, si8_image());
+    while station.beeps() == 0 {
+        tokio::task::yield_now().await;
+    }
+    let outbox = Outbox::open(&path).unwrap();
+    assert_eq!(outbox.len(), 1);
+    let Some(Event::Readout {
+        reader, readout, ..
+    }) = outbox.front()
+    else {
+        panic!("expected a readout event");
+    };
+    assert_eq!(reader, "finish");
+    assert_eq!(readout.data, expected);
+    assert_eq!(wake_ups.try_recv(), Ok(()));
+    poll.abort();
+}
@@ -30,6 +30,59 @@ pub enum Error {
     NotAutoSendMode,
     #[error("No reader detected")]
     NoReaderDetected,
+    #[error("Invalid backup cursor file (line {0})")]
+    InvalidBackupCursorFile(usize),
+    #[error("Backup memory holds {0} records that were not synchronized yet, synchronize first or force clearing")]
+    BackupNotSynchronized(u32),
+    #[error(
+        "Invalid backup memory address ({0:#x}), must be a record address inside the backup memory"
+    )]
+    InvalidBackupAddress(u32),
+    #[error("Invalid station code ({0}), must be at most 1023")]
+    InvalidStationCode(u16),
+    #[error("A remote station is already coupled to this reader")]
+    RemoteStationAlreadyCoupled,
+    #[error("This feature only supports SRR stations")]
+    NotSRRStation,
+    #[cfg(feature = "iof")]
+    #[error("XML error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+    #[cfg(feature = "iof")]
+    #[error("Invalid IOF XML: {0}")]
+    InvalidIofXml(String),
+    #[cfg(feature = "csv")]
+    #[error("CSV error: {0}")]
+    CsvError(#[from] csv::Error),
+    #[cfg(feature = "csv")]
+    #[error("Invalid CSV record (line {0}): {1}")]
+    InvalidCsvRecord(u64, String),
+    #[cfg(feature = "daemon")]
+    #[error("Invalid daemon configuration: {0}")]
+    InvalidConfig(String),
+    #[cfg(feature = "daemon")]
+    #[error("Invalid outbox file (line {0})")]
+    InvalidOutboxFile(usize),
+    #[cfg(feature = "daemon")]
+    #[error("Sink failed: {0}")]
+    SinkFailed(String),
+    #[cfg(feature = "sirap")]
+    #[error("Invalid SIRAP record: {0}")]
+    InvalidSirapRecord(String),
+    #[cfg(feature = "printout")]
+    #[error("Invalid printout template: {0}")]
+    InvalidPrintoutTemplate(String),
+    #[cfg(feature = "store")]
+    #[error("SQLite error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+    #[cfg(feature = "store")]
+    #[error("Invalid data in store: {0}")]
+    InvalidStoreData(String),
+    #[cfg(feature = "store")]
+    #[error("Store schema version {0} is newer than this version supports")]
+    UnsupportedStoreVersion(u32),
+    #[cfg(feature = "journal")]
+    #[error("Invalid journal entry (offset {0})")]
+    InvalidJournalEntry(usize),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,193 @@
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+
+use crate::backup::RECORD_SIZE;
+use crate::{CardPunch, PunchSource, Reader, Result, BACKUP_MEMORY_START};
+
+/// Backup memory addresses of a station whose punches never reached the reader.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PunchGap {
+    pub station_code: u16,
+    /// The missing addresses. Gaps in radio punches are kept as received: a range ending
+    /// before its start wraps around the end of the backup memory of the station, if its
+    /// memory overflowed.
+    pub addresses: Range<u32>,
+}
+
+/// A stream of auto-sent punches that detects missing backup memory addresses.
+///
+/// Gaps in punches of the connected station are filled from its backup memory, the
+/// backfilled punches are yielded with [`PunchSource::Backup`] before the punch that revealed
+/// the gap. Gaps in radio punches can't be read over the air, they are collected in
+/// [`PunchFeed::gaps`] and filled by [`PunchFeed::backfill_gaps`] once the station is placed
+/// on the coupling coil of the connected station.
+pub struct PunchFeed<'a> {
+    reader: &'a mut Reader,
+    next_addresses: HashMap<u16, u32>,
+    gaps: Vec<PunchGap>,
+    queue: VecDeque<CardPunch>,
+}
+
+impl Reader {
+    pub fn punch_feed(&mut self) -> PunchFeed<'_> {
+        PunchFeed {
+            reader: self,
+            next_addresses: HashMap::new(),
+            gaps: Vec::new(),
+            queue: VecDeque::new(),
+        }
+    }
+}
+
+impl PunchFeed<'_> {
+    /// Continue after a disconnect, expecting the next punch of a station at `next_address`.
+    #[must_use]
+    pub fn resume(mut self, station_code: u16, next_address: u32) -> Self {
+        self.next_addresses.insert(station_code, next_address);
+        self
+    }
+
+    /// The backup address expected for the next punch of each station, persist these to
+    /// [`PunchFeed::resume`] after a disconnect.
+    #[must_use]
+    pub const fn next_addresses(&self) -> &HashMap<u16, u32> {
+        &self.next_addresses
+    }
+
+    /// Gaps that could not be filled from the backup memory of the connected station.
+    #[must_use]
+    pub fn gaps(&self) -> &[PunchGap] {
+        &self.gaps
+    }
+
+    pub async fn next_punch(&mut self) -> Result<CardPunch> {
+        loop {
+            if let Some(punch) = self.queue.pop_front() {
+                return Ok(punch);
+            }
+
+            let punch = self.reader.poll_punch().await?;
+            let Some(address) = punch.backup_address else {
+                return Ok(punch);
+            };
+            let station_code = punch.station_code;
+            if punch.source != PunchSource::Direct {
+                // The size of the backup memory of the sending station is unknown until it is
+                // coupled, so the gap is kept as is and wrapped around by `backfill_gaps`.
+                let next_address = address + u32::from(RECORD_SIZE);
+                let expected = self.next_addresses.insert(station_code, next_address);
+                if let Some(expected) = expected.filter(|expected| *expected != address) {
+                    self.gaps.push(PunchGap {
+                        station_code,
+                        addresses: expected..address,
+                    });
+                }
+                return Ok(punch);
+            }
+
+            let end = self.reader.system_configuration().backup_memory_end();
+            let mut next_address = address + u32::from(RECORD_SIZE);
+            if next_address >= end {
+                next_address = BACKUP_MEMORY_START;
+            }
+            let Some(expected) = self.next_addresses.insert(station_code, next_address) else {
+                return Ok(punch);
+            };
+            // The overflow flag is set once the memory wraps around, which may have happened
+            // since the configuration was read.
+            let overflow = if address < expected {
+                match self.reader.refresh_system_configuration().await {
+                    Ok(system_configuration) => system_configuration.memory_overflow,
+                    Err(e) => {
+                        self.queue.push_back(punch);
+                        return Err(e);
+                    }
+                }
+            } else {
+                false
+            };
+            let missing = missing_addresses(expected, address, overflow, end);
+            if missing.is_empty() {
+                return Ok(punch);
+            }
+            self.queue.push_back(punch);
+
+            let mut backfilled = Vec::new();
+            for (i, addresses) in missing.iter().enumerate() {
+                match self.reader.read_backup_records(addresses.clone()).await {
+                    Ok(records) => backfilled.extend(records),
+                    Err(e) => {
+                        self.gaps
+                            .extend(missing[i..].iter().map(|addresses| PunchGap {
+                                station_code,
+                                addresses: addresses.clone(),
+                            }));
+                        return Err(e);
+                    }
+                }
+            }
+            for record in backfilled.into_iter().rev() {
+                self.queue
+                    .push_front(CardPunch::from_backup_record(record, station_code));
+            }
+        }
+    }
+
+    /// Fill the gaps in the punches of the station on the coupling coil of the connected
+    /// station from its backup memory, returning the missing punches.
+    ///
+    /// Gaps of other stations are kept in [`PunchFeed::gaps`] until they are coupled in turn.
+    pub async fn backfill_gaps(&mut self) -> Result<Vec<CardPunch>> {
+        let mut remote = self.reader.remote_station().await?;
+        let station_code = remote.system_configuration().station_code;
+        let end = remote.system_configuration().backup_memory_end();
+        let overflow = remote.system_configuration().memory_overflow;
+        let mut punches = Vec::new();
+        while let Some(index) = self
+            .gaps
+            .iter()
+            .position(|gap| gap.station_code == station_code)
+        {
+            let Range {
+                mut start,
+                end: address,
+            } = self.gaps[index].addresses.clone();
+            if start >= end {
+                start = BACKUP_MEMORY_START;
+            }
+            for addresses in missing_addresses(start, address, overflow, end) {
+                let records = remote.read_backup_records(addresses).await?;
+                punches.extend(
+                    records
+                        .into_iter()
+                        .map(|record| CardPunch::from_backup_record(record, station_code)),
+                );
+            }
+            self.gaps.remove(index);
+        }
+        remote.close().await?;
+
+        Ok(punches)
+    }
+}
+
+/// The backup addresses between the expected address of a punch and its actual address.
+fn missing_addresses(expected: u32, address: u32, overflow: bool, end: u32) -> Vec<Range<u32>> {
+    if address >= expected {
+        return std::iter::once(expected..address)
+            .filter(|range| !range.is_empty())
+            .collect();
+    }
+    // The address went backwards: either the memory wrapped around, or it was cleared.
+    if overflow && expected < end {
+        return [expected..end, BACKUP_MEMORY_START..address]
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect();
+    }
+
+    vec![]
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,184 @@
+#![allow(clippy::single_range_in_vec_init)]
+
+use crate::feed::missing_addresses;
+use crate::BACKUP_MEMORY_START;
+
+const END: u32 = 128 * 1024;
+
+#[test]
+fn no_gap() {
+    assert_eq!(missing_addresses(0x108, 0x108, false, END), vec![]);
+}
+
+#[test]
+fn gap() {
+    assert_eq!(
+        missing_addresses(0x108, 0x120, false, END),
+        vec![0x108..0x120]
+    );
+}
+
+#[test]
+fn gap_across_wrap_around() {
+    assert_eq!(
+        missing_addresses(END - 0x10, 0x108, true, END),
+        vec![END - 0x10..END, BACKUP_MEMORY_START..0x108]
+    );
+}
+
+#[test]
+fn cleared_memory() {
+    assert_eq!(missing_addresses(0x400, 0x108, false, END), vec![]);
+}
+
+#[cfg(feature = "simulator")]
+mod station {
+    use chrono::NaiveTime;
+
+    use crate::feed::PunchGap;
+    use crate::simulator::tests::{configure_backup_memory, direct_punch};
+    use crate::simulator::SimulatedStation;
+    use crate::{CardPunch, PunchSource, SRRChannel, StationMode, BACKUP_MEMORY_START};
+
+    /// Punches stored by `station` in its backup memory, starting at the backup pointer.
+    fn store_punches(
+        station: &SimulatedStation,
+        station_code: u16,
+        source: PunchSource,
+        first_address: u32,
+        count: u32,
+    ) -> Vec<CardPunch> {
+        (0..count)
+            .map(|i| {
+                let mut punch = direct_punch(
+                    7_001_234 + i,
+                    station_code,
+                    NaiveTime::from_hms_opt(10, 0, i).unwrap(),
+                );
+                station.store_punch(&punch);
+                punch.source = source;
+                punch.backup_address = Some(first_address + 8 * i);
+                punch
+            })
+            .collect()
+    }
+
+    fn backfilled(punch: &CardPunch) -> CardPunch {
+        CardPunch {
+            source: PunchSource::Backup,
+            ..punch.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn direct_gap() {
+        let (station, mut reader) = SimulatedStation::connect(StationMode::Control, 31)
+            .await
+            .unwrap();
+        let punches = store_punches(&station, 31, PunchSource::Direct, BACKUP_MEMORY_START, 4);
+        // The last punch arrives while the gap is being read from the backup memory.
+        for punch in [&punches[0], &punches[2], &punches[3]] {
+            station.punch(punch.clone());
+        }
+
+        let mut feed = reader.punch_feed();
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            received.push(feed.next_punch().await.unwrap());
+        }
+
+        assert_eq!(
+            received,
+            vec![
+                punches[0].clone(),
+                backfilled(&punches[1]),
+                punches[2].clone(),
+                punches[3].clone(),
+            ]
+        );
+        assert_eq!(feed.gaps(), &[]);
+    }
+
+    #[tokio::test]
+    async fn direct_gap_across_wrap_around() {
+        let (station, mut reader) = SimulatedStation::connect(StationMode::Control, 31)
+            .await
+            .unwrap();
+        let end = reader.system_configuration().backup_memory_end();
+        reader.set_backup_pointer(end - 16).await.unwrap();
+        let punches = store_punches(&station, 31, PunchSource::Direct, end - 16, 3);
+        let mut last = punches[2].clone();
+        last.backup_address = Some(BACKUP_MEMORY_START);
+        station.punch(punches[0].clone());
+        station.punch(last.clone());
+
+        let mut feed = reader.punch_feed();
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(feed.next_punch().await.unwrap());
+        }
+
+        assert_eq!(
+            received,
+            vec![punches[0].clone(), backfilled(&punches[1]), last]
+        );
+    }
+
+    #[tokio::test]
+    async fn radio_gap() {
+        let (station, mut reader) = SimulatedStation::connect(StationMode::Control, 10)
+            .await
+            .unwrap();
+        let (remote, _) = SimulatedStation::spawn(StationMode::Control, 31);
+        let radio = PunchSource::Radio(SRRChannel::Red);
+        let punches = store_punches(&remote, 31, radio, BACKUP_MEMORY_START, 3);
+        station.punch(punches[0].clone());
+        station.punch(punches[2].clone());
+
+        let mut feed = reader.punch_feed();
+        assert_eq!(feed.next_punch().await.unwrap(), punches[0]);
+        assert_eq!(feed.next_punch().await.unwrap(), punches[2]);
+        assert_eq!(
+            feed.gaps(),
+            &[PunchGap {
+                station_code: 31,
+                addresses: BACKUP_MEMORY_START + 8..BACKUP_MEMORY_START + 16,
+            }]
+        );
+
+        station.couple(&remote);
+        assert_eq!(
+            feed.backfill_gaps().await.unwrap(),
+            vec![backfilled(&punches[1])]
+        );
+        assert_eq!(feed.gaps(), &[]);
+    }
+
+    #[tokio::test]
+    async fn radio_gap_across_wrap_around() {
+        let (station, mut reader) = SimulatedStation::connect(StationMode::Control, 10)
+            .await
+            .unwrap();
+        // The remote station wraps around long before the end of the dongle's memory.
+        let (remote, _) = SimulatedStation::spawn(StationMode::Control, 31);
+        let end = 64 * 1024;
+        configure_backup_memory(&remote, 64, end - 16);
+        let radio = PunchSource::Radio(SRRChannel::Red);
+        let punches = store_punches(&remote, 31, radio, end - 16, 3);
+        let mut last = punches[2].clone();
+        last.backup_address = Some(BACKUP_MEMORY_START);
+        station.punch(punches[0].clone());
+        station.punch(last.clone());
+
+        let mut feed = reader.punch_feed();
+        assert_eq!(feed.next_punch().await.unwrap(), punches[0]);
+        assert_eq!(feed.next_punch().await.unwrap(), last);
+
+        station.couple(&remote);
+        assert_eq!(
+            feed.backfill_gaps().await.unwrap(),
+            vec![backfilled(&punches[1])]
+        );
+        assert_eq!(feed.gaps(), &[]);
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use crate::iof::element::{invalid, Element};
+use crate::iof::{parse_event, Event};
+use crate::{Course, CourseControl};
+
+/// Course definitions from an IOF XML `CourseData` document, as exported by course setting
+/// programs.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct CourseData {
+    pub event: Option<Event>,
+    /// Courses by name. Start and finish controls are not part of the courses.
+    pub courses: BTreeMap<String, Course>,
+    /// Course names by class name.
+    pub class_courses: BTreeMap<String, String>,
+}
+
+impl CourseData {
+    pub fn from_xml(xml: &str) -> crate::Result<Self> {
+        let root = Element::parse(xml)?;
+        if root.name != "CourseData" {
+            return Err(invalid(format!("expected CourseData, found {}", root.name)));
+        }
+
+        let mut course_data = Self {
+            event: root.child("Event").map(parse_event).transpose()?,
+            ..Self::default()
+        };
+        for race in root.children("RaceCourseData") {
+            for course in race.children("Course") {
+                let name = course
+                    .text("Name")
+                    .ok_or_else(|| invalid("course without a name"))?;
+                course_data
+                    .courses
+                    .insert(name.to_string(), parse_course(course)?);
+            }
+            for assignment in race.children("ClassCourseAssignment") {
+                if let (Some(class), Some(course)) =
+                    (assignment.text("ClassName"), assignment.text("CourseName"))
+                {
+                    course_data
+                        .class_courses
+                        .insert(class.to_string(), course.to_string());
+                }
+            }
+        }
+        Ok(course_data)
+    }
+
+    #[must_use]
+    pub fn course_for_class(&self, class: &str) -> Option<&Course> {
+        self.courses.get(self.class_courses.get(class)?)
+    }
+}
+
+/// Consecutive controls in random order form an unordered section, several controls of a
+/// single course control are alternatives.
+fn parse_course(course: &Element) -> crate::Result<Course> {
+    let mut parsed = Course::new();
+    let mut unordered = Vec::new();
+
+    for course_control in course.children("CourseControl") {
+        if course_control.attribute("type").unwrap_or("Control") != "Control" {
+            continue;
+        }
+
+        let codes = course_control
+            .children("Control")
+            .map(|control| {
+                control
+                    .text
+                    .parse::<u16>()
+                    .map_err(|_| invalid(format!("invalid control code {}", control.text)))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        let control = match codes.as_slice() {
+            [] => return Err(invalid("course control without a control")),
+            [code] => CourseControl::Control(*code),
+            _ => CourseControl::OneOf(codes),
+        };
+
+        if course_control.attribute("randomOrder") == Some("true") {
+            unordered.push(control);
+            continue;
+        }
+        if !unordered.is_empty() {
+            parsed = parsed.unordered(std::mem::take(&mut unordered));
+        }
+        parsed = match control {
+            CourseControl::Control(code) => parsed.control(code),
+            CourseControl::OneOf(codes) => parsed.one_of(codes),
+        };
+    }
+    if !unordered.is_empty() {
+        parsed = parsed.unordered(unordered);
+    }
+    Ok(parsed)
+}
@@ -0,0 +1,111 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::Error;
+
+/// An XML element with its attributes, text and child elements. IOF XML documents are small
+/// enough to be parsed into a tree before they are interpreted.
+#[derive(Debug, Default)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub text: String,
+    pub children: Vec<Self>,
+}
+
+impl Element {
+    /// Parses the root element of a document. Namespace prefixes are dropped from names.
+    pub fn parse(xml: &str) -> crate::Result<Self> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+        let mut stack = vec![Self::default()];
+        loop {
+            match reader.read_event()? {
+                Event::Start(start) => stack.push(Self::from_start(&start)?),
+                Event::Empty(start) => {
+                    let element = Self::from_start(&start)?;
+                    push_child(&mut stack, element)?;
+                }
+                Event::Text(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text.unescape()?);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&String::from_utf8_lossy(&data));
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or_else(|| invalid("unexpected end tag"))?;
+                    push_child(&mut stack, element)?;
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        match (stack.pop(), stack.is_empty()) {
+            (Some(document), true) => document
+                .children
+                .into_iter()
+                .next()
+                .ok_or_else(|| invalid("empty document")),
+            _ => Err(invalid("unclosed element")),
+        }
+    }
+
+    fn from_start(start: &BytesStart) -> crate::Result<Self> {
+        let attributes = start
+            .attributes()
+            .map(|attribute| {
+                let attribute = attribute.map_err(quick_xml::Error::from)?;
+                Ok((
+                    String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                    attribute.unescape_value()?.into_owned(),
+                ))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            attributes,
+            ..Self::default()
+        })
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Self> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Self> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// The text of the descendant at a `/` separated path of element names.
+    pub fn text(&self, path: &str) -> Option<&str> {
+        path.split('/')
+            .try_fold(self, |element, name| element.child(name))
+            .map(|element| element.text.as_str())
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidIofXml(message.into())
+}
+
+fn push_child(stack: &mut [Element], element: Element) -> crate::Result<()> {
+    stack
+        .last_mut()
+        .ok_or_else(|| invalid("unexpected element"))?
+        .children
+        .push(element);
+    Ok(())
+}
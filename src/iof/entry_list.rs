@@ -0,0 +1,68 @@
+use crate::iof::element::{invalid, Element};
+use crate::iof::{parse_event, parse_person, Event, Person};
+
+/// Entries from an IOF XML `EntryList` document.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct EntryList {
+    pub event: Option<Event>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Entry {
+    pub person: Person,
+    pub organisation: Option<String>,
+    /// The number of the SI card the competitor runs with.
+    pub card_number: Option<u32>,
+    pub class: Option<String>,
+}
+
+impl EntryList {
+    pub fn from_xml(xml: &str) -> crate::Result<Self> {
+        let root = Element::parse(xml)?;
+        if root.name != "EntryList" {
+            return Err(invalid(format!("expected EntryList, found {}", root.name)));
+        }
+
+        Ok(Self {
+            event: root.child("Event").map(parse_event).transpose()?,
+            entries: root
+                .children("PersonEntry")
+                .map(parse_entry)
+                .collect::<crate::Result<_>>()?,
+        })
+    }
+
+    #[must_use]
+    pub fn entry_for_card(&self, card_number: u32) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.card_number == Some(card_number))
+    }
+}
+
+fn parse_entry(entry: &Element) -> crate::Result<Entry> {
+    let person = entry
+        .child("Person")
+        .ok_or_else(|| invalid("person entry without a person"))?;
+    // Cards of other punching systems can't be read by this crate.
+    let card_number = entry
+        .children("ControlCard")
+        .find(|card| {
+            card.attribute("punchingSystem")
+                .is_none_or(|system| system.eq_ignore_ascii_case("SI"))
+        })
+        .map(|card| {
+            card.text
+                .parse()
+                .map_err(|_| invalid(format!("invalid card number {}", card.text)))
+        })
+        .transpose()?;
+
+    Ok(Entry {
+        person: parse_person(person),
+        organisation: entry.text("Organisation/Name").map(str::to_string),
+        card_number,
+        class: entry.text("Class/Name").map(str::to_string),
+    })
+}
@@ -0,0 +1,71 @@
+//! Import and export of [IOF XML 3.0](https://orienteering.sport/iof/it/data-standard-3-0/)
+//! documents.
+
+use std::io::Write;
+
+use chrono::NaiveDate;
+use quick_xml::events::BytesText;
+use quick_xml::Writer;
+
+pub use course_data::CourseData;
+pub use entry_list::{Entry, EntryList};
+pub use result_list::{ClassResult, PersonResult, ResultList};
+
+use crate::iof::element::{invalid, Element};
+
+mod course_data;
+mod element;
+mod entry_list;
+mod result_list;
+
+#[cfg(test)]
+mod tests;
+
+const NAMESPACE: &str = "http://www.orienteering.org/datastandard/3.0";
+const VERSION: &str = "3.0";
+const CREATOR: &str = "sportident-rs";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Event {
+    pub name: String,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Person {
+    pub id: Option<String>,
+    pub given_name: String,
+    pub family_name: String,
+}
+
+fn write_text<W: Write>(writer: &mut Writer<W>, name: &str, text: &str) -> std::io::Result<()> {
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::new(text))?;
+    Ok(())
+}
+
+fn write_name<W: Write>(writer: &mut Writer<W>, name: &str) -> std::io::Result<()> {
+    write_text(writer, "Name", name)
+}
+
+fn parse_event(event: &Element) -> crate::Result<Event> {
+    let date = event
+        .text("StartTime/Date")
+        .ok_or_else(|| invalid("event without a start date"))?;
+
+    Ok(Event {
+        name: event.text("Name").unwrap_or_default().to_string(),
+        date: date
+            .parse()
+            .map_err(|_| invalid(format!("invalid date {date}")))?,
+    })
+}
+
+fn parse_person(person: &Element) -> Person {
+    Person {
+        id: person.text("Id").map(str::to_string),
+        given_name: person.text("Name/Given").unwrap_or_default().to_string(),
+        family_name: person.text("Name/Family").unwrap_or_default().to_string(),
+    }
+}
@@ -0,0 +1,441 @@
+use std::io::Write;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use quick_xml::events::{BytesDecl, Event as XmlEvent};
+use quick_xml::Writer;
+
+use crate::iof::element::{invalid, Element};
+use crate::iof::{
+    parse_event, parse_person, write_name, write_text, Event, Person, CREATOR, NAMESPACE, VERSION,
+};
+use crate::results::elapsed;
+use crate::{
+    CardReadout, Course, CourseControl, CourseResult, CourseStatus, DayOfWeek, Punch, SplitControl,
+    WeekCounter,
+};
+
+/// The results of an event, as an IOF XML `ResultList`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResultList {
+    pub event: Event,
+    pub classes: Vec<ClassResult>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClassResult {
+    pub name: String,
+    pub results: Vec<PersonResult>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PersonResult {
+    pub person: Person,
+    pub organisation: Option<String>,
+    pub card_number: Option<u32>,
+    /// The validation result of the competitor's readout, `None` if they did not start.
+    pub result: Option<CourseResult>,
+}
+
+impl PersonResult {
+    /// Validates a readout against the course of the competitor's class.
+    #[must_use]
+    pub fn from_readout(person: Person, readout: &CardReadout, course: &Course) -> Self {
+        Self {
+            person,
+            organisation: None,
+            card_number: Some(readout.card_number),
+            result: Some(course.validate(readout)),
+        }
+    }
+}
+
+impl ResultList {
+    #[must_use]
+    pub const fn new(event: Event) -> Self {
+        Self {
+            event,
+            classes: Vec::new(),
+        }
+    }
+
+    pub fn write(&self, writer: impl Write) -> crate::Result<()> {
+        let mut writer = Writer::new_with_indent(writer, b' ', 2);
+        writer.write_event(XmlEvent::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        writer
+            .create_element("ResultList")
+            .with_attributes([
+                ("xmlns", NAMESPACE),
+                ("iofVersion", VERSION),
+                ("creator", CREATOR),
+                ("status", "Complete"),
+            ])
+            .write_inner_content(|writer| {
+                writer
+                    .create_element("Event")
+                    .write_inner_content(|writer| {
+                        write_name(writer, &self.event.name)?;
+                        writer
+                            .create_element("StartTime")
+                            .write_inner_content(|writer| {
+                                write_text(writer, "Date", &self.event.date.to_string())
+                            })?;
+                        Ok(())
+                    })?;
+                for class in &self.classes {
+                    self.write_class(writer, class)?;
+                }
+                Ok(())
+            })?;
+        Ok(())
+    }
+
+    pub fn to_xml(&self) -> crate::Result<String> {
+        let mut xml = Vec::new();
+        self.write(&mut xml)?;
+        Ok(String::from_utf8_lossy(&xml).into_owned())
+    }
+
+    /// Reads a result list back, for example to merge results of another program. IOF XML has
+    /// no week counters, so punches are in the first week, on the weekday of their date.
+    /// Additional punches without a time are dropped.
+    pub fn from_xml(xml: &str) -> crate::Result<Self> {
+        let root = Element::parse(xml)?;
+        if root.name != "ResultList" {
+            return Err(invalid(format!("expected ResultList, found {}", root.name)));
+        }
+
+        Ok(Self {
+            event: parse_event(
+                root.child("Event")
+                    .ok_or_else(|| invalid("result list without an event"))?,
+            )?,
+            classes: root
+                .children("ClassResult")
+                .map(parse_class_result)
+                .collect::<crate::Result<_>>()?,
+        })
+    }
+
+    fn write_class<W: Write>(
+        &self,
+        writer: &mut Writer<W>,
+        class: &ClassResult,
+    ) -> std::io::Result<()> {
+        let ok_times = class
+            .results
+            .iter()
+            .filter_map(|result| result.result.as_ref())
+            .filter(|result| result.status == CourseStatus::Ok)
+            .filter_map(|result| result.running_time)
+            .collect::<Vec<_>>();
+        let best = ok_times.iter().min().copied();
+
+        writer
+            .create_element("ClassResult")
+            .write_inner_content(|writer| {
+                writer
+                    .create_element("Class")
+                    .write_inner_content(|writer| write_name(writer, &class.name))?;
+                for result in &class.results {
+                    self.write_person_result(writer, result, best, &ok_times)?;
+                }
+                Ok(())
+            })?;
+        Ok(())
+    }
+
+    fn write_person_result<W: Write>(
+        &self,
+        writer: &mut Writer<W>,
+        result: &PersonResult,
+        best: Option<TimeDelta>,
+        ok_times: &[TimeDelta],
+    ) -> std::io::Result<()> {
+        writer
+            .create_element("PersonResult")
+            .write_inner_content(|writer| {
+                write_person(writer, &result.person)?;
+                if let Some(organisation) = &result.organisation {
+                    writer
+                        .create_element("Organisation")
+                        .write_inner_content(|writer| write_name(writer, organisation))?;
+                }
+                writer
+                    .create_element("Result")
+                    .write_inner_content(|writer| {
+                        if let Some(course_result) = &result.result {
+                            self.write_course_result(writer, course_result, best, ok_times)?;
+                        } else {
+                            write_text(writer, "Status", "DidNotStart")?;
+                        }
+                        if let Some(card_number) = result.card_number {
+                            write_text(writer, "ControlCard", &card_number.to_string())?;
+                        }
+                        Ok(())
+                    })?;
+                Ok(())
+            })?;
+        Ok(())
+    }
+
+    fn write_course_result<W: Write>(
+        &self,
+        writer: &mut Writer<W>,
+        result: &CourseResult,
+        best: Option<TimeDelta>,
+        ok_times: &[TimeDelta],
+    ) -> std::io::Result<()> {
+        if let Some(start) = result.start {
+            write_date_time(writer, "StartTime", self.event.date, start, None)?;
+        }
+        if let Some(finish) = result.finish {
+            write_date_time(writer, "FinishTime", self.event.date, finish, result.start)?;
+        }
+        if let Some(running_time) = result.running_time {
+            write_seconds(writer, "Time", running_time)?;
+            if result.status == CourseStatus::Ok {
+                if let Some(best) = best {
+                    write_seconds(writer, "TimeBehind", running_time - best)?;
+                }
+                let position = ok_times.iter().filter(|time| **time < running_time).count() + 1;
+                write_text(writer, "Position", &position.to_string())?;
+            }
+        }
+        write_text(writer, "Status", status(result.status))?;
+
+        // Controls in course order, missing ones between the punched ones around them.
+        let mut missing = result.missing.iter().zip(&result.missing_after).peekable();
+        let controls = result
+            .splits()
+            .into_iter()
+            .filter_map(|split| match split.to {
+                SplitControl::Control(code) => Some((code, split.split_time)),
+                _ => None,
+            });
+        for (index, (code, split_time)) in controls.enumerate() {
+            while let Some((control, _)) = missing.next_if(|(_, after)| **after <= index) {
+                write_missing(writer, control)?;
+            }
+            writer
+                .create_element("SplitTime")
+                .write_inner_content(|writer| {
+                    write_text(writer, "ControlCode", &code.to_string())?;
+                    write_seconds(writer, "Time", split_time)
+                })?;
+        }
+        for (control, _) in missing {
+            write_missing(writer, control)?;
+        }
+        for punch in &result.extra {
+            writer
+                .create_element("SplitTime")
+                .with_attribute(("status", "Additional"))
+                .write_inner_content(|writer| {
+                    write_text(writer, "ControlCode", &punch.code.to_string())?;
+                    if let Some(start) = result.start {
+                        write_seconds(writer, "Time", elapsed(start, punch.time))?;
+                    }
+                    Ok(())
+                })?;
+        }
+        Ok(())
+    }
+}
+
+fn write_missing<W: Write>(writer: &mut Writer<W>, control: &CourseControl) -> std::io::Result<()> {
+    let code = match control {
+        CourseControl::Control(code) => Some(*code),
+        CourseControl::OneOf(codes) => codes.first().copied(),
+    };
+    if let Some(code) = code {
+        writer
+            .create_element("SplitTime")
+            .with_attribute(("status", "Missing"))
+            .write_inner_content(|writer| write_text(writer, "ControlCode", &code.to_string()))?;
+    }
+    Ok(())
+}
+
+fn parse_class_result(class: &Element) -> crate::Result<ClassResult> {
+    Ok(ClassResult {
+        name: class.text("Class/Name").unwrap_or_default().to_string(),
+        results: class
+            .children("PersonResult")
+            .map(parse_person_result)
+            .collect::<crate::Result<_>>()?,
+    })
+}
+
+fn parse_person_result(person_result: &Element) -> crate::Result<PersonResult> {
+    let person = person_result
+        .child("Person")
+        .ok_or_else(|| invalid("person result without a person"))?;
+    let result = person_result.child("Result");
+    let card_number = result
+        .and_then(|result| result.text("ControlCard"))
+        .map(|card_number| {
+            card_number
+                .parse()
+                .map_err(|_| invalid(format!("invalid card number {card_number}")))
+        })
+        .transpose()?;
+
+    Ok(PersonResult {
+        person: parse_person(person),
+        organisation: person_result.text("Organisation/Name").map(str::to_string),
+        card_number,
+        result: match result {
+            Some(result) if result.text("Status") != Some("DidNotStart") => {
+                Some(parse_course_result(result)?)
+            }
+            _ => None,
+        },
+    })
+}
+
+fn parse_course_result(result: &Element) -> crate::Result<CourseResult> {
+    let start = result.text("StartTime").map(parse_date_time).transpose()?;
+    let finish = result.text("FinishTime").map(parse_date_time).transpose()?;
+    let punch = |code: &str, time: &str| -> crate::Result<Punch> {
+        let start = start.ok_or_else(|| invalid("split time without a start time"))?;
+        punch_at(code, start + parse_seconds(time)?)
+    };
+
+    let mut matched = Vec::new();
+    let mut missing = Vec::new();
+    let mut missing_after = Vec::new();
+    let mut extra = Vec::new();
+    for split in result.children("SplitTime") {
+        let code = split
+            .text("ControlCode")
+            .ok_or_else(|| invalid("split time without a control code"))?;
+        match (split.attribute("status"), split.text("Time")) {
+            (Some("Missing"), _) => {
+                missing.push(CourseControl::Control(parse_code(code)?));
+                missing_after.push(matched.len());
+            }
+            (Some("Additional"), Some(time)) => extra.push(punch(code, time)?),
+            (Some("Additional"), None) => {}
+            (_, Some(time)) => matched.push(punch(code, time)?),
+            (_, None) => return Err(invalid(format!("split time of {code} without a time"))),
+        }
+    }
+
+    let status = match result.text("Status") {
+        Some("OK") => CourseStatus::Ok,
+        Some("DidNotFinish") => CourseStatus::DidNotFinish,
+        Some("MissingPunch") if !missing.is_empty() => CourseStatus::Mispunch,
+        Some("MissingPunch") if start.is_none() => CourseStatus::MissingStart,
+        Some("MissingPunch") if finish.is_none() => CourseStatus::MissingFinish,
+        Some("MissingPunch") => CourseStatus::Mispunch,
+        status => {
+            return Err(invalid(format!(
+                "unsupported result status {}",
+                status.unwrap_or_default()
+            )))
+        }
+    };
+
+    Ok(CourseResult {
+        status,
+        matched,
+        missing,
+        missing_after,
+        extra,
+        start: start.map(|start| start.time()),
+        finish: finish.map(|finish| finish.time()),
+        running_time: result.text("Time").map(parse_seconds).transpose()?,
+    })
+}
+
+fn parse_date_time(date_time: &str) -> crate::Result<NaiveDateTime> {
+    date_time
+        .parse::<NaiveDateTime>()
+        .or_else(|_| {
+            chrono::DateTime::parse_from_rfc3339(date_time).map(|date_time| date_time.naive_local())
+        })
+        .map_err(|_| invalid(format!("invalid date and time {date_time}")))
+}
+
+/// Parses a time in seconds, dropping fractions like the results do.
+fn parse_seconds(seconds: &str) -> crate::Result<TimeDelta> {
+    let (whole, _) = seconds.split_once('.').unwrap_or((seconds, ""));
+    whole
+        .parse()
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .ok_or_else(|| invalid(format!("invalid time {seconds}")))
+}
+
+fn parse_code(code: &str) -> crate::Result<u16> {
+    code.parse()
+        .map_err(|_| invalid(format!("invalid control code {code}")))
+}
+
+fn punch_at(code: &str, date_time: NaiveDateTime) -> crate::Result<Punch> {
+    let weekday = date_time.weekday().num_days_from_monday();
+    Ok(Punch {
+        time: date_time.time(),
+        day_of_week: u8::try_from(weekday)
+            .ok()
+            .and_then(DayOfWeek::from_repr)
+            .ok_or_else(|| invalid(format!("invalid weekday {weekday}")))?,
+        week_counter: WeekCounter::First,
+        code: parse_code(code)?,
+    })
+}
+
+fn write_person<W: Write>(writer: &mut Writer<W>, person: &Person) -> std::io::Result<()> {
+    writer
+        .create_element("Person")
+        .write_inner_content(|writer| {
+            if let Some(id) = &person.id {
+                write_text(writer, "Id", id)?;
+            }
+            writer
+                .create_element("Name")
+                .write_inner_content(|writer| {
+                    write_text(writer, "Family", &person.family_name)?;
+                    write_text(writer, "Given", &person.given_name)
+                })?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+/// Writes a punch time on the event date. Times before `after` are on the next day.
+fn write_date_time<W: Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    date: NaiveDate,
+    time: NaiveTime,
+    after: Option<NaiveTime>,
+) -> std::io::Result<()> {
+    let mut date_time = NaiveDateTime::new(date, time);
+    if after.is_some_and(|after| time < after) {
+        date_time += TimeDelta::days(1);
+    }
+    write_text(
+        writer,
+        name,
+        &date_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    )
+}
+
+fn write_seconds<W: Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    time: TimeDelta,
+) -> std::io::Result<()> {
+    write_text(writer, name, &time.num_seconds().to_string())
+}
+
+const fn status(status: CourseStatus) -> &'static str {
+    match status {
+        CourseStatus::Ok => "OK",
+        CourseStatus::Mispunch | CourseStatus::MissingStart | CourseStatus::MissingFinish => {
+            "MissingPunch"
+        }
+        CourseStatus::DidNotFinish => "DidNotFinish",
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::NaiveDate;
+
+use crate::iof::{CourseData, Event};
+use crate::{Course, CourseControl};
+
+const COURSE_DATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CourseData xmlns="http://www.orienteering.org/datastandard/3.0" iofVersion="3.0" createTime="2024-05-30T20:15:00" creator="Purple Pen">
+  <Event>
+    <Name>Midsummer Middle</Name>
+    <StartTime><Date>2024-06-01</Date></StartTime>
+  </Event>
+  <RaceCourseData>
+    <Map><Scale>10000</Scale></Map>
+    <Control type="Start"><Id>STA1</Id></Control>
+    <Control><Id>31</Id></Control>
+    <Control><Id>32</Id></Control>
+    <Control type="Finish"><Id>FIN1</Id></Control>
+    <Course>
+      <Name>Long</Name>
+      <Length>5200</Length>
+      <CourseControl type="Start"><Control>STA1</Control></CourseControl>
+      <CourseControl type="Control"><Control>31</Control><LegLength>400</LegLength></CourseControl>
+      <CourseControl type="Control"><Control>32</Control><Control>42</Control></CourseControl>
+      <CourseControl type="Control" randomOrder="true"><Control>33</Control></CourseControl>
+      <CourseControl type="Control" randomOrder="true"><Control>34</Control></CourseControl>
+      <CourseControl type="Control"><Control>100</Control></CourseControl>
+      <CourseControl type="Finish"><Control>FIN1</Control></CourseControl>
+    </Course>
+    <Course>
+      <Name>Short</Name>
+      <CourseControl type="Start"><Control>STA1</Control></CourseControl>
+      <CourseControl><Control>31</Control></CourseControl>
+      <CourseControl type="Finish"><Control>FIN1</Control></CourseControl>
+    </Course>
+    <ClassCourseAssignment><ClassName>H21</ClassName><CourseName>Long</CourseName></ClassCourseAssignment>
+    <ClassCourseAssignment><ClassName>D21</ClassName><CourseName>Long</CourseName></ClassCourseAssignment>
+    <ClassCourseAssignment><ClassName>Open</ClassName><CourseName>Short</CourseName></ClassCourseAssignment>
+  </RaceCourseData>
+</CourseData>"#;
+
+#[test]
+fn courses() {
+    let course_data = CourseData::from_xml(COURSE_DATA).unwrap();
+
+    assert_eq!(
+        course_data.event,
+        Some(Event {
+            name: "Midsummer Middle".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+        })
+    );
+    assert_eq!(
+        course_data.courses["Long"],
+        Course::new()
+            .control(31)
+            .one_of([32, 42])
+            .unordered([33, 34])
+            .control(100)
+    );
+    assert_eq!(course_data.courses["Short"], Course::new().control(31));
+}
+
+#[test]
+fn class_courses() {
+    let course_data = CourseData::from_xml(COURSE_DATA).unwrap();
+
+    assert_eq!(
+        course_data.course_for_class("D21").unwrap().sections()[1],
+        crate::CourseSection::Ordered(CourseControl::OneOf(vec![32, 42]))
+    );
+    assert_eq!(
+        course_data.course_for_class("Open"),
+        Some(&Course::new().control(31))
+    );
+    assert_eq!(course_data.course_for_class("H10"), None);
+}
+
+#[test]
+fn invalid_control_code() {
+    let xml = COURSE_DATA.replace("<Control>100</Control>", "<Control>A1</Control>");
+
+    assert!(CourseData::from_xml(&xml).is_err());
+    assert!(CourseData::from_xml("<EntryList/>").is_err());
+}
@@ -0,0 +1,79 @@
+use crate::iof::{Entry, EntryList, Person};
+
+const ENTRY_LIST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<EntryList xmlns="http://www.orienteering.org/datastandard/3.0" iofVersion="3.0" createTime="2024-05-31T08:00:00" creator="Eventor">
+  <Event>
+    <Name>Midsummer Middle</Name>
+    <StartTime><Date>2024-06-01</Date></StartTime>
+  </Event>
+  <PersonEntry>
+    <Id>1001</Id>
+    <Person>
+      <Id type="Eventor">42</Id>
+      <Name><Family>Alexandersson</Family><Given>Tove</Given></Name>
+    </Person>
+    <Organisation><Id>7</Id><Name>Stora Tuna OK</Name></Organisation>
+    <ControlCard punchingSystem="Emit">123456</ControlCard>
+    <ControlCard punchingSystem="SI">7001234</ControlCard>
+    <Class><Name>D21</Name></Class>
+  </PersonEntry>
+  <PersonEntry>
+    <Person>
+      <Name><Family>Kyburz</Family><Given>Matthias</Given></Name>
+    </Person>
+    <Class><Name>H21</Name></Class>
+  </PersonEntry>
+</EntryList>"#;
+
+#[test]
+fn entries() {
+    let entry_list = EntryList::from_xml(ENTRY_LIST).unwrap();
+
+    assert_eq!(entry_list.event.unwrap().name, "Midsummer Middle");
+    assert_eq!(
+        entry_list.entries,
+        vec![
+            Entry {
+                person: Person {
+                    id: Some("42".to_string()),
+                    given_name: "Tove".to_string(),
+                    family_name: "Alexandersson".to_string(),
+                },
+                organisation: Some("Stora Tuna OK".to_string()),
+                card_number: Some(7_001_234),
+                class: Some("D21".to_string()),
+            },
+            Entry {
+                person: Person {
+                    id: None,
+                    given_name: "Matthias".to_string(),
+                    family_name: "Kyburz".to_string(),
+                },
+                organisation: None,
+                card_number: None,
+                class: Some("H21".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn entry_for_card() {
+    let entry_list = EntryList::from_xml(ENTRY_LIST).unwrap();
+
+    assert_eq!(
+        entry_list
+            .entry_for_card(7_001_234)
+            .unwrap()
+            .person
+            .family_name,
+        "Alexandersson"
+    );
+    assert!(entry_list.entry_for_card(123_456).is_none());
+}
+
+#[test]
+fn invalid_document() {
+    assert!(EntryList::from_xml("<EntryList><PersonEntry>").is_err());
+    assert!(EntryList::from_xml("<CourseData/>").is_err());
+}
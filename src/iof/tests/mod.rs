@@ -0,0 +1,16 @@
+#![allow(clippy::pedantic)]
+
+use crate::iof::element::Element;
+
+mod course_data;
+mod entry_list;
+mod result_list;
+
+impl Element {
+    fn child_names(&self) -> Vec<&str> {
+        self.children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect()
+    }
+}
@@ -0,0 +1,270 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::iof::element::Element;
+use crate::iof::{ClassResult, Event, Person, PersonResult, ResultList};
+use crate::test_support::punch;
+use crate::CardType::Si10;
+use crate::DayOfWeek::Sunday;
+use crate::StartOrFinishPunch::Normal;
+use crate::{CardReadout, Course, CourseControl, CourseStatus, Punch};
+
+fn readout(card_number: u32, start: &str, finish: &str, punches: &[(u16, &str)]) -> CardReadout {
+    CardReadout {
+        card_number,
+        card_type: Si10,
+        start: Some(Normal(punch(1, start))),
+        finish: Some(Normal(punch(2, finish))),
+        check: None,
+        punches: punches
+            .iter()
+            .map(|(code, time)| punch(*code, time))
+            .collect(),
+    }
+}
+
+fn person(given_name: &str, family_name: &str) -> Person {
+    Person {
+        id: None,
+        given_name: given_name.to_string(),
+        family_name: family_name.to_string(),
+    }
+}
+
+fn result_list() -> ResultList {
+    let course = Course::new().control(31).control(32);
+    let mut winner = PersonResult::from_readout(
+        person("Tove", "Alexandersson"),
+        &readout(
+            7_001_234,
+            "10:00:00",
+            "10:12:30",
+            &[(31, "10:04:10"), (32, "10:10:00")],
+        ),
+        &course,
+    );
+    winner.person.id = Some("42".to_string());
+    winner.organisation = Some("Stora Tuna OK".to_string());
+
+    ResultList {
+        event: Event {
+            name: "Midsummer Sprint & Middle".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+        },
+        classes: vec![ClassResult {
+            name: "D21".to_string(),
+            results: vec![
+                winner,
+                PersonResult::from_readout(
+                    person("Simona", "Aebersold"),
+                    &readout(
+                        7_004_321,
+                        "10:02:00",
+                        "10:16:00",
+                        &[(31, "10:06:00"), (32, "10:13:00")],
+                    ),
+                    &course,
+                ),
+                PersonResult::from_readout(
+                    person("Natalia", "Gemperle"),
+                    &readout(
+                        8_000_001,
+                        "23:55:00",
+                        "00:05:00",
+                        &[(33, "23:59:00"), (32, "00:02:00")],
+                    ),
+                    &course,
+                ),
+                PersonResult {
+                    person: person("Venla", "Harju"),
+                    organisation: None,
+                    card_number: None,
+                    result: None,
+                },
+            ],
+        }],
+    }
+}
+
+#[test]
+fn document() {
+    let root = Element::parse(&result_list().to_xml().unwrap()).unwrap();
+
+    assert_eq!(root.name, "ResultList");
+    assert_eq!(
+        root.attribute("xmlns"),
+        Some("http://www.orienteering.org/datastandard/3.0")
+    );
+    assert_eq!(root.attribute("iofVersion"), Some("3.0"));
+    assert_eq!(root.child_names(), ["Event", "ClassResult"]);
+    assert_eq!(
+        root.text("Event/Name").unwrap(),
+        "Midsummer Sprint & Middle"
+    );
+    assert_eq!(root.text("Event/StartTime/Date").unwrap(), "2024-06-01");
+    assert_eq!(root.text("ClassResult/Class/Name").unwrap(), "D21");
+}
+
+#[test]
+fn ok_result() {
+    let root = Element::parse(&result_list().to_xml().unwrap()).unwrap();
+    let person_results = root
+        .child("ClassResult")
+        .unwrap()
+        .children("PersonResult")
+        .collect::<Vec<_>>();
+    let winner = person_results[0];
+
+    assert_eq!(winner.child_names(), ["Person", "Organisation", "Result"]);
+    assert_eq!(winner.text("Person/Id").unwrap(), "42");
+    assert_eq!(winner.text("Person/Name/Family").unwrap(), "Alexandersson");
+    assert_eq!(winner.text("Person/Name/Given").unwrap(), "Tove");
+    assert_eq!(winner.text("Organisation/Name").unwrap(), "Stora Tuna OK");
+
+    let result = winner.child("Result").unwrap();
+    // Element order as defined by the `Result` type of the schema.
+    assert_eq!(
+        result.child_names(),
+        [
+            "StartTime",
+            "FinishTime",
+            "Time",
+            "TimeBehind",
+            "Position",
+            "Status",
+            "SplitTime",
+            "SplitTime",
+            "ControlCard",
+        ]
+    );
+    assert_eq!(result.text("StartTime").unwrap(), "2024-06-01T10:00:00");
+    assert_eq!(result.text("FinishTime").unwrap(), "2024-06-01T10:12:30");
+    assert_eq!(result.text("Time").unwrap(), "750");
+    assert_eq!(result.text("TimeBehind").unwrap(), "0");
+    assert_eq!(result.text("Position").unwrap(), "1");
+    assert_eq!(result.text("Status").unwrap(), "OK");
+    assert_eq!(result.text("ControlCard").unwrap(), "7001234");
+    let splits = result
+        .children("SplitTime")
+        .map(|split| {
+            (
+                split.text("ControlCode").unwrap(),
+                split.text("Time").unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(splits, [("31", "250"), ("32", "600")]);
+
+    let second = person_results[1].child("Result").unwrap();
+    assert_eq!(second.text("TimeBehind").unwrap(), "90");
+    assert_eq!(second.text("Position").unwrap(), "2");
+}
+
+#[test]
+fn mispunch_and_did_not_start() {
+    let root = Element::parse(&result_list().to_xml().unwrap()).unwrap();
+    let person_results = root
+        .child("ClassResult")
+        .unwrap()
+        .children("PersonResult")
+        .collect::<Vec<_>>();
+
+    let mispunch = person_results[2].child("Result").unwrap();
+    assert_eq!(mispunch.text("Status").unwrap(), "MissingPunch");
+    assert_eq!(mispunch.text("FinishTime").unwrap(), "2024-06-02T00:05:00");
+    assert_eq!(mispunch.text("Time").unwrap(), "600");
+    assert!(mispunch.child("Position").is_none());
+    let splits = mispunch
+        .children("SplitTime")
+        .map(|split| {
+            (
+                split.attribute("status"),
+                split.text("ControlCode").unwrap(),
+                split.child("Time").map(|time| time.text.as_str()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        splits,
+        [
+            (Some("Missing"), "31", None),
+            (None, "32", Some("420")),
+            (Some("Additional"), "33", Some("240")),
+        ]
+    );
+
+    let did_not_start = person_results[3];
+    assert_eq!(did_not_start.child_names(), ["Person", "Result"]);
+    assert_eq!(
+        did_not_start.child("Result").unwrap().child_names(),
+        ["Status"]
+    );
+    assert_eq!(did_not_start.text("Result/Status").unwrap(), "DidNotStart");
+}
+
+#[test]
+#[ignore = "needs xmllint and the official IOF XML 3.0 schema (IOF.xsd) at the path in IOF_XSD"]
+fn valid_against_schema() {
+    let schema = std::env::var("IOF_XSD").expect("IOF_XSD is not set");
+    let mut xmllint = Command::new("xmllint")
+        .args(["--noout", "--schema", &schema, "-"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("xmllint is not installed");
+    xmllint
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(result_list().to_xml().unwrap().as_bytes())
+        .unwrap();
+    let output = xmllint.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn round_trip() {
+    let result_list = result_list();
+    let xml = result_list.to_xml().unwrap();
+    let parsed = ResultList::from_xml(&xml).unwrap();
+
+    assert_eq!(parsed.event, result_list.event);
+    // Apart from the punch after midnight, which is read back on Sunday.
+    assert_eq!(
+        parsed.classes[0].results[..2],
+        result_list.classes[0].results[..2]
+    );
+    assert_eq!(
+        parsed.classes[0].results[3],
+        result_list.classes[0].results[3]
+    );
+    assert_eq!(parsed.to_xml().unwrap(), xml);
+}
+
+#[test]
+fn read_back_mispunch() {
+    let parsed = ResultList::from_xml(&result_list().to_xml().unwrap()).unwrap();
+    let result = parsed.classes[0].results[2].result.as_ref().unwrap();
+
+    assert_eq!(result.status, CourseStatus::Mispunch);
+    assert_eq!(
+        result.matched,
+        [Punch {
+            day_of_week: Sunday,
+            ..punch(32, "00:02:00")
+        }]
+    );
+    assert_eq!(result.missing, [CourseControl::Control(31)]);
+    assert_eq!(result.extra, [punch(33, "23:59:00")]);
+    assert_eq!(
+        result.finish,
+        Some(NaiveTime::from_hms_opt(0, 5, 0).unwrap())
+    );
+}
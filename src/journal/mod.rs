@@ -0,0 +1,337 @@
+//! A write-ahead journal of readouts, so a crash between reading a card and saving the result
+//! loses nothing.
+//!
+//! [`Reader::poll_journaled_readout`] appends the card insertion, every raw block and the
+//! decoded readout to the journal, each synced to disk before the next step. Once the result is
+//! saved elsewhere, [`Journal::mark_saved`] records it. On restart, [`Journal::open`] replays the
+//! journal and returns the readouts that were interrupted or never saved.
+//!
+//! Each entry is framed as its length (4 bytes, little-endian), a JSON payload and the CRC of
+//! the payload (2 bytes, big-endian), so an entry torn by a crash is detected and dropped.
+//! Entries are written and synced on the blocking thread pool of tokio.
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::crc;
+use crate::{
+    Card, CardBlocks, CardImage, CardReadout, Error, FromCardBlocks, Reader, Readout, BLOCK_SIZE,
+};
+
+#[cfg(test)]
+mod tests;
+
+const LENGTH_SIZE: usize = 4;
+const CRC_SIZE: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalEntry {
+    CardInserted {
+        inserted_at: DateTime<Local>,
+        station_code: u16,
+        card: Card,
+    },
+    Block {
+        card_number: u32,
+        index: u8,
+        data: Vec<u8>,
+    },
+    Readout {
+        card_number: u32,
+        readout: CardReadout,
+    },
+    /// The readout was saved by the application, it needs no recovery.
+    Saved { card_number: u32 },
+}
+
+/// An append-only journal file.
+#[derive(Debug)]
+pub struct Journal {
+    file: Arc<File>,
+}
+
+/// The state rebuilt from a journal.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Replay {
+    /// Every card insertion, in journal order.
+    pub readouts: Vec<JournaledReadout>,
+    /// Whether the journal ended with an entry torn by a crash, which was dropped.
+    pub torn: bool,
+}
+
+/// A card insertion and what was journaled for it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct JournaledReadout {
+    pub inserted_at: DateTime<Local>,
+    pub station_code: u16,
+    pub card: Card,
+    /// The raw blocks read, by index.
+    pub blocks: BTreeMap<u8, Vec<u8>>,
+    pub readout: Option<CardReadout>,
+    pub saved: bool,
+}
+
+impl Journal {
+    /// Opens or creates a journal, replaying its entries. A torn entry at the end of the file is
+    /// cut off, so new entries are appended after the last complete one. A corrupt entry
+    /// followed by complete entries is an [`Error::InvalidJournalEntry`] instead, as cutting it
+    /// off would lose them.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<(Self, Replay)> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+
+        let (entries, valid_length) = read_entries(&content)?;
+        let torn = valid_length < content.len();
+        if torn {
+            file.set_len(valid_length as u64)?;
+            file.sync_data()?;
+        }
+
+        let mut replay = Replay {
+            readouts: Vec::new(),
+            torn,
+        };
+        for entry in entries {
+            replay.apply(entry);
+        }
+
+        Ok((
+            Self {
+                file: Arc::new(file),
+            },
+            replay,
+        ))
+    }
+
+    /// Appends an entry, returning once it is on disk.
+    pub async fn append(&mut self, entry: &JournalEntry) -> crate::Result<()> {
+        let payload = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+        let length = u32::try_from(payload.len()).map_err(std::io::Error::other)?;
+
+        let mut frame = Vec::with_capacity(LENGTH_SIZE + payload.len() + CRC_SIZE);
+        frame.extend_from_slice(&length.to_le_bytes());
+        frame.extend_from_slice(&payload);
+        frame.extend_from_slice(&crc(&payload).to_be_bytes());
+        let file = self.file.clone();
+        tokio::task::spawn_blocking(move || {
+            (&*file).write_all(&frame)?;
+            file.sync_data()
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        Ok(())
+    }
+
+    /// Records that the last readout of a card was saved by the application.
+    pub async fn mark_saved(&mut self, card_number: u32) -> crate::Result<()> {
+        self.append(&JournalEntry::Saved { card_number }).await
+    }
+}
+
+impl Replay {
+    fn apply(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::CardInserted {
+                inserted_at,
+                station_code,
+                card,
+            } => self.readouts.push(JournaledReadout {
+                inserted_at,
+                station_code,
+                card,
+                blocks: BTreeMap::new(),
+                readout: None,
+                saved: false,
+            }),
+            JournalEntry::Block {
+                card_number,
+                index,
+                data,
+            } => {
+                if let Some(readout) = self.last_mut(card_number) {
+                    readout.blocks.insert(index, data);
+                }
+            }
+            JournalEntry::Readout {
+                card_number,
+                readout: data,
+            } => {
+                if let Some(readout) = self.last_mut(card_number) {
+                    readout.readout = Some(data);
+                }
+            }
+            JournalEntry::Saved { card_number } => {
+                if let Some(readout) = self.last_mut(card_number) {
+                    readout.saved = true;
+                }
+            }
+        }
+    }
+
+    fn last_mut(&mut self, card_number: u32) -> Option<&mut JournaledReadout> {
+        self.readouts
+            .iter_mut()
+            .rev()
+            .find(|readout| readout.card.number == card_number)
+    }
+
+    /// Insertions whose readout was interrupted, and not completed by a later insertion of the
+    /// same card.
+    pub fn incomplete(&self) -> impl Iterator<Item = &JournaledReadout> {
+        self.readouts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, readout)| {
+                let completed_later = self.readouts[index + 1..].iter().any(|later| {
+                    later.card.number == readout.card.number && later.readout.is_some()
+                });
+                (readout.readout.is_none() && !completed_later).then_some(readout)
+            })
+    }
+
+    /// Readouts which were read completely, but not marked as saved.
+    pub fn unsaved(&self) -> impl Iterator<Item = &JournaledReadout> {
+        self.readouts
+            .iter()
+            .filter(|readout| readout.readout.is_some() && !readout.saved)
+    }
+}
+
+impl JournaledReadout {
+    /// The raw card image, if every block of the card was journaled.
+    #[must_use]
+    pub fn image(&self) -> Option<CardImage> {
+        let data = (0..CardImage::block_count(self.card.card_type))
+            .map(|index| self.blocks.get(&index).map(Vec::as_slice))
+            .collect::<Option<Vec<_>>>()?
+            .concat();
+        Some(CardImage {
+            card_type: self.card.card_type,
+            data,
+        })
+    }
+
+    /// The journaled readout, or the readout decoded from the journaled blocks.
+    pub async fn recover(&self) -> crate::Result<CardReadout> {
+        if let Some(readout) = &self.readout {
+            return Ok(readout.clone());
+        }
+        let mut image = self.image().ok_or(Error::CardRemovedWhileReadingData)?;
+        CardReadout::from_card_blocks(&mut image, self.card.card_type).await
+    }
+}
+
+impl Reader {
+    /// Waits for a card and reads it like [`Reader::poll_readout`], journaling the insertion,
+    /// every block and the decoded readout. Beep only once this returns.
+    pub async fn poll_journaled_readout(
+        &mut self,
+        journal: &mut Journal,
+    ) -> crate::Result<Readout<CardReadout>> {
+        let (station_code, card) = self.poll_card_inserted().await?;
+        journal
+            .append(&JournalEntry::CardInserted {
+                inserted_at: Local::now(),
+                station_code,
+                card: card.clone(),
+            })
+            .await?;
+
+        let mut blocks = JournaledBlocks {
+            blocks: self.card_blocks(),
+            journal,
+            card_number: card.number,
+        };
+        let mut image = CardImage::from_card_blocks(&mut blocks, card.card_type).await?;
+        let data = CardReadout::from_card_blocks(&mut image, card.card_type).await?;
+        blocks
+            .journal
+            .append(&JournalEntry::Readout {
+                card_number: card.number,
+                readout: data.clone(),
+            })
+            .await?;
+
+        Ok(Readout {
+            station_code,
+            card,
+            data,
+        })
+    }
+}
+
+/// Card blocks which are journaled as they are read.
+struct JournaledBlocks<'a, B> {
+    blocks: B,
+    journal: &'a mut Journal,
+    card_number: u32,
+}
+
+impl<B: CardBlocks> CardBlocks for JournaledBlocks<'_, B> {
+    async fn get_block(&mut self, index: u8) -> crate::Result<&[u8; BLOCK_SIZE]> {
+        let block = self.blocks.get_block(index).await?;
+        self.journal
+            .append(&JournalEntry::Block {
+                card_number: self.card_number,
+                index,
+                data: block.to_vec(),
+            })
+            .await?;
+        Ok(block)
+    }
+}
+
+/// Reads the complete entries at the start of `content`, returning them along with their length.
+fn read_entries(content: &[u8]) -> crate::Result<(Vec<JournalEntry>, usize)> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while let Some((payload, valid, end)) = read_frame(content, offset) {
+        if !valid {
+            // Only the last write can be torn by a crash.
+            if valid_frame_follows(content, end) {
+                return Err(Error::InvalidJournalEntry(offset));
+            }
+            break;
+        }
+        entries
+            .push(serde_json::from_slice(payload).map_err(|_| Error::InvalidJournalEntry(offset))?);
+        offset = end;
+    }
+    Ok((entries, offset))
+}
+
+/// The payload of the complete frame at `offset`, whether its CRC matches and where it ends.
+fn read_frame(content: &[u8], offset: usize) -> Option<(&[u8], bool, usize)> {
+    let length = content
+        .get(offset..offset + LENGTH_SIZE)
+        .and_then(|length| length.try_into().ok())
+        .map(|length| u32::from_le_bytes(length) as usize)?;
+    let payload_start = offset + LENGTH_SIZE;
+    let end = payload_start.checked_add(length)?.checked_add(CRC_SIZE)?;
+    let (payload, checksum) = content.get(payload_start..end)?.split_at(length);
+
+    Some((payload, crc(payload).to_be_bytes() == checksum, end))
+}
+
+fn valid_frame_follows(content: &[u8], mut offset: usize) -> bool {
+    while let Some((_, valid, end)) = read_frame(content, offset) {
+        if valid {
+            return true;
+        }
+        offset = end;
+    }
+    false
+}
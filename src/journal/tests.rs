@@ -0,0 +1,233 @@
+#![allow(clippy::pedantic)]
+
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveTime};
+
+use crate::journal::{Journal, JournalEntry};
+use crate::CardType::Si10;
+use crate::DayOfWeek::Saturday;
+use crate::StartOrFinishPunch::Normal;
+use crate::WeekCounter::First;
+use crate::{Card, CardReadout, Error, Punch};
+
+fn journal_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "sportident-journal-{name}-{}.log",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn inserted(card_number: u32) -> JournalEntry {
+    JournalEntry::CardInserted {
+        inserted_at: Local::now(),
+        station_code: 10,
+        card: Card::new(card_number).unwrap(),
+    }
+}
+
+fn readout(card_number: u32) -> CardReadout {
+    let punch = |code, time| Punch {
+        time: NaiveTime::from_hms_opt(10, time, 0).unwrap(),
+        day_of_week: Saturday,
+        week_counter: First,
+        code,
+    };
+    CardReadout {
+        card_number,
+        card_type: Si10,
+        start: Some(Normal(punch(1, 0))),
+        finish: Some(Normal(punch(2, 12))),
+        check: None,
+        punches: vec![punch(31, 4)],
+    }
+}
+
+#[tokio::test]
+async fn replay() {
+    let path = journal_path("replay");
+    let (mut journal, replay) = Journal::open(&path).unwrap();
+    assert!(replay.readouts.is_empty());
+    assert!(!replay.torn);
+
+    journal.append(&inserted(7_001_234)).await.unwrap();
+    journal
+        .append(&JournalEntry::Readout {
+            card_number: 7_001_234,
+            readout: readout(7_001_234),
+        })
+        .await
+        .unwrap();
+    journal.mark_saved(7_001_234).await.unwrap();
+    journal.append(&inserted(7_001_235)).await.unwrap();
+    journal
+        .append(&JournalEntry::Readout {
+            card_number: 7_001_235,
+            readout: readout(7_001_235),
+        })
+        .await
+        .unwrap();
+    journal.append(&inserted(7_001_236)).await.unwrap();
+    journal
+        .append(&JournalEntry::Block {
+            card_number: 7_001_236,
+            index: 0,
+            data: vec![0xee; 128],
+        })
+        .await
+        .unwrap();
+    drop(journal);
+
+    let (_, replay) = Journal::open(&path).unwrap();
+    assert!(!replay.torn);
+    assert_eq!(replay.readouts.len(), 3);
+    assert!(replay.readouts[0].saved);
+    assert_eq!(
+        replay
+            .unsaved()
+            .map(|readout| readout.card.number)
+            .collect::<Vec<_>>(),
+        vec![7_001_235]
+    );
+    let incomplete = replay.incomplete().collect::<Vec<_>>();
+    assert_eq!(incomplete.len(), 1);
+    assert_eq!(incomplete[0].card.number, 7_001_236);
+    assert_eq!(incomplete[0].blocks.len(), 1);
+    assert!(incomplete[0].image().is_none());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn incomplete_readout_completed_later() {
+    let path = journal_path("completed");
+    let (mut journal, _) = Journal::open(&path).unwrap();
+    journal.append(&inserted(7_001_234)).await.unwrap();
+    journal.append(&inserted(7_001_234)).await.unwrap();
+    journal
+        .append(&JournalEntry::Readout {
+            card_number: 7_001_234,
+            readout: readout(7_001_234),
+        })
+        .await
+        .unwrap();
+    drop(journal);
+
+    let (_, replay) = Journal::open(&path).unwrap();
+    assert_eq!(replay.readouts.len(), 2);
+    assert_eq!(replay.incomplete().count(), 0);
+    assert_eq!(replay.unsaved().count(), 1);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn torn_tail_is_dropped() {
+    let path = journal_path("torn");
+    let (mut journal, _) = Journal::open(&path).unwrap();
+    journal.append(&inserted(7_001_234)).await.unwrap();
+    drop(journal);
+    let complete = std::fs::read(&path).unwrap();
+
+    let (mut journal, _) = Journal::open(&path).unwrap();
+    journal
+        .append(&JournalEntry::Readout {
+            card_number: 7_001_234,
+            readout: readout(7_001_234),
+        })
+        .await
+        .unwrap();
+    drop(journal);
+    let content = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &content[..content.len() - 10]).unwrap();
+
+    let (mut journal, replay) = Journal::open(&path).unwrap();
+    assert!(replay.torn);
+    assert_eq!(replay.incomplete().count(), 1);
+    assert_eq!(std::fs::read(&path).unwrap(), complete);
+
+    journal.mark_saved(7_001_234).await.unwrap();
+    drop(journal);
+    let (_, replay) = Journal::open(&path).unwrap();
+    assert!(!replay.torn);
+    assert!(replay.readouts[0].saved);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn corrupt_entry_before_complete_entries() {
+    let path = journal_path("corrupt-middle");
+    let (mut journal, _) = Journal::open(&path).unwrap();
+    journal.append(&inserted(7_001_234)).await.unwrap();
+    let second_entry = std::fs::metadata(&path).unwrap().len() as usize;
+    journal.append(&inserted(7_001_235)).await.unwrap();
+    journal.append(&inserted(7_001_236)).await.unwrap();
+    drop(journal);
+    let mut content = std::fs::read(&path).unwrap();
+    content[second_entry + 10] ^= 0xff;
+    std::fs::write(&path, &content).unwrap();
+
+    assert!(matches!(
+        Journal::open(&path),
+        Err(Error::InvalidJournalEntry(offset)) if offset == second_entry
+    ));
+    assert_eq!(std::fs::read(&path).unwrap(), content);
+
+    content[second_entry + 10] ^= 0xff;
+    let last = content.len() - 10;
+    content[last] ^= 0xff;
+    std::fs::write(&path, &content).unwrap();
+    let (_, replay) = Journal::open(&path).unwrap();
+    assert!(replay.torn);
+    assert_eq!(replay.readouts.len(), 2);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn corrupt_entry() {
+    let path = journal_path("corrupt");
+    let payload = b"{\"type\":\"unknown\"}";
+    let mut content = (payload.len() as u32).to_le_bytes().to_vec();
+    content.extend(payload);
+    content.extend(crate::protocol::crc(payload).to_be_bytes());
+    std::fs::write(&path, content).unwrap();
+
+    assert!(matches!(
+        Journal::open(&path),
+        Err(Error::InvalidJournalEntry(0))
+    ));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "simulator")]
+#[tokio::test]
+async fn journaled_readout() {
+    use crate::simulator::tests::{si8_image, si8_readout};
+    use crate::simulator::SimulatedStation;
+    use crate::StationMode;
+
+    let path = journal_path("reader");
+    let (station, mut reader) = SimulatedStation::connect(StationMode::Readout, 10)
+        .await
+        .unwrap();
+    let expected = si8_readout().await;
+    let (mut journal, _) = Journal::open(&path).unwrap();
+
+    station.insert_card(expected.card_number, si8_image());
+    let readout = reader.poll_journaled_readout(&mut journal).await.unwrap();
+    assert_eq!(readout.station_code, 10);
+    assert_eq!(readout.data, expected);
+    drop(journal);
+
+    let (_, replay) = Journal::open(&path).unwrap();
+    assert_eq!(replay.incomplete().count(), 0);
+    let unsaved = replay.unsaved().collect::<Vec<_>>();
+    assert_eq!(unsaved.len(), 1);
+    assert_eq!(unsaved[0].station_code, 10);
+    assert_eq!(unsaved[0].image().unwrap(), si8_image());
+
+    let mut interrupted = unsaved[0].clone();
+    interrupted.readout = None;
+    assert_eq!(interrupted.recover().await.unwrap(), expected);
+    std::fs::remove_file(path).unwrap();
+}
@@ -2,14 +2,55 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::multiple_crate_versions)]
 #![allow(clippy::missing_errors_doc)]
+pub use backup::{
+    BackupCursor, BackupCursors, BackupSync, BackupSyncKind, ClearBackupMode, ClearBackupReport,
+    BACKUP_MEMORY_START,
+};
 pub use error::{Error, Result};
+pub use feed::{PunchFeed, PunchGap};
 pub use protocol::{
     responses::card::{Card, CardType},
-    responses::card_punch::CardPunch,
-    CardOwnerData, CardReadout, DayOfWeek, DecoderError, EncoderError, Punch, StartOrFinishPunch,
-    SubSecondPunch, WeekCounter,
+    responses::card_punch::{CardPunch, PunchSource},
+    BackupRecord, CardBlocks, CardImage, CardOwnerData, CardReadout, DayOfWeek, DecoderError,
+    EncoderError, FromCardBlocks, Model, ProtocolConfiguration, Punch, PunchFeedback,
+    SI6CardBlocks, SRRChannel, SRRConfiguration, StartOrFinishPunch, StationMode, StationProgram,
+    SubSecondPunch, SystemConfiguration, WeekCounter, BLOCK_SIZE,
+};
+pub use reader::{Reader, Readout, Transport};
+pub use remote::RemoteStation;
+pub use results::{
+    rank_legs, Course, CourseControl, CourseResult, CourseSection, CourseStatus, RankedSplit,
+    Relay, RelayLeg, RelayLegResult, RelayResult, RelayStatus, ScoreCourse, ScorePunch,
+    ScoreResult, ScoreStatus, Split, SplitControl,
 };
-pub use reader::Reader;
+pub use timestamp::{PunchTime, PunchTimeResolver};
+mod backup;
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "daemon")]
+pub mod daemon;
 mod error;
+mod feed;
+#[cfg(feature = "iof")]
+pub mod iof;
+#[cfg(feature = "journal")]
+pub mod journal;
+#[cfg(feature = "printout")]
+pub mod printout;
 mod protocol;
 mod reader;
+mod remote;
+mod results;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "sirap")]
+pub mod sirap;
+#[cfg(feature = "store")]
+pub mod store;
+#[cfg(test)]
+pub(crate) mod test_support;
+mod timestamp;
@@ -0,0 +1,377 @@
+//! Split tickets printed for runners after readout, as plain text, an HTML page or ESC/POS
+//! commands for thermal printers.
+//!
+//! The layout is set by a [`PrintoutTemplate`]. Without a course, every punch of the card is
+//! listed in the splits table.
+use std::fmt::Write as _;
+use std::io::Write;
+
+use chrono::{NaiveTime, TimeDelta, Timelike};
+
+pub use template::PrintoutTemplate;
+
+use crate::printout::template::{Field, Part, TemplateLine};
+use crate::{
+    CardOwnerData, CardReadout, Course, CourseControl, CourseResult, CourseStatus, Split,
+    SplitControl,
+};
+
+mod template;
+#[cfg(test)]
+mod tests;
+
+const TIME_COLUMN_WIDTH: usize = 8;
+const SPLITS_HEADER: [&str; 3] = ["Control", "Split", "Leg"];
+
+const ESC: u8 = 0x1b;
+const GS: u8 = 0x1d;
+/// `ESC @`: reset the printer.
+const INITIALIZE: [u8; 2] = [ESC, b'@'];
+/// `ESC t 16`: Windows-1252, which matches Latin-1 for printable characters.
+const SELECT_CODE_PAGE: [u8; 3] = [ESC, b't', 16];
+/// `ESC E 1` and `GS ! 0x01`: bold, double height.
+const HEADING_ON: [u8; 6] = [ESC, b'E', 1, GS, b'!', 0x01];
+const HEADING_OFF: [u8; 6] = [ESC, b'E', 0, GS, b'!', 0x00];
+/// `GS V 66 3`: feed 3 lines and cut partially.
+const FEED_AND_CUT: [u8; 4] = [GS, b'V', 66, 3];
+
+/// The paper width of a thermal printer.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Paper {
+    /// 58 mm, 32 columns.
+    Mm58,
+    /// 80 mm, 48 columns.
+    Mm80,
+}
+
+/// The split ticket of a readout.
+#[derive(Debug, Clone)]
+pub struct Printout<'a> {
+    readout: &'a CardReadout,
+    owner_data: Option<&'a CardOwnerData>,
+    course: Option<(&'a str, &'a Course)>,
+    manual_start: Option<NaiveTime>,
+    template: PrintoutTemplate,
+}
+
+/// A line of the printout, with the fields of the template filled in.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Line {
+    Heading(String),
+    Text(String),
+    Splits(Vec<[String; 3]>),
+}
+
+impl Paper {
+    #[must_use]
+    pub const fn columns(self) -> usize {
+        match self {
+            Self::Mm58 => 32,
+            Self::Mm80 => 48,
+        }
+    }
+}
+
+impl<'a> Printout<'a> {
+    #[must_use]
+    pub fn new(readout: &'a CardReadout) -> Self {
+        Self {
+            readout,
+            owner_data: None,
+            course: None,
+            manual_start: None,
+            template: PrintoutTemplate::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_owner_data(mut self, owner_data: &'a CardOwnerData) -> Self {
+        self.owner_data = Some(owner_data);
+        self
+    }
+
+    /// Validate the readout against a course, which also prints its name and the status.
+    #[must_use]
+    pub const fn with_course(mut self, name: &'a str, course: &'a Course) -> Self {
+        self.course = Some((name, course));
+        self
+    }
+
+    /// The start time used when the card has no start punch.
+    #[must_use]
+    pub const fn with_manual_start(mut self, start: NaiveTime) -> Self {
+        self.manual_start = Some(start);
+        self
+    }
+
+    #[must_use]
+    pub fn with_template(mut self, template: PrintoutTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// A plain-text ticket, with lines cut to `width` characters.
+    #[must_use]
+    pub fn text(&self, width: usize) -> String {
+        let mut text = String::new();
+        for line in self.lines() {
+            for row in text_rows(&line, width) {
+                text.push_str(&row);
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// A standalone HTML page.
+    #[must_use]
+    pub fn html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Card {}</title>\n\
+             </head>\n<body>\n",
+            self.readout.card_number
+        );
+        for line in self.lines() {
+            match line {
+                Line::Heading(text) => {
+                    let _ = writeln!(html, "<h1>{}</h1>", escape(&text));
+                }
+                Line::Text(text) => {
+                    let _ = writeln!(html, "<p>{}</p>", escape(&text));
+                }
+                Line::Splits(rows) => {
+                    html.push_str("<table>\n<tr>");
+                    for header in SPLITS_HEADER {
+                        let _ = write!(html, "<th>{header}</th>");
+                    }
+                    html.push_str("</tr>\n");
+                    for row in rows {
+                        html.push_str("<tr>");
+                        for cell in row {
+                            let _ = write!(html, "<td>{}</td>", escape(&cell));
+                        }
+                        html.push_str("</tr>\n");
+                    }
+                    html.push_str("</table>\n");
+                }
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// The ESC/POS commands printing the ticket and cutting the paper.
+    #[must_use]
+    pub fn escpos(&self, paper: Paper) -> Vec<u8> {
+        let mut bytes = INITIALIZE.to_vec();
+        bytes.extend_from_slice(&SELECT_CODE_PAGE);
+        for line in self.lines() {
+            let heading = matches!(line, Line::Heading(_));
+            if heading {
+                bytes.extend_from_slice(&HEADING_ON);
+            }
+            for row in text_rows(&line, paper.columns()) {
+                bytes.extend(row.chars().map(code_page_byte));
+                bytes.push(b'\n');
+            }
+            if heading {
+                bytes.extend_from_slice(&HEADING_OFF);
+            }
+        }
+        bytes.extend_from_slice(&FEED_AND_CUT);
+        bytes
+    }
+
+    /// Writes the ESC/POS commands to a file, a printer device like `/dev/usb/lp0`, or any
+    /// other writer.
+    pub fn write_escpos(&self, paper: Paper, mut writer: impl Write) -> crate::Result<()> {
+        writer.write_all(&self.escpos(paper))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Prints on a thermal printer connected to a serial port.
+    pub fn print_to_serial_port(
+        &self,
+        paper: Paper,
+        port: &str,
+        baud_rate: u32,
+    ) -> crate::Result<()> {
+        self.write_escpos(paper, tokio_serial::new(port, baud_rate).open()?)
+    }
+
+    fn result(&self) -> (Option<&str>, CourseResult) {
+        let validate = |course: &Course| {
+            self.manual_start.map_or_else(
+                || course.validate(self.readout),
+                |start| course.validate_with_start(self.readout, start),
+            )
+        };
+        if let Some((name, course)) = self.course {
+            return (Some(name), validate(course));
+        }
+        let course = self
+            .readout
+            .punches
+            .iter()
+            .fold(Course::new(), |course, punch| course.control(punch.code));
+        (None, validate(&course))
+    }
+
+    fn lines(&self) -> Vec<Line> {
+        let (course, result) = self.result();
+        let field = |field: Field| self.field(field, course, &result);
+
+        let fill = |parts: &[Part]| {
+            let mut text = String::new();
+            let mut has_fields = false;
+            let mut has_values = false;
+            for part in parts {
+                match part {
+                    Part::Literal(literal) => text.push_str(literal),
+                    Part::Field(name) => {
+                        let value = field(*name);
+                        has_fields = true;
+                        has_values |= !value.is_empty();
+                        text.push_str(&value);
+                    }
+                }
+            }
+            (!has_fields || has_values).then(|| text.trim_end().to_string())
+        };
+
+        self.template
+            .lines()
+            .iter()
+            .filter_map(|line| match line {
+                TemplateLine::Heading(parts) => fill(parts).map(Line::Heading),
+                TemplateLine::Text(parts) => fill(parts).map(Line::Text),
+                TemplateLine::Splits => Some(Line::Splits(split_rows(&result.splits()))),
+            })
+            .collect()
+    }
+
+    fn field(&self, field: Field, course: Option<&str>, result: &CourseResult) -> String {
+        let owner = |value: fn(&CardOwnerData) -> &str| {
+            self.owner_data.map(value).unwrap_or_default().to_string()
+        };
+        match field {
+            Field::Name => {
+                owner(|owner| &owner.first_name) + " " + &owner(|owner| &owner.last_name)
+            }
+            Field::FirstName => owner(|owner| &owner.first_name),
+            Field::LastName => owner(|owner| &owner.last_name),
+            Field::Club => owner(|owner| owner.club.as_deref().unwrap_or_default()),
+            Field::CardNumber => self.readout.card_number.to_string(),
+            Field::Course => course.unwrap_or_default().to_string(),
+            Field::Status => course
+                .map(|_| status(result.status))
+                .unwrap_or_default()
+                .to_string(),
+            Field::Start => result.start.map(format_time).unwrap_or_default(),
+            Field::Finish => result.finish.map(format_time).unwrap_or_default(),
+            Field::RunningTime => result.running_time.map(format_duration).unwrap_or_default(),
+            Field::Missing => result
+                .missing
+                .iter()
+                .map(|control| match control {
+                    CourseControl::Control(code) => code.to_string(),
+                    CourseControl::OneOf(codes) => codes
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+        .trim()
+        .to_string()
+    }
+}
+
+const fn status(status: CourseStatus) -> &'static str {
+    match status {
+        CourseStatus::Ok => "OK",
+        CourseStatus::Mispunch => "MP",
+        CourseStatus::DidNotFinish => "DNF",
+        CourseStatus::MissingStart => "No start",
+        CourseStatus::MissingFinish => "No finish",
+    }
+}
+
+fn split_rows(splits: &[Split]) -> Vec<[String; 3]> {
+    splits
+        .iter()
+        .enumerate()
+        .map(|(index, split)| {
+            let control = match split.to {
+                SplitControl::Control(code) => format!("{}. {code}", index + 1),
+                SplitControl::Start | SplitControl::Finish => "Finish".to_string(),
+            };
+            [
+                control,
+                format_duration(split.split_time),
+                format_duration(split.leg_time),
+            ]
+        })
+        .collect()
+}
+
+/// The rows of a line in a fixed-width layout, cut to `width` characters.
+fn text_rows(line: &Line, width: usize) -> Vec<String> {
+    let cut = |text: &str| text.chars().take(width).collect::<String>();
+    match line {
+        Line::Heading(text) | Line::Text(text) => vec![cut(text)],
+        Line::Splits(rows) => std::iter::once(SPLITS_HEADER.map(ToString::to_string))
+            .chain(rows.iter().cloned())
+            .map(|[control, split, leg]| {
+                let control_width = width.saturating_sub(2 * TIME_COLUMN_WIDTH);
+                cut(&format!(
+                    "{control:<control_width$}{split:>TIME_COLUMN_WIDTH$}{leg:>TIME_COLUMN_WIDTH$}"
+                ))
+            })
+            .collect(),
+    }
+}
+
+fn format_time(time: NaiveTime) -> String {
+    time.with_nanosecond(0)
+        .unwrap_or(time)
+        .format("%H:%M:%S")
+        .to_string()
+}
+
+/// `M:SS` below an hour, `H:MM:SS` above.
+fn format_duration(duration: TimeDelta) -> String {
+    let seconds = duration.num_seconds();
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.unsigned_abs();
+    if seconds >= 3600 {
+        format!(
+            "{sign}{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{sign}{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The byte of a character in Windows-1252, `?` for characters it doesn't hold.
+fn code_page_byte(character: char) -> u8 {
+    let byte = u8::try_from(u32::from(character)).unwrap_or(b'?');
+    if matches!(byte, 0x20..=0x7e | 0xa0..=0xff) {
+        byte
+    } else {
+        b'?'
+    }
+}
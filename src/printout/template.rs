@@ -0,0 +1,129 @@
+use crate::Error;
+
+/// The layout of a printout, one template line per printed line.
+///
+/// Lines starting with `# ` are headings. `{field}` is replaced by the value of a field:
+/// `name`, `first_name`, `last_name`, `club`, `card_number`, `course`, `status`, `start`,
+/// `finish`, `running_time` or `missing`. Lines whose fields are all empty, like `{club}` for a
+/// card without owner data, are skipped. A line holding only `{splits}` is replaced by the
+/// splits table.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PrintoutTemplate {
+    lines: Vec<TemplateLine>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TemplateLine {
+    Heading(Vec<Part>),
+    Text(Vec<Part>),
+    Splits,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Part {
+    Literal(String),
+    Field(Field),
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Field {
+    Name,
+    FirstName,
+    LastName,
+    Club,
+    CardNumber,
+    Course,
+    Status,
+    Start,
+    Finish,
+    RunningTime,
+    Missing,
+}
+
+const DEFAULT_TEMPLATE: &str = "# {name}
+{club}
+Card {card_number}
+Course {course}
+Start {start}
+{splits}
+Finish {finish}
+Missing {missing}
+Time {running_time} {status}";
+
+impl PrintoutTemplate {
+    pub fn parse(template: &str) -> crate::Result<Self> {
+        let lines = template
+            .lines()
+            .map(parse_line)
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self { lines })
+    }
+
+    pub(crate) fn lines(&self) -> &[TemplateLine] {
+        &self.lines
+    }
+}
+
+impl Default for PrintoutTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TEMPLATE).unwrap_or(Self { lines: Vec::new() })
+    }
+}
+
+impl Field {
+    fn parse(name: &str) -> crate::Result<Self> {
+        Ok(match name {
+            "name" => Self::Name,
+            "first_name" => Self::FirstName,
+            "last_name" => Self::LastName,
+            "club" => Self::Club,
+            "card_number" => Self::CardNumber,
+            "course" => Self::Course,
+            "status" => Self::Status,
+            "start" => Self::Start,
+            "finish" => Self::Finish,
+            "running_time" => Self::RunningTime,
+            "missing" => Self::Missing,
+            "splits" => {
+                return Err(Error::InvalidPrintoutTemplate(
+                    "{splits} must be on a line of its own".to_string(),
+                ))
+            }
+            _ => {
+                return Err(Error::InvalidPrintoutTemplate(format!(
+                    "Unknown field {{{name}}}"
+                )))
+            }
+        })
+    }
+}
+
+fn parse_line(line: &str) -> crate::Result<TemplateLine> {
+    if line.trim() == "{splits}" {
+        return Ok(TemplateLine::Splits);
+    }
+    line.strip_prefix("# ").map_or_else(
+        || parse_parts(line).map(TemplateLine::Text),
+        |heading| parse_parts(heading).map(TemplateLine::Heading),
+    )
+}
+
+fn parse_parts(line: &str) -> crate::Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut rest = line;
+    while let Some(open) = rest.find('{') {
+        let close = rest[open..].find('}').ok_or_else(|| {
+            Error::InvalidPrintoutTemplate(format!("Unclosed field in line \"{line}\""))
+        })?;
+        if open > 0 {
+            parts.push(Part::Literal(rest[..open].to_string()));
+        }
+        parts.push(Part::Field(Field::parse(&rest[open + 1..open + close])?));
+        rest = &rest[open + close + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest.to_string()));
+    }
+    Ok(parts)
+}
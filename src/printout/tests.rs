@@ -0,0 +1,179 @@
+#![allow(clippy::pedantic)]
+
+use chrono::NaiveTime;
+
+use crate::printout::{Paper, Printout, PrintoutTemplate};
+use crate::test_support::punch;
+use crate::CardType::Si10;
+use crate::StartOrFinishPunch::Normal;
+use crate::{CardOwnerData, CardReadout, Course, Error};
+
+fn readout() -> CardReadout {
+    CardReadout {
+        card_number: 7_001_234,
+        card_type: Si10,
+        start: Some(Normal(punch(1, "10:00:00"))),
+        finish: Some(Normal(punch(2, "10:12:30"))),
+        check: None,
+        punches: vec![punch(31, "10:04:10"), punch(32, "10:10:00")],
+    }
+}
+
+fn owner_data() -> CardOwnerData {
+    CardOwnerData {
+        first_name: "Åsa".to_string(),
+        last_name: "Lindström".to_string(),
+        gender: None,
+        birthday: None,
+        club: Some("Tisaren & Co".to_string()),
+        email: None,
+        phone: None,
+        city: None,
+        street: None,
+        zip: None,
+        country: None,
+    }
+}
+
+#[test]
+fn text() {
+    let readout = readout();
+    let owner_data = owner_data();
+    let course = Course::new().control(31).control(32);
+    let printout = Printout::new(&readout)
+        .with_owner_data(&owner_data)
+        .with_course("A", &course);
+
+    assert_eq!(
+        printout.text(32),
+        "Åsa Lindström
+Tisaren & Co
+Card 7001234
+Course A
+Start 10:00:00
+Control            Split     Leg
+1. 31               4:10    4:10
+2. 32              10:00    5:50
+Finish             12:30    2:30
+Finish 10:12:30
+Time 12:30 OK
+"
+    );
+}
+
+#[test]
+fn text_without_course_and_owner_data() {
+    let readout = readout();
+
+    assert_eq!(
+        Printout::new(&readout).text(48),
+        "Card 7001234
+Start 10:00:00
+Control                            Split     Leg
+1. 31                               4:10    4:10
+2. 32                              10:00    5:50
+Finish                             12:30    2:30
+Finish 10:12:30
+Time 12:30
+"
+    );
+}
+
+#[test]
+fn custom_template() {
+    let mut readout = readout();
+    readout.start = None;
+    let course = Course::new().control(31).one_of([33, 34]).control(32);
+    let template =
+        PrintoutTemplate::parse("# {card_number} {status}\nMissing: {missing}\n{club}\n{splits}")
+            .unwrap();
+
+    let printout = Printout::new(&readout)
+        .with_course("B", &course)
+        .with_manual_start(NaiveTime::from_hms_opt(10, 2, 0).unwrap())
+        .with_template(template);
+
+    assert_eq!(
+        printout.text(24),
+        "7001234 MP
+Missing: 33/34
+Control    Split     Leg
+1. 31       2:10    2:10
+2. 32       8:00    5:50
+Finish     10:30    2:30
+"
+    );
+}
+
+#[test]
+fn invalid_templates() {
+    for template in ["{unknown}", "Splits: {splits}", "Card {card_number"] {
+        assert!(
+            matches!(
+                PrintoutTemplate::parse(template),
+                Err(Error::InvalidPrintoutTemplate(_))
+            ),
+            "{template}"
+        );
+    }
+}
+
+#[test]
+fn html() {
+    let readout = readout();
+    let owner_data = owner_data();
+    let template = PrintoutTemplate::parse("# {name}\n{club}\n{splits}").unwrap();
+    let printout = Printout::new(&readout)
+        .with_owner_data(&owner_data)
+        .with_template(template);
+
+    assert_eq!(
+        printout.html(),
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Card 7001234</title>
+</head>
+<body>
+<h1>Åsa Lindström</h1>
+<p>Tisaren &amp; Co</p>
+<table>
+<tr><th>Control</th><th>Split</th><th>Leg</th></tr>
+<tr><td>1. 31</td><td>4:10</td><td>4:10</td></tr>
+<tr><td>2. 32</td><td>10:00</td><td>5:50</td></tr>
+<tr><td>Finish</td><td>12:30</td><td>2:30</td></tr>
+</table>
+</body>
+</html>
+"
+    );
+}
+
+#[test]
+fn escpos() {
+    let readout = readout();
+    let owner_data = owner_data();
+    let template = PrintoutTemplate::parse("# {name} ☺\n{splits}").unwrap();
+    let printout = Printout::new(&readout)
+        .with_owner_data(&owner_data)
+        .with_template(template);
+    let path = std::env::temp_dir().join(format!("sportident-printout-{}.bin", std::process::id()));
+
+    printout
+        .write_escpos(Paper::Mm58, std::fs::File::create(&path).unwrap())
+        .unwrap();
+
+    let mut expected = vec![0x1b, b'@', 0x1b, b't', 16];
+    expected.extend([0x1b, b'E', 1, 0x1d, b'!', 1]);
+    expected.extend(b"\xc5sa Lindstr\xf6m ?\n");
+    expected.extend([0x1b, b'E', 0, 0x1d, b'!', 0]);
+    expected.extend(b"Control            Split     Leg\n");
+    expected.extend(b"1. 31               4:10    4:10\n");
+    expected.extend(b"2. 32              10:00    5:50\n");
+    expected.extend(b"Finish             12:30    2:30\n");
+    expected.extend([0x1d, b'V', 66, 3]);
+    assert_eq!(std::fs::read(&path).unwrap(), expected);
+    assert_eq!(printout.escpos(Paper::Mm58), expected);
+    std::fs::remove_file(path).unwrap();
+}
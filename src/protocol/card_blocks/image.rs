@@ -0,0 +1,44 @@
+use crate::protocol::card_blocks::FromCardBlocks;
+use crate::protocol::responses::card::CardType;
+use crate::protocol::{CardBlocks, DecoderError, BLOCK_SIZE};
+
+/// The raw blocks of a card, which can be stored and decoded later like a card in a reader.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CardImage {
+    pub card_type: CardType,
+    pub data: Vec<u8>,
+}
+
+impl CardImage {
+    #[must_use]
+    pub const fn block_count(card_type: CardType) -> u8 {
+        match card_type {
+            CardType::Si8 | CardType::Si9 | CardType::PunchCard => 2,
+            CardType::Si10 | CardType::Si11 | CardType::Siac => 8,
+        }
+    }
+}
+
+impl FromCardBlocks for CardImage {
+    async fn from_card_blocks(
+        blocks: &mut impl CardBlocks,
+        card_type: CardType,
+    ) -> crate::Result<Self> {
+        let mut data = Vec::with_capacity(usize::from(Self::block_count(card_type)) * BLOCK_SIZE);
+        for index in 0..Self::block_count(card_type) {
+            data.extend_from_slice(blocks.get_block(index).await?);
+        }
+
+        Ok(Self { card_type, data })
+    }
+}
+
+impl CardBlocks for CardImage {
+    async fn get_block(&mut self, index: u8) -> crate::Result<&[u8; BLOCK_SIZE]> {
+        let start = usize::from(index) * BLOCK_SIZE;
+        self.data
+            .get(start..start + BLOCK_SIZE)
+            .and_then(|block| block.try_into().ok())
+            .ok_or_else(|| DecoderError::InvalidReadoutDataLength.into())
+    }
+}
@@ -1,3 +1,4 @@
+pub use image::*;
 pub use owner_data::*;
 pub use readout::*;
 use std::future::Future;
@@ -25,6 +26,7 @@ impl<U: FromCardBlocks, T: FromCardBlocks> FromCardBlocks for (U, T) {
     }
 }
 
+pub mod image;
 pub mod owner_data;
 pub mod readout;
 
@@ -4,6 +4,7 @@ use crate::protocol::responses::card::CardType;
 use crate::protocol::{CardBlocks, DecoderError, FromCardBlocks, BLOCK_SIZE};
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CardOwnerData {
     pub first_name: String,
     pub last_name: String,
@@ -57,8 +58,8 @@ impl FromCardBlocks for CardOwnerData {
             }
         }?;
 
-        let first_name = parts[0].to_string();
-        let last_name = parts[1].to_string();
+        let first_name = parts[0].clone();
+        let last_name = parts[1].clone();
         let gender = parts
             .get(2)
             .filter(|s| !s.is_empty())
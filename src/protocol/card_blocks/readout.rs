@@ -3,6 +3,7 @@ use crate::protocol::responses::card::CardType;
 use crate::protocol::{CardBlocks, DecoderError, Punch, StartOrFinishPunch, BLOCK_SIZE};
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CardReadout {
     pub card_number: u32,
     pub card_type: CardType,
@@ -0,0 +1,28 @@
+use crate::protocol::responses::card::CardType::{Si10, Si8};
+use crate::protocol::{CardBlocks, CardImage, CardReadout, FromCardBlocks};
+
+#[tokio::test]
+async fn decode_image() {
+    let mut data = hex::decode("05760c87eaeaeaea1a01a23a2a01a2331a01a244000000b3021f9b2a0cffcbfe4461666e613b596f6765763b4150484e413b0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee").unwrap();
+
+    let mut image = CardImage::from_card_blocks(&mut data, Si8).await.unwrap();
+
+    assert_eq!(image.data, data);
+    assert_eq!(
+        CardReadout::from_card_blocks(&mut image, Si8)
+            .await
+            .unwrap(),
+        CardReadout::from_card_blocks(&mut data, Si8).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn missing_block() {
+    let mut image = CardImage {
+        card_type: Si10,
+        data: vec![0; 128 * 2],
+    };
+
+    assert!(image.get_block(1).await.is_ok());
+    assert!(image.get_block(2).await.is_err());
+}
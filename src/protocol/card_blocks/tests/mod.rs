@@ -2,6 +2,7 @@
 
 use crate::protocol::{CardBlocks, BLOCK_SIZE};
 
+mod image;
 mod pcard;
 mod si10;
 mod si8;
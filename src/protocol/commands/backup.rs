@@ -0,0 +1,101 @@
+use crate::protocol::responses::card::Card;
+use crate::protocol::{Command, DecoderError, Response, SubSecondPunch};
+
+pub const BACKUP_RECORD_SIZE: usize = 8;
+pub const MAX_BACKUP_READ_LENGTH: u8 = 128;
+
+const BACKUP_ADDRESS_LENGTH: usize = 3;
+
+#[derive(Copy, Clone, Debug)]
+pub struct GetBackupData {
+    address: u32,
+    length: u8,
+}
+
+impl GetBackupData {
+    pub const fn new(address: u32, length: u8) -> Self {
+        Self { address, length }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct EraseBackupData;
+
+#[derive(Debug)]
+pub struct EraseBackupDataResponse;
+
+#[derive(Debug)]
+pub struct BackupData {
+    pub address: u32,
+    pub data: Vec<u8>,
+}
+
+/// A single punch stored in the backup memory of a station.
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackupRecord {
+    /// The backup memory address the record was read from.
+    pub address: u32,
+    pub card: Card,
+    pub punch: SubSecondPunch,
+}
+
+impl Command for GetBackupData {
+    fn command_parameters(&self) -> Vec<u8> {
+        let mut parameters = self.address.to_be_bytes()[1..].to_vec();
+        parameters.push(self.length);
+        parameters
+    }
+
+    fn magic(&self) -> u8 {
+        0x81
+    }
+}
+
+impl Command for EraseBackupData {
+    fn command_parameters(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn magic(&self) -> u8 {
+        0xf5
+    }
+}
+
+impl Response for EraseBackupDataResponse {
+    fn decode(_data: &[u8]) -> Result<Self, DecoderError> {
+        Ok(Self)
+    }
+}
+
+impl Response for BackupData {
+    fn decode(data: &[u8]) -> Result<Self, DecoderError> {
+        if data.len() < BACKUP_ADDRESS_LENGTH {
+            return Err(DecoderError::InvalidBackupDataLength(data.len()));
+        }
+
+        Ok(Self {
+            address: u32::from_be_bytes([0, data[0], data[1], data[2]]),
+            data: data[BACKUP_ADDRESS_LENGTH..].to_vec(),
+        })
+    }
+}
+
+impl BackupRecord {
+    pub(crate) fn decode(address: u32, data: &[u8]) -> Result<Self, DecoderError> {
+        if data.len() != BACKUP_RECORD_SIZE {
+            return Err(DecoderError::InvalidBackupDataLength(data.len()));
+        }
+
+        let card = Card::decode(&data[0..4])?;
+        let punch =
+            SubSecondPunch::decode_punch(card.card_type, [data[4], data[7], data[5], data[6]])?
+                .ok_or(DecoderError::InvalidPunchTime)?;
+
+        Ok(Self {
+            address,
+            card,
+            punch,
+        })
+    }
+}
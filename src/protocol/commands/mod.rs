@@ -1,15 +1,21 @@
+pub use backup::*;
 pub use beep::*;
 pub use read_card_data::*;
 pub use set_master_slave::*;
+pub use set_system_value::*;
 pub use system_configuration::*;
+pub use time::*;
 
 use crate::protocol::decoder::DecoderError;
 use crate::protocol::EncoderError;
 
+mod backup;
 mod beep;
 mod read_card_data;
 mod set_master_slave;
+mod set_system_value;
 mod system_configuration;
+mod time;
 
 #[enum_dispatch::enum_dispatch]
 pub trait Command {
@@ -3,7 +3,6 @@ use crate::protocol::decoder::DecoderError;
 use crate::protocol::Command;
 
 #[derive(Copy, Clone, Debug)]
-#[allow(dead_code)]
 pub enum SetMasterSlave {
     Master = 0x4d,
     Slave = 0x53,
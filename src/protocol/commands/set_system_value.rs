@@ -0,0 +1,42 @@
+use crate::protocol::commands::Response;
+use crate::protocol::decoder::DecoderError;
+use crate::protocol::Command;
+
+/// Write `data` to the system memory of the station, starting at `offset`.
+#[derive(Clone, Debug)]
+pub struct SetSystemValue {
+    offset: u8,
+    data: Vec<u8>,
+}
+
+impl SetSystemValue {
+    pub const fn new(offset: u8, data: Vec<u8>) -> Self {
+        Self { offset, data }
+    }
+}
+
+#[derive(Debug)]
+pub struct SetSystemValueResponse {
+    pub offset: u8,
+}
+
+impl Command for SetSystemValue {
+    fn command_parameters(&self) -> Vec<u8> {
+        let mut parameters = vec![self.offset];
+        parameters.extend_from_slice(&self.data);
+        parameters
+    }
+
+    fn magic(&self) -> u8 {
+        0x82
+    }
+}
+
+impl Response for SetSystemValueResponse {
+    fn decode(data: &[u8]) -> Result<Self, DecoderError> {
+        let offset = *data
+            .first()
+            .ok_or(DecoderError::InvalidSystemValueResponse)?;
+        Ok(Self { offset })
+    }
+}
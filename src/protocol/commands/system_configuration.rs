@@ -1,4 +1,4 @@
-use std::ops::BitAnd;
+use std::ops::{BitAnd, Shl};
 
 use bitflags::bitflags;
 use chrono::{Duration, NaiveDate};
@@ -10,8 +10,18 @@ use crate::protocol::Command;
 
 const SYSTEM_CONFIGURATION_LENGTH: usize = 0x81;
 
+pub const SRR_CONFIGURATION_OFFSET: u8 = 0x04;
+pub const BACKUP_POINTER_HIGH_OFFSET: u8 = 0x1c;
+pub const BACKUP_POINTER_LOW_OFFSET: u8 = 0x21;
+pub const SRR_CHANNEL_OFFSET: u8 = 0x34;
+pub const MEMORY_OVERFLOW_OFFSET: u8 = 0x3d;
+pub const STATION_MODE_OFFSET: u8 = 0x71;
+pub const STATION_CODE_OFFSET: u8 = 0x72;
+pub const PROTOCOL_CONFIGURATION_OFFSET: u8 = 0x74;
+
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SystemConfiguration {
     pub serial_number: u32,
     pub srr_configuration: SRRConfiguration,
@@ -34,11 +44,13 @@ pub struct SystemConfiguration {
     pub punch_feedback: PunchFeedback,
     pub protocol_configuration: ProtocolConfiguration,
     pub wakeup_date: NaiveDate,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::duration"))]
     pub active_duration: Duration,
 }
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct SI6CardBlocks: u8 {
         const FIRST =   0b0000_0001;
         const SECOND =  0b0000_0010;
@@ -52,7 +64,8 @@ bitflags! {
 }
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct PunchFeedback: u8 {
         const OPTICAL = 0b0000_0001;
         const AUDIBLE = 0b0000_0100;
@@ -60,15 +73,17 @@ bitflags! {
 }
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct SRRConfiguration: u8 {
-        const OPTICAL = 0b0000_0001;
-        const AUDIBLE = 0b0000_0100;
+        const RED_CHANNEL =  0b0000_0001;
+        const BLUE_CHANNEL = 0b0000_0010;
     }
 }
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Copy, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
     pub struct ProtocolConfiguration: u8 {
         const EXTENDED_PROTOCOL =   0b0000_0001;
         const AUTO_SEND_OUT =       0b0000_0010;
@@ -78,7 +93,8 @@ bitflags! {
     }
 }
 
-#[derive(FromRepr, Debug)]
+#[derive(FromRepr, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum Model {
     SRRDongle = 0x6F21,
@@ -103,20 +119,23 @@ pub enum Model {
     BS11BS = 0xCD9B,
 }
 
-#[derive(FromRepr, Debug)]
+#[derive(FromRepr, Debug, PartialEq, Eq, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum SRRChannel {
     Red = 0x00,
     Blue = 0x01,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StationProgram {
     Competition,
     Training,
 }
 
 #[derive(FromRepr, Debug, PartialEq, Eq, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum StationMode {
     SIACSpecial = 0x01,
@@ -188,7 +207,7 @@ impl Response for SystemConfiguration {
             },
             mode: StationMode::from_repr(data[113])
                 .ok_or(DecoderError::UnknownStationMode(data[113]))?,
-            station_code: u16::from_be_bytes([data[114], data[115].bitand(0b1100_0000)]),
+            station_code: u16::from(data[114]) + u16::from(data[115].bitand(0b1100_0000)).shl(2u8),
             punch_feedback: PunchFeedback::from_bits_retain(data[115]),
             protocol_configuration: ProtocolConfiguration::from_bits_retain(data[116]),
             wakeup_date: naive_date_from_data(data[117], data[118], data[119])?,
@@ -199,17 +218,41 @@ impl Response for SystemConfiguration {
     }
 }
 
-fn naive_date_from_data(year: u8, month: u8, day: u8) -> Result<NaiveDate, DecoderError> {
+pub fn naive_date_from_data(year: u8, month: u8, day: u8) -> Result<NaiveDate, DecoderError> {
     let (year, month, day) = (2000 + i32::from(year), u32::from(month), u32::from(day));
 
     NaiveDate::from_ymd_opt(year, month, day).ok_or(DecoderError::InvalidDate(year, month, day))
 }
 
+impl SystemConfiguration {
+    /// The address the station will write its next backup record to.
+    #[must_use]
+    pub const fn backup_pointer(&self) -> u32 {
+        (self.backup_pointer_high as u32) << 16 | self.backup_pointer_low as u32
+    }
+
+    /// The first address past the end of the backup memory.
+    #[must_use]
+    pub const fn backup_memory_end(&self) -> u32 {
+        self.mem_kilobytes as u32 * 1024
+    }
+}
+
+impl Model {
+    /// Whether the station receives punches over SRR radio.
+    #[must_use]
+    pub const fn is_srr(&self) -> bool {
+        matches!(self, Self::SRRDongle | Self::BSM8SRR)
+    }
+}
+
 impl ProtocolConfiguration {
+    #[must_use]
     pub const fn is_extended_protocol(&self) -> bool {
         self.contains(Self::EXTENDED_PROTOCOL)
     }
 
+    #[must_use]
     pub const fn is_auto_send(&self) -> bool {
         self.contains(Self::AUTO_SEND_OUT)
     }
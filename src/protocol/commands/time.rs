@@ -0,0 +1,82 @@
+use chrono::{Datelike, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+
+use crate::protocol::commands::Response;
+use crate::protocol::decoder::DecoderError;
+use crate::protocol::{naive_date_from_data, Command};
+
+const TIME_LENGTH: usize = 7;
+const SECONDS_IN_HALF_DAY: u32 = 12 * 60 * 60;
+
+#[derive(Copy, Clone, Debug)]
+pub struct GetTime;
+
+#[derive(Copy, Clone, Debug)]
+pub struct SetTime {
+    time: NaiveDateTime,
+}
+
+impl SetTime {
+    pub const fn new(time: NaiveDateTime) -> Self {
+        Self { time }
+    }
+}
+
+#[derive(Debug)]
+pub struct StationTime(pub NaiveDateTime);
+
+impl Command for GetTime {
+    fn command_parameters(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn magic(&self) -> u8 {
+        0xf7
+    }
+}
+
+impl Command for SetTime {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn command_parameters(&self) -> Vec<u8> {
+        let seconds = self.time.num_seconds_from_midnight();
+        let half_day_seconds = (seconds % SECONDS_IN_HALF_DAY) as u16;
+        let time_date = (self.time.weekday().num_days_from_sunday() as u8) << 1
+            | u8::from(seconds >= SECONDS_IN_HALF_DAY);
+        let sub_seconds =
+            (u64::from(self.time.nanosecond().min(999_999_999)) * 256 / 1_000_000_000) as u8;
+
+        let [half_day_seconds_high, half_day_seconds_low] = half_day_seconds.to_be_bytes();
+        vec![
+            (self.time.year() - 2000).clamp(0, 99) as u8,
+            self.time.month() as u8,
+            self.time.day() as u8,
+            time_date,
+            half_day_seconds_high,
+            half_day_seconds_low,
+            sub_seconds,
+        ]
+    }
+
+    fn magic(&self) -> u8 {
+        0xf6
+    }
+}
+
+impl Response for StationTime {
+    fn decode(data: &[u8]) -> Result<Self, DecoderError> {
+        if data.len() != TIME_LENGTH {
+            return Err(DecoderError::InvalidTimeLength(TIME_LENGTH, data.len()));
+        }
+
+        let day = naive_date_from_data(data[0], data[1], data[2])?;
+
+        let mut seconds = u32::from(u16::from_be_bytes([data[4], data[5]]));
+        if data[3] & 0b0000_0001 == 1 {
+            seconds += SECONDS_IN_HALF_DAY;
+        }
+        let time = NaiveTime::from_num_seconds_from_midnight_opt(seconds, 0)
+            .ok_or(DecoderError::InvalidPunchTime)?
+            + TimeDelta::milliseconds(i64::from(data[6]) * 1000 / 256);
+
+        Ok(Self(day.and_time(time)))
+    }
+}
@@ -0,0 +1,80 @@
+/// The CRC polynomial of the station protocol, `x^16 + x^15 + x^2 + 1`.
+const POLYNOMIAL: u16 = 0x8005;
+
+/// The remainder of each high byte shifted out of the register.
+const TABLE: [u16; 256] = table();
+
+const fn table() -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut value = (byte as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 0x8000 == 0 {
+                value << 1
+            } else {
+                (value << 1) ^ POLYNOMIAL
+            };
+            bit += 1;
+        }
+        table[byte] = value;
+        byte += 1;
+    }
+    table
+}
+
+/// The checksum of a frame, computed over its command, length and data bytes.
+#[must_use]
+pub fn crc(buf: &[u8]) -> u16 {
+    let mut crc = Crc::new();
+    crc.update(buf);
+    crc.finish()
+}
+
+/// An incremental CRC, giving the same checksum as [`crc`] over the concatenation
+/// of every slice passed to [`Crc::update`].
+///
+/// The register starts with the first two bytes and shifts in the rest, padded with zeros to
+/// an even length plus two bytes, except that a single word is returned as is and a single
+/// byte checksums to 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Crc {
+    register: u16,
+    len: usize,
+}
+
+impl Crc {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            register: 0,
+            len: 0,
+        }
+    }
+
+    pub fn update(&mut self, buf: &[u8]) {
+        for &byte in buf {
+            self.register = shift(self.register, byte);
+        }
+        self.len += buf.len();
+    }
+
+    #[must_use]
+    pub const fn finish(&self) -> u16 {
+        match self.len {
+            0 | 1 => 0,
+            2 => self.register,
+            len if len % 2 == 1 => shift(self.register, 0),
+            _ => shift(shift(self.register, 0), 0),
+        }
+    }
+}
+
+const fn shift(register: u16, byte: u8) -> u16 {
+    (register << 8 | byte as u16) ^ TABLE[(register >> 8) as usize]
+}
+
+#[cfg(test)]
+mod tests;
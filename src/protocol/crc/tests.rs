@@ -1,7 +1,15 @@
 #![allow(clippy::all, clippy::pedantic, clippy::nursery)]
 use std::ffi::{c_int, c_short, c_uchar, c_uint, c_ushort};
 
-pub fn crc(buf: &[u8]) -> u16 {
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use super::{crc, Crc};
+
+/// The translated C routine from the SportIdent protocol documentation, which `crc` replaces.
+/// Its loop counter overflows for buffers of 64 KiB and above.
+fn reference_crc(buf: &[u8]) -> u16 {
+    assert!(buf.len() < 1 << 16);
     unsafe { generated_crc(buf.len() as c_uint, buf.as_ptr() as *const c_uchar) as u16 }
 }
 
@@ -60,3 +68,47 @@ unsafe extern "C" fn generated_crc(ui_count: c_uint, puc_dat: *const c_uchar) ->
     }
     ui_tmp1 as c_uint
 }
+
+#[test]
+fn get_system_configuration_command() {
+    assert_eq!(crc(&[0x83, 0x02, 0x00, 0x80]), 0xbf17);
+}
+
+#[test]
+fn short_buffers() {
+    assert_eq!(crc(&[]), 0);
+    assert_eq!(crc(&[0xab]), 0);
+    assert_eq!(crc(&[0xab, 0xcd]), 0xabcd);
+}
+
+#[test]
+fn buffers_above_64_kib() {
+    let buf: Vec<u8> = (0..70_000u32).map(|i| i as u8).collect();
+    let mut expected = Crc::new();
+    buf.chunks(1000).for_each(|chunk| expected.update(chunk));
+    assert_eq!(crc(&buf), expected.finish());
+    assert_ne!(crc(&buf), u16::from_be_bytes([buf[0], buf[1]]));
+}
+
+proptest! {
+    #[test]
+    fn matches_reference(buf in vec(any::<u8>(), 0..600)) {
+        prop_assert_eq!(crc(&buf), reference_crc(&buf));
+    }
+
+    #[test]
+    fn incremental_matches_reference(
+        buf in vec(any::<u8>(), 0..600),
+        splits in vec(any::<prop::sample::Index>(), 0..8),
+    ) {
+        let mut splits: Vec<usize> = splits.iter().map(|index| index.index(buf.len() + 1)).collect();
+        splits.sort_unstable();
+        let mut hasher = Crc::new();
+        let mut start = 0;
+        for split in splits.into_iter().chain([buf.len()]) {
+            hasher.update(&buf[start..split]);
+            start = split;
+        }
+        prop_assert_eq!(hasher.finish(), reference_crc(&buf));
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::{BitAnd, Shl};
+
 use tokio_util::bytes::{Buf, BytesMut};
 use tokio_util::codec::Decoder;
 
@@ -5,10 +7,12 @@ use crate::protocol;
 use crate::protocol::responses::card::{Card, CardRemoved};
 use crate::protocol::responses::card_punch::CardPunch;
 use crate::protocol::{
-    crc, Codec, ReadCardDataResponse, Response, SetMasterSlaveResponse, SystemConfiguration,
+    BackupData, Codec, Crc, EraseBackupDataResponse, ReadCardDataResponse, Response,
+    SetMasterSlaveResponse, SetSystemValueResponse, StationTime, SystemConfiguration,
 };
 
-const IGNORED_DATA_LENGTH: usize = 2;
+const STATION_CODE_LENGTH: usize = 2;
+const STATION_CODE_HIGH_MASK: u8 = 0b0000_0011;
 
 #[derive(thiserror::Error, Debug)]
 pub enum DecoderError {
@@ -18,7 +22,7 @@ pub enum DecoderError {
     InvalidCommandSent,
     #[error("Invalid start byte ({0:#04x})")]
     InvalidStartByte(u8),
-    #[error("Length should be at least {IGNORED_DATA_LENGTH} bytes, but was {0}")]
+    #[error("Length should be at least {STATION_CODE_LENGTH} bytes, but was {0}")]
     InvalidLength(usize),
     #[error("Invalid end byte ({0:#04x})")]
     InvalidEndByte(u8),
@@ -48,6 +52,21 @@ pub enum DecoderError {
     InvalidPunchTime,
     #[error("Invalid owner data")]
     InvalidOwnerData,
+    #[error("Received invalid backup data length ({0} bytes)")]
+    InvalidBackupDataLength(usize),
+    #[error("Received invalid set system value response")]
+    InvalidSystemValueResponse,
+    #[error("Received invalid time, should be exactly {0} bytes but {1} bytes were given")]
+    InvalidTimeLength(usize, usize),
+    #[error("Received invalid punch length ({0} bytes)")]
+    InvalidPunchLength(usize),
+}
+
+/// A response, along with the code of the station that sent it.
+#[derive(Debug)]
+pub struct Frame {
+    pub station_code: u16,
+    pub response: Responses,
 }
 
 #[derive(Debug)]
@@ -58,26 +77,41 @@ pub enum Responses {
     CardRemoved(CardRemoved),
     CardData(ReadCardDataResponse),
     CardPunch(CardPunch),
+    BackupData(BackupData),
+    EraseBackupDataResponse(EraseBackupDataResponse),
+    SetSystemValueResponse(SetSystemValueResponse),
+    StationTime(StationTime),
 }
 
-impl TryFrom<(u8, Vec<u8>)> for Responses {
+impl TryFrom<(u8, [u8; STATION_CODE_LENGTH], Vec<u8>)> for Responses {
     type Error = DecoderError;
 
-    fn try_from((cmd, data): (u8, Vec<u8>)) -> Result<Self, Self::Error> {
+    fn try_from(
+        (cmd, station, data): (u8, [u8; STATION_CODE_LENGTH], Vec<u8>),
+    ) -> Result<Self, Self::Error> {
         Ok(match cmd {
             0x83 => Self::SystemConfiguration(SystemConfiguration::decode(&data)?),
             0xf0 => Self::SetMasterSlaveResponse(SetMasterSlaveResponse::decode(&data)?),
             0xe7 => Self::CardRemoved(CardRemoved::decode(&data)?),
             0xe8 => Self::CardInserted(Card::decode(&data)?),
             0xef => Self::CardData(ReadCardDataResponse::decode(&data)?),
-            0xd3 => Self::CardPunch(CardPunch::decode(&data)?),
+            0xd3 => Self::CardPunch(CardPunch::decode(station, &data)?),
+            0x81 => Self::BackupData(BackupData::decode(&data)?),
+            0xf5 => Self::EraseBackupDataResponse(EraseBackupDataResponse::decode(&data)?),
+            0x82 => Self::SetSystemValueResponse(SetSystemValueResponse::decode(&data)?),
+            0xf6 | 0xf7 => Self::StationTime(StationTime::decode(&data)?),
             _ => return Err(DecoderError::InvalidCommand(cmd)),
         })
     }
 }
 
+/// The station code stored in the CN1 and CN0 bytes of a frame.
+pub fn station_code([station_high, station_low]: [u8; STATION_CODE_LENGTH]) -> u16 {
+    u16::from(station_low) + u16::from(station_high.bitand(STATION_CODE_HIGH_MASK)).shl(8u8)
+}
+
 impl Decoder for Codec {
-    type Item = Responses;
+    type Item = Frame;
     type Error = DecoderError;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
@@ -110,7 +144,7 @@ impl Decoder for Codec {
 
         let (cmd, length) = (cmd_and_length[0], cmd_and_length[1] as usize);
 
-        if length < IGNORED_DATA_LENGTH {
+        if length < STATION_CODE_LENGTH {
             return Err(DecoderError::InvalidLength(length));
         }
 
@@ -126,14 +160,18 @@ impl Decoder for Codec {
             return Ok(None);
         }
         src.advance(2);
+        let mut crc = Crc::new();
+        crc.update(&cmd_and_length);
 
-        let mut ignored = [0; IGNORED_DATA_LENGTH];
+        let mut station = [0; STATION_CODE_LENGTH];
 
-        ignored.copy_from_slice(&src[..IGNORED_DATA_LENGTH]);
-        src.advance(IGNORED_DATA_LENGTH);
+        station.copy_from_slice(&src[..STATION_CODE_LENGTH]);
+        src.advance(STATION_CODE_LENGTH);
+        crc.update(&station);
 
-        let data = src[..length - IGNORED_DATA_LENGTH].to_vec();
-        src.advance(length - IGNORED_DATA_LENGTH);
+        let data = src[..length - STATION_CODE_LENGTH].to_vec();
+        src.advance(length - STATION_CODE_LENGTH);
+        crc.update(&data);
 
         let mut crc_and_end = [0; 3];
 
@@ -151,15 +189,14 @@ impl Decoder for Codec {
             return Err(DecoderError::InvalidEndByte(end));
         }
 
-        let mut check = Vec::with_capacity(2 + length);
-        check.extend_from_slice(cmd_and_length.as_slice());
-        check.extend_from_slice(ignored.as_slice());
-        check.extend_from_slice(data.as_slice());
-        let crc_calc = crc(check.as_slice());
+        let crc_calc = crc.finish();
         if crc_calc != crc_recv {
             return Err(DecoderError::InvalidChecksum(crc_calc, crc_recv));
         }
 
-        Ok(Some((cmd, data).try_into()?))
+        Ok(Some(Frame {
+            station_code: station_code(station),
+            response: (cmd, station, data).try_into()?,
+        }))
     }
 }
@@ -3,7 +3,8 @@ use tokio_util::codec::Encoder;
 
 use crate::protocol;
 use crate::protocol::{
-    crc, Beep, Codec, Command, GetSystemConfiguration, ReadCardData, SetMasterSlave,
+    crc, Beep, Codec, Command, EraseBackupData, GetBackupData, GetSystemConfiguration, GetTime,
+    ReadCardData, SetMasterSlave, SetSystemValue, SetTime,
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -22,6 +23,11 @@ pub enum Commands {
     GetSystemConfiguration(GetSystemConfiguration),
     Beep(Beep),
     ReadCardData(ReadCardData),
+    GetBackupData(GetBackupData),
+    EraseBackupData(EraseBackupData),
+    SetSystemValue(SetSystemValue),
+    GetTime(GetTime),
+    SetTime(SetTime),
 }
 impl Encoder<Commands> for Codec {
     type Error = EncoderError;
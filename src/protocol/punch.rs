@@ -5,6 +5,7 @@ use std::ops::{AddAssign, BitAnd, Shl, Shr};
 use strum_macros::FromRepr;
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone, FromRepr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum DayOfWeek {
     Monday = 0,
@@ -17,6 +18,7 @@ pub enum DayOfWeek {
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone, FromRepr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum WeekCounter {
     First = 0,
@@ -32,6 +34,7 @@ struct BasePunch {
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Punch {
     pub time: NaiveTime,
     pub day_of_week: DayOfWeek,
@@ -40,6 +43,7 @@ pub struct Punch {
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubSecondPunch {
     pub time: NaiveTime,
     pub day_of_week: DayOfWeek,
@@ -47,6 +51,7 @@ pub struct SubSecondPunch {
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StartOrFinishPunch {
     Normal(Punch),
     SubSecond(SubSecondPunch),
@@ -83,11 +88,8 @@ impl BasePunch {
                     time.add_assign(TWELVE_HOURS);
                 }
 
-                let day_of_week = punch_time_date
-                    .bitand(0b0000_1110)
-                    .shr(1u8)
-                    .wrapping_sub(1u8)
-                    % 7u8;
+                // Stations count days from Sunday (0), `DayOfWeek` counts from Monday.
+                let day_of_week = (punch_time_date.bitand(0b0000_1110).shr(1u8) + 6u8) % 7u8;
                 let week_counter = punch_time_date.bitand(0b0011_0000).shr(4u8);
 
                 Ok(Some(Self {
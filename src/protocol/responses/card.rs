@@ -1,6 +1,7 @@
 use crate::protocol::{DecoderError, Response};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CardType {
     Si8,
     Si9,
@@ -25,6 +26,8 @@ impl CardType {
 }
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SerializedCard"))]
 pub struct Card {
     pub card_type: CardType,
     pub number: u32,
@@ -38,6 +41,26 @@ impl Card {
     }
 }
 
+/// A card as it is serialized, checked by [`Card::new`] when it is deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SerializedCard {
+    card_type: CardType,
+    number: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerializedCard> for Card {
+    type Error = DecoderError;
+
+    fn try_from(card: SerializedCard) -> Result<Self, Self::Error> {
+        match Self::new(card.number)? {
+            checked if checked.card_type == card.card_type => Ok(checked),
+            _ => Err(DecoderError::InvalidCardNumber(card.number)),
+        }
+    }
+}
+
 impl Response for Card {
     fn decode(data: &[u8]) -> Result<Self, DecoderError> {
         const CARD_INSERTED_LENGTH: usize = 4;
@@ -1,18 +1,117 @@
+use std::ops::BitAnd;
+
 use crate::protocol::responses::card::Card;
-use crate::protocol::{DecoderError, Response, SubSecondPunch};
+use crate::protocol::{
+    station_code, BackupRecord, DecoderError, Response, SRRChannel, SubSecondPunch,
+};
+
+/// Set in CN1 when an SRR dongle received the punch over the air.
+const SRR_RADIO_FLAG: u8 = 0b1000_0000;
+/// Set in CN1 when the punch arrived on the blue SRR channel.
+const SRR_BLUE_CHANNEL_FLAG: u8 = 0b0100_0000;
+
+/// How a punch reached the reader.
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PunchSource {
+    /// Punched directly on the connected station (or sent over a cable or GSM link).
+    Direct,
+    /// Received over the air by an SRR dongle, on the given channel.
+    Radio(SRRChannel),
+    /// Read back from the backup memory of the connected station, to fill a gap in the stream.
+    Backup,
+}
 
 #[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CardPunch {
     pub punch: SubSecondPunch,
     pub card: Card,
+    /// The code of the station the card was punched at.
+    pub station_code: u16,
+    pub source: PunchSource,
+    /// The backup memory address the punch was stored at by the station.
+    pub backup_address: Option<u32>,
 }
 
-impl Response for CardPunch {
-    fn decode(data: &[u8]) -> Result<Self, DecoderError> {
+impl CardPunch {
+    pub(crate) const fn from_backup_record(record: BackupRecord, station_code: u16) -> Self {
+        Self {
+            punch: record.punch,
+            card: record.card,
+            station_code,
+            source: PunchSource::Backup,
+            backup_address: Some(record.address),
+        }
+    }
+
+    pub(crate) fn decode(station: [u8; 2], data: &[u8]) -> Result<Self, DecoderError> {
+        if data.len() < 8 {
+            return Err(DecoderError::InvalidPunchLength(data.len()));
+        }
         let card = Card::decode(&data[0..4])?;
         let punch =
             SubSecondPunch::decode_punch(card.card_type, [data[4], data[7], data[5], data[6]])?
-                .unwrap();
-        Ok(Self { punch, card })
+                .ok_or(DecoderError::InvalidPunchTime)?;
+
+        let backup_address = data
+            .get(8..11)
+            .map(|address| u32::from_be_bytes([0, address[0], address[1], address[2]]));
+        let station_high = station[0];
+        let source = if station_high.bitand(SRR_RADIO_FLAG) == 0 {
+            PunchSource::Direct
+        } else if station_high.bitand(SRR_BLUE_CHANNEL_FLAG) == 0 {
+            PunchSource::Radio(SRRChannel::Red)
+        } else {
+            PunchSource::Radio(SRRChannel::Blue)
+        };
+
+        Ok(Self {
+            punch,
+            card,
+            station_code: station_code(station),
+            source,
+            backup_address,
+        })
+    }
+
+    /// The CN1 and CN0 bytes and the data of the frame a station sends this punch in.
+    #[cfg(feature = "simulator")]
+    pub(crate) fn encode(&self) -> ([u8; 2], Vec<u8>) {
+        use chrono::Timelike;
+
+        let [_, _, code_high, code_low] = u32::from(self.station_code).to_be_bytes();
+        let station_high = code_high.bitand(0b0000_0011)
+            | match self.source {
+                PunchSource::Direct | PunchSource::Backup => 0,
+                PunchSource::Radio(SRRChannel::Red) => SRR_RADIO_FLAG,
+                PunchSource::Radio(SRRChannel::Blue) => SRR_RADIO_FLAG | SRR_BLUE_CHANNEL_FLAG,
+            };
+
+        let time = self.punch.time;
+        let seconds = time.num_seconds_from_midnight();
+        // Stations count days from Sunday (0), `DayOfWeek` counts from Monday.
+        let day_of_week = (self.punch.day_of_week as u8 + 1) % 7;
+        let punch_time_date =
+            (self.punch.week_counter as u8) << 4 | day_of_week << 1 | u8::from(seconds >= 43200);
+        let [_, _, time_high, time_low] = (seconds % 43200).to_be_bytes();
+        let sub_second =
+            u8::try_from((time.nanosecond() / 1_000_000 * 255).div_ceil(1000)).unwrap_or(u8::MAX);
+
+        let [_, card_2, card_1, card_0] = self.card.number.to_be_bytes();
+        let mut data = vec![
+            0,
+            card_2,
+            card_1,
+            card_0,
+            punch_time_date,
+            time_high,
+            time_low,
+            sub_second,
+        ];
+        if let Some(address) = self.backup_address {
+            data.extend_from_slice(&address.to_be_bytes()[1..]);
+        }
+        ([station_high, code_low], data)
     }
 }
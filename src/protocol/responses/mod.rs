@@ -1,2 +1,5 @@
 pub mod card;
 pub mod card_punch;
+
+#[cfg(test)]
+mod tests;
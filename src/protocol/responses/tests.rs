@@ -0,0 +1,81 @@
+#![allow(clippy::pedantic)]
+
+use std::str::FromStr;
+
+use chrono::NaiveTime;
+
+use crate::protocol::punch::DayOfWeek::{Friday, Sunday};
+use crate::protocol::punch::SubSecondPunch;
+use crate::protocol::punch::WeekCounter::Second;
+use crate::protocol::responses::card::Card;
+use crate::protocol::responses::card_punch::{CardPunch, PunchSource};
+use crate::protocol::SRRChannel;
+
+const PUNCH_DATA: [u8; 11] = [
+    0x00, 0x6b, 0xe4, 0x04, 0x1b, 0x50, 0x3d, 0x80, 0x00, 0x01, 0x08,
+];
+
+fn expected_punch() -> SubSecondPunch {
+    SubSecondPunch {
+        time: NaiveTime::from_str("17:42:21.501").unwrap(),
+        day_of_week: Friday,
+        week_counter: Second,
+    }
+}
+
+#[test]
+fn direct_punch() {
+    assert_eq!(
+        CardPunch::decode([0x00, 0x1f], &PUNCH_DATA).unwrap(),
+        CardPunch {
+            punch: expected_punch(),
+            card: Card::new(7070724).unwrap(),
+            station_code: 31,
+            source: PunchSource::Direct,
+            backup_address: Some(0x108),
+        }
+    );
+}
+
+#[test]
+fn sunday_punch() {
+    let mut data = PUNCH_DATA;
+    // Day 0, the first day stations count from.
+    data[4] = 0x11;
+    assert_eq!(
+        CardPunch::decode([0x00, 0x1f], &data)
+            .unwrap()
+            .punch
+            .day_of_week,
+        Sunday
+    );
+}
+
+#[test]
+fn radio_punch() {
+    assert_eq!(
+        CardPunch::decode([0xc1, 0x05], &PUNCH_DATA).unwrap(),
+        CardPunch {
+            punch: expected_punch(),
+            card: Card::new(7070724).unwrap(),
+            station_code: 261,
+            source: PunchSource::Radio(SRRChannel::Blue),
+            backup_address: Some(0x108),
+        }
+    );
+}
+
+#[test]
+fn punch_without_backup_address() {
+    assert_eq!(
+        CardPunch::decode([0x00, 0x1f], &PUNCH_DATA[..8])
+            .unwrap()
+            .backup_address,
+        None
+    );
+}
+
+#[test]
+fn short_punch() {
+    assert!(CardPunch::decode([0x00, 0x1f], &PUNCH_DATA[..6]).is_err());
+}
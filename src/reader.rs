@@ -1,27 +1,55 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
+use chrono::{Local, NaiveDateTime};
 use futures::{SinkExt, StreamExt};
 use serialport::{SerialPortType, UsbPortInfo};
-use tokio_serial::{ClearBuffer, SerialPort, SerialPortBuilderExt, SerialStream};
-use tokio_util::codec::Framed;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_serial::{ClearBuffer, SerialPort, SerialPortBuilderExt};
+use tokio_util::codec::{Framed, FramedParts};
 
 use crate::error::Result;
-use crate::protocol::responses::card::CardType;
+use crate::protocol::responses::card::{Card, CardType};
 use crate::protocol::responses::card_punch::CardPunch;
 use crate::protocol::{
-    Beep, CardBlocks, CardOwnerData, CardReadout, Codec, Commands, DecoderError, FromCardBlocks,
-    GetSystemConfiguration, ReadCardData, ReadCardDataResponse, Responses, SetMasterSlave,
-    StationMode, SystemConfiguration, BLOCK_SIZE,
+    Beep, CardBlocks, CardOwnerData, CardReadout, Codec, Commands, DecoderError, Frame,
+    FromCardBlocks, GetBackupData, GetSystemConfiguration, GetTime, ProtocolConfiguration,
+    ReadCardData, ReadCardDataResponse, Responses, SRRChannel, SRRConfiguration, SetMasterSlave,
+    SetSystemValue, SetTime, StationMode, StationTime, SystemConfiguration, BLOCK_SIZE,
+    PROTOCOL_CONFIGURATION_OFFSET, SRR_CHANNEL_OFFSET, SRR_CONFIGURATION_OFFSET,
+    STATION_CODE_OFFSET, STATION_MODE_OFFSET,
 };
 use crate::Error;
 
 const HIGH_SPEED_BAUD_RATE: u32 = 38400;
 const LOW_SPEED_BAUD_RATE: u32 = 4800;
 
+/// Data read from a card, along with the card and the code of the station that read it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Readout<T> {
+    pub station_code: u16,
+    pub card: Card,
+    pub data: T,
+}
+
+/// A byte stream to a station.
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Transport for T {}
+
+type Connection = Framed<Box<dyn Transport>, Codec>;
+
 pub struct Reader {
-    framed_codec: Framed<SerialStream, Codec>,
+    framed_codec: Connection,
     system_configuration: SystemConfiguration,
+    /// The configuration of the master station, while a remote station is coupled.
+    master_system_configuration: Option<SystemConfiguration>,
+    master_mode_pending: bool,
+    /// Punches sent in auto send mode while waiting for the response to a command.
+    pending_punches: VecDeque<CardPunch>,
+    /// A frame received by [`Reader::wait_for_frame`], handled by the next poll.
+    received_frame: Option<Frame>,
 }
 
 impl Reader {
@@ -53,6 +81,23 @@ impl Reader {
             .await?;
         }
 
+        Self::from_connection(into_connection(framed_codec)).await
+    }
+
+    /// Connect to a station over any byte stream, e.g. a simulated station or a serial port
+    /// forwarded over the network.
+    pub async fn from_transport(transport: impl Transport + 'static) -> Result<Self> {
+        let mut framed_codec: Connection = Framed::new(Box::new(transport), Codec::default());
+        send_and_receive_command(
+            &mut framed_codec,
+            Commands::SetMasterSlave(SetMasterSlave::Master),
+        )
+        .await?;
+
+        Self::from_connection(framed_codec).await
+    }
+
+    async fn from_connection(mut framed_codec: Connection) -> Result<Self> {
         let system_configuration = send_and_receive_command(
             &mut framed_codec,
             Commands::GetSystemConfiguration(GetSystemConfiguration),
@@ -64,23 +109,38 @@ impl Reader {
         Ok(Self {
             framed_codec,
             system_configuration,
+            master_system_configuration: None,
+            master_mode_pending: false,
+            pending_punches: VecDeque::new(),
+            received_frame: None,
         })
     }
 
-    pub async fn auto_connect() -> Result<Self> {
+    /// The serial ports of connected readers, detected by their USB vendor and product ids.
+    pub fn available_ports() -> Result<Vec<String>> {
         const SPORTIDENT_VENDOR_ID: u16 = 4292;
         const SPORTIDENT_READER_PRODUCT_ID: u16 = 32778;
 
-        for port in serialport::available_ports()? {
-            if let SerialPortType::UsbPort(UsbPortInfo {
-                vid: SPORTIDENT_VENDOR_ID,
-                pid: SPORTIDENT_READER_PRODUCT_ID,
-                ..
-            }) = port.port_type
-            {
-                if let Ok(reader) = Self::connect(port.port_name).await {
-                    return Ok(reader);
-                }
+        Ok(serialport::available_ports()?
+            .into_iter()
+            .filter(|port| {
+                matches!(
+                    port.port_type,
+                    SerialPortType::UsbPort(UsbPortInfo {
+                        vid: SPORTIDENT_VENDOR_ID,
+                        pid: SPORTIDENT_READER_PRODUCT_ID,
+                        ..
+                    })
+                )
+            })
+            .map(|port| port.port_name)
+            .collect())
+    }
+
+    pub async fn auto_connect() -> Result<Self> {
+        for port in Self::available_ports()? {
+            if let Ok(reader) = Self::connect(port).await {
+                return Ok(reader);
             }
         }
 
@@ -88,22 +148,287 @@ impl Reader {
     }
 }
 
+impl Reader {
+    /// The system configuration of the connected station.
+    #[must_use]
+    pub const fn system_configuration(&self) -> &SystemConfiguration {
+        &self.system_configuration
+    }
+
+    /// Read the system configuration of the connected station again.
+    pub async fn refresh_system_configuration(&mut self) -> Result<&SystemConfiguration> {
+        let Responses::SystemConfiguration(system_configuration) = self
+            .send_and_receive(Commands::GetSystemConfiguration(GetSystemConfiguration))
+            .await?
+        else {
+            return Err(Error::InvalidResponseReceived);
+        };
+        self.system_configuration = system_configuration;
+
+        Ok(&self.system_configuration)
+    }
+
+    /// Read `length` bytes (at most 128) of the backup memory starting at `address`.
+    pub async fn read_backup(&mut self, address: u32, length: u8) -> Result<Vec<u8>> {
+        let Responses::BackupData(backup_data) = self
+            .send_and_receive(Commands::GetBackupData(GetBackupData::new(address, length)))
+            .await?
+        else {
+            return Err(Error::InvalidResponseReceived);
+        };
+        if backup_data.address != address {
+            return Err(Error::InvalidResponseReceived);
+        }
+
+        Ok(backup_data.data)
+    }
+
+    /// Read the clock of the station.
+    pub async fn station_time(&mut self) -> Result<NaiveDateTime> {
+        let Responses::StationTime(StationTime(time)) =
+            self.send_and_receive(Commands::GetTime(GetTime)).await?
+        else {
+            return Err(Error::InvalidResponseReceived);
+        };
+
+        Ok(time)
+    }
+
+    /// Set the clock of the station.
+    pub async fn set_station_time(&mut self, time: NaiveDateTime) -> Result<()> {
+        let Responses::StationTime(_) = self
+            .send_and_receive(Commands::SetTime(SetTime::new(time)))
+            .await?
+        else {
+            return Err(Error::InvalidResponseReceived);
+        };
+
+        Ok(())
+    }
+
+    /// Set the clock of the station to the local time of this computer.
+    pub async fn sync_station_time(&mut self) -> Result<()> {
+        self.set_station_time(Local::now().naive_local()).await
+    }
+
+    pub async fn set_station_mode(&mut self, mode: StationMode) -> Result<()> {
+        self.write_system_value(STATION_MODE_OFFSET, vec![mode as u8])
+            .await?;
+        self.refresh_system_configuration().await?;
+
+        Ok(())
+    }
+
+    /// Set the station code, codes above 255 use the two high bits of the feedback byte.
+    ///
+    /// The code must fit in these 10 bits.
+    pub async fn set_station_code(&mut self, code: u16) -> Result<()> {
+        if code > 0x3ff {
+            return Err(Error::InvalidStationCode(code));
+        }
+        let [code_high, code_low] = code.to_be_bytes();
+        let feedback = self.system_configuration.punch_feedback.bits() & 0b0011_1111;
+        self.write_system_value(
+            STATION_CODE_OFFSET,
+            vec![code_low, code_high << 6 | feedback],
+        )
+        .await?;
+        self.refresh_system_configuration().await?;
+
+        Ok(())
+    }
+
+    pub async fn set_protocol_configuration(
+        &mut self,
+        protocol_configuration: ProtocolConfiguration,
+    ) -> Result<()> {
+        self.write_system_value(
+            PROTOCOL_CONFIGURATION_OFFSET,
+            vec![protocol_configuration.bits()],
+        )
+        .await?;
+        self.refresh_system_configuration().await?;
+
+        Ok(())
+    }
+
+    /// Set the channel an SRR station sends or listens on.
+    pub async fn set_srr_channel(&mut self, channel: SRRChannel) -> Result<()> {
+        if !self.system_configuration.model.is_srr() {
+            return Err(Error::NotSRRStation);
+        }
+        self.write_system_value(SRR_CHANNEL_OFFSET, vec![channel as u8])
+            .await?;
+        self.refresh_system_configuration().await?;
+
+        Ok(())
+    }
+
+    /// Select the SRR channels a dongle receives punches on.
+    pub async fn set_srr_configuration(
+        &mut self,
+        srr_configuration: SRRConfiguration,
+    ) -> Result<()> {
+        if !self.system_configuration.model.is_srr() {
+            return Err(Error::NotSRRStation);
+        }
+        self.write_system_value(SRR_CONFIGURATION_OFFSET, vec![srr_configuration.bits()])
+            .await?;
+        self.refresh_system_configuration().await?;
+
+        Ok(())
+    }
+
+    /// Receive punches on both the red and the blue SRR channel.
+    pub async fn enable_srr_channels(&mut self) -> Result<()> {
+        self.set_srr_configuration(SRRConfiguration::RED_CHANNEL | SRRConfiguration::BLUE_CHANNEL)
+            .await
+    }
+
+    pub(crate) async fn write_system_value(&mut self, offset: u8, data: Vec<u8>) -> Result<()> {
+        let Responses::SetSystemValueResponse(response) = self
+            .send_and_receive(Commands::SetSystemValue(SetSystemValue::new(offset, data)))
+            .await?
+        else {
+            return Err(Error::InvalidResponseReceived);
+        };
+        if response.offset != offset {
+            return Err(Error::InvalidResponseReceived);
+        }
+
+        Ok(())
+    }
+
+    /// Switch the station to slave mode, so commands reach the station on its coupling coil.
+    pub(crate) async fn enter_slave_mode(&mut self) -> Result<()> {
+        if self.master_system_configuration.is_some() {
+            return Err(Error::RemoteStationAlreadyCoupled);
+        }
+        self.send_and_receive(Commands::SetMasterSlave(SetMasterSlave::Slave))
+            .await?;
+
+        // Read the configuration of the coupled station before anything switches back to
+        // master mode, and switch back if it can't be read.
+        let remote_system_configuration = match send_and_receive_command(
+            &mut self.framed_codec,
+            Commands::GetSystemConfiguration(GetSystemConfiguration),
+        )
+        .await
+        {
+            Ok(Responses::SystemConfiguration(system_configuration)) => system_configuration,
+            Ok(_) => {
+                self.master_mode_pending = true;
+                return Err(Error::InvalidResponseReceived);
+            }
+            Err(e) => {
+                self.master_mode_pending = true;
+                return Err(e);
+            }
+        };
+        self.master_system_configuration = Some(std::mem::replace(
+            &mut self.system_configuration,
+            remote_system_configuration,
+        ));
+
+        Ok(())
+    }
+
+    /// Switch back to master mode before the next command is sent.
+    pub(crate) const fn schedule_master_mode(&mut self) {
+        self.master_mode_pending = true;
+    }
+
+    pub(crate) async fn restore_master_mode(&mut self) -> Result<()> {
+        if !self.master_mode_pending {
+            return Ok(());
+        }
+        send_and_receive_command(
+            &mut self.framed_codec,
+            Commands::SetMasterSlave(SetMasterSlave::Master),
+        )
+        .await?;
+        if let Some(master_system_configuration) = self.master_system_configuration.take() {
+            self.system_configuration = master_system_configuration;
+        }
+        self.master_mode_pending = false;
+
+        Ok(())
+    }
+
+    pub(crate) async fn send_and_receive(&mut self, cmd: Commands) -> Result<Responses> {
+        self.restore_master_mode().await?;
+        self.framed_codec.send(cmd).await?;
+        loop {
+            match receive_command(&mut self.framed_codec).await? {
+                Responses::CardPunch(punch) => self.pending_punches.push_back(punch),
+                response => return Ok(response),
+            }
+        }
+    }
+}
+
 impl Reader {
     pub async fn beep_until_card_removed(&mut self) -> Result<()> {
+        self.restore_master_mode().await?;
         Ok(self.framed_codec.send(Commands::Beep(Beep)).await?)
     }
     pub async fn poll_card(&mut self) -> Result<CardReadout> {
-        self.poll_card_generic().await
+        Ok(self.poll_readout().await?.data)
     }
 
     pub async fn poll_card_with_owner_data(&mut self) -> Result<(CardReadout, CardOwnerData)> {
-        self.poll_card_generic().await
+        Ok(self.poll_readout().await?.data)
     }
     pub async fn poll_owner_data(&mut self) -> Result<CardOwnerData> {
-        self.poll_card_generic().await
+        Ok(self.poll_readout().await?.data)
+    }
+
+    /// Wait for a card to be inserted and read it, along with the code of the reading station.
+    pub async fn poll_readout<T: FromCardBlocks>(&mut self) -> Result<Readout<T>> {
+        let (station_code, card) = self.poll_card_inserted().await?;
+        let data = self.read_card_data(card.card_type).await?;
+
+        Ok(Readout {
+            station_code,
+            card,
+            data,
+        })
+    }
+
+    /// Waits until the next poll can return without waiting for the station: in readout mode
+    /// until a card is inserted, which [`Reader::poll_readout`] then reads, otherwise until a
+    /// punch arrives for [`Reader::poll_punch`]. Other frames, e.g. a removed card, are skipped.
+    /// Unlike polling, waiting is cancel safe: dropping the future neither loses a frame nor
+    /// interrupts a command, so it can be raced against other work with `tokio::select!`.
+    pub async fn wait_for_frame(&mut self) -> Result<()> {
+        let readout_mode = self.system_configuration.mode == StationMode::Readout;
+        if !readout_mode && !self.pending_punches.is_empty() {
+            return Ok(());
+        }
+
+        let polled = |frame: &Frame| match frame.response {
+            Responses::CardInserted(_) => readout_mode,
+            Responses::CardPunch(_) => !readout_mode,
+            _ => false,
+        };
+        self.received_frame = self.received_frame.take().filter(polled);
+        while self.received_frame.is_none() {
+            let frame = receive_frame(&mut self.framed_codec).await?;
+            self.received_frame = Some(frame).filter(polled);
+        }
+        Ok(())
+    }
+
+    async fn next_frame(&mut self) -> Result<Frame> {
+        match self.received_frame.take() {
+            Some(frame) => Ok(frame),
+            None => receive_frame(&mut self.framed_codec).await,
+        }
     }
 
-    async fn poll_card_generic<T: FromCardBlocks>(&mut self) -> Result<T> {
+    /// Wait for a card to be inserted, returning it along with the code of the reading station.
+    pub(crate) async fn poll_card_inserted(&mut self) -> Result<(u16, Card)> {
+        self.restore_master_mode().await?;
         if !self
             .system_configuration
             .protocol_configuration
@@ -116,13 +441,17 @@ impl Reader {
         }
 
         loop {
-            if let Responses::CardInserted(card) = receive_command(&mut self.framed_codec).await? {
-                let card_data = self.read_card_data(card.card_type).await?;
-                return Ok(card_data);
+            let Frame {
+                station_code,
+                response,
+            } = self.next_frame().await?;
+            if let Responses::CardInserted(card) = response {
+                return Ok((station_code, card));
             }
         }
     }
     pub async fn poll_punch(&mut self) -> Result<CardPunch> {
+        self.restore_master_mode().await?;
         if !self
             .system_configuration
             .protocol_configuration
@@ -131,27 +460,47 @@ impl Reader {
             return Err(Error::NotExtendedProtocolMode);
         }
 
-        if !self
-            .system_configuration
-            .protocol_configuration
-            .is_auto_send()
+        // SRR stations forward every received punch, regardless of the auto send setting.
+        if !self.system_configuration.model.is_srr()
+            && !self
+                .system_configuration
+                .protocol_configuration
+                .is_auto_send()
         {
             return Err(Error::NotAutoSendMode);
         }
 
-        match receive_command(&mut self.framed_codec).await? {
+        if let Some(punch) = self.pending_punches.pop_front() {
+            return Ok(punch);
+        }
+        match self.next_frame().await?.response {
             Responses::CardPunch(punch) => Ok(punch),
             _ => Err(Error::InvalidResponseReceived),
         }
     }
 
     async fn read_card_data<T: FromCardBlocks>(&mut self, card_type: CardType) -> Result<T> {
-        T::from_card_blocks(&mut ReaderBlocks::new(&mut self.framed_codec), card_type).await
+        T::from_card_blocks(&mut self.card_blocks(), card_type).await
+    }
+
+    /// The blocks of the inserted card, read on demand.
+    pub(crate) fn card_blocks(&mut self) -> impl CardBlocks + '_ {
+        ReaderBlocks::new(&mut self.framed_codec)
     }
 }
 
-async fn send_and_receive_command(
-    framed: &mut Framed<SerialStream, Codec>,
+/// Moves a connection to a type-erased transport, keeping its buffers.
+fn into_connection<T: Transport + 'static>(framed: Framed<T, Codec>) -> Connection {
+    let parts = framed.into_parts();
+    let mut connection =
+        FramedParts::new::<Commands>(Box::new(parts.io) as Box<dyn Transport>, parts.codec);
+    connection.read_buf = parts.read_buf;
+    connection.write_buf = parts.write_buf;
+    Framed::from_parts(connection)
+}
+
+async fn send_and_receive_command<T: Transport>(
+    framed: &mut Framed<T, Codec>,
     cmd: Commands,
 ) -> Result<Responses> {
     framed.send(cmd).await?;
@@ -159,13 +508,15 @@ async fn send_and_receive_command(
     receive_command(framed).await
 }
 
-async fn receive_command(framed: &mut Framed<SerialStream, Codec>) -> Result<Responses> {
+async fn receive_command<T: Transport>(framed: &mut Framed<T, Codec>) -> Result<Responses> {
+    Ok(receive_frame(framed).await?.response)
+}
+
+async fn receive_frame<T: Transport>(framed: &mut Framed<T, Codec>) -> Result<Frame> {
     Ok(framed.next().await.ok_or(Error::PortClosed)??)
 }
 
-async fn receive_card_data_response(
-    framed: &mut Framed<SerialStream, Codec>,
-) -> Result<ReadCardDataResponse> {
+async fn receive_card_data_response(framed: &mut Connection) -> Result<ReadCardDataResponse> {
     let response = receive_command(framed).await.map_err(|e| {
         if matches!(e, Error::DecoderError(DecoderError::InvalidCommandSent)) {
             Error::CardRemovedWhileReadingData
@@ -182,12 +533,12 @@ async fn receive_card_data_response(
 }
 
 struct ReaderBlocks<'a> {
-    framed_codec: &'a mut Framed<SerialStream, Codec>,
+    framed_codec: &'a mut Connection,
     cache: HashMap<usize, [u8; BLOCK_SIZE]>,
 }
 
 impl<'a> ReaderBlocks<'a> {
-    fn new(framed_codec: &'a mut Framed<SerialStream, Codec>) -> Self {
+    fn new(framed_codec: &'a mut Connection) -> Self {
         Self {
             framed_codec,
             cache: HashMap::default(),
@@ -195,8 +546,8 @@ impl<'a> ReaderBlocks<'a> {
     }
 }
 
-impl<'a> CardBlocks for ReaderBlocks<'a> {
-    async fn get_block<'b>(&'b mut self, index: u8) -> Result<&'b [u8; BLOCK_SIZE]> {
+impl CardBlocks for ReaderBlocks<'_> {
+    async fn get_block(&mut self, index: u8) -> Result<&[u8; BLOCK_SIZE]> {
         if let Entry::Vacant(e) = self.cache.entry(index as usize) {
             self.framed_codec
                 .send(Commands::ReadCardData(ReadCardData::new(index)))
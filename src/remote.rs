@@ -0,0 +1,56 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{Reader, Result};
+
+/// A station placed on the coupling coil of the master station, configured in slave mode.
+///
+/// Every [`Reader`] command issued through this handle (system configuration, time, backup
+/// memory...) reaches the coupled station. The master station is switched back to master mode
+/// by [`RemoteStation::close`], or before the next command once the handle is dropped.
+pub struct RemoteStation<'a> {
+    reader: &'a mut Reader,
+    closed: bool,
+}
+
+impl Reader {
+    /// Switch to slave mode and talk to the station on the coupling coil.
+    pub async fn remote_station(&mut self) -> Result<RemoteStation<'_>> {
+        self.enter_slave_mode().await?;
+
+        Ok(RemoteStation {
+            reader: self,
+            closed: false,
+        })
+    }
+}
+
+impl RemoteStation<'_> {
+    /// Switch the master station back to master mode.
+    pub async fn close(mut self) -> Result<()> {
+        self.closed = true;
+        self.reader.schedule_master_mode();
+        self.reader.restore_master_mode().await
+    }
+}
+
+impl Deref for RemoteStation<'_> {
+    type Target = Reader;
+
+    fn deref(&self) -> &Self::Target {
+        self.reader
+    }
+}
+
+impl DerefMut for RemoteStation<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.reader
+    }
+}
+
+impl Drop for RemoteStation<'_> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.reader.schedule_master_mode();
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::{elapsed, finish_time, start_time};
+use crate::{CardReadout, Punch};
+
+/// A control of a course, identified by the code of its station.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CourseControl {
+    Control(u16),
+    /// Any one of the given codes is accepted, e.g. for forked or replaced controls.
+    OneOf(Vec<u16>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CourseSection {
+    Ordered(CourseControl),
+    /// Controls that must all be punched, in any order.
+    Unordered(Vec<CourseControl>),
+}
+
+/// An ordered list of controls a card readout is validated against.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Course {
+    sections: Vec<CourseSection>,
+}
+
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone)]
+pub enum CourseStatus {
+    Ok,
+    /// The card was read after finishing, but some controls are missing.
+    Mispunch,
+    /// There is no finish punch and some controls are missing.
+    DidNotFinish,
+    /// All controls were punched, but there is neither a start punch nor a manual start time.
+    MissingStart,
+    /// All controls were punched, but there is no finish punch.
+    MissingFinish,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CourseResult {
+    pub status: CourseStatus,
+    /// Punches matched to controls of the course, in the order they were punched.
+    pub matched: Vec<Punch>,
+    pub missing: Vec<CourseControl>,
+    /// For each missing control, the number of matched punches before it in course order.
+    pub missing_after: Vec<usize>,
+    /// Punches which do not belong to the course, or repeat an already matched control.
+    pub extra: Vec<Punch>,
+    /// The start punch time, or the manual start time if the card has no start punch.
+    pub start: Option<NaiveTime>,
+    pub finish: Option<NaiveTime>,
+    pub running_time: Option<TimeDelta>,
+}
+
+impl CourseControl {
+    #[must_use]
+    pub fn accepts(&self, code: u16) -> bool {
+        match self {
+            Self::Control(control) => *control == code,
+            Self::OneOf(codes) => codes.contains(&code),
+        }
+    }
+}
+
+impl From<u16> for CourseControl {
+    fn from(code: u16) -> Self {
+        Self::Control(code)
+    }
+}
+
+impl Course {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sections: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn control(mut self, code: u16) -> Self {
+        self.sections
+            .push(CourseSection::Ordered(CourseControl::Control(code)));
+        self
+    }
+
+    #[must_use]
+    pub fn one_of(mut self, codes: impl IntoIterator<Item = u16>) -> Self {
+        self.sections
+            .push(CourseSection::Ordered(CourseControl::OneOf(
+                codes.into_iter().collect(),
+            )));
+        self
+    }
+
+    #[must_use]
+    pub fn unordered(
+        mut self,
+        controls: impl IntoIterator<Item = impl Into<CourseControl>>,
+    ) -> Self {
+        self.sections.push(CourseSection::Unordered(
+            controls.into_iter().map(Into::into).collect(),
+        ));
+        self
+    }
+
+    #[must_use]
+    pub fn sections(&self) -> &[CourseSection] {
+        &self.sections
+    }
+
+    #[must_use]
+    pub fn validate(&self, readout: &CardReadout) -> CourseResult {
+        self.validate_readout(readout, None)
+    }
+
+    /// Validates a readout, using `manual_start` when the card has no start punch.
+    #[must_use]
+    pub fn validate_with_start(
+        &self,
+        readout: &CardReadout,
+        manual_start: NaiveTime,
+    ) -> CourseResult {
+        self.validate_readout(readout, Some(manual_start))
+    }
+
+    fn validate_readout(
+        &self,
+        readout: &CardReadout,
+        manual_start: Option<NaiveTime>,
+    ) -> CourseResult {
+        let punches = &readout.punches;
+        let mut used = vec![false; punches.len()];
+        let mut missing = Vec::new();
+        let mut missing_after = Vec::new();
+        let mut cursor = 0;
+
+        let candidates = |control: &CourseControl, used: &[bool], cursor: usize| {
+            (cursor..punches.len())
+                .filter(|&index| !used[index] && control.accepts(punches[index].code))
+                .collect::<Vec<_>>()
+        };
+
+        for section in &self.sections {
+            match section {
+                CourseSection::Ordered(control) => {
+                    if let Some(&index) = candidates(control, &used, cursor).first() {
+                        used[index] = true;
+                        cursor = index + 1;
+                    } else {
+                        missing.push(control.clone());
+                        missing_after.push(used[..cursor].iter().filter(|used| **used).count());
+                    }
+                }
+                CourseSection::Unordered(controls) => {
+                    let candidates = controls
+                        .iter()
+                        .map(|control| candidates(control, &used, cursor))
+                        .collect::<Vec<_>>();
+                    let mut section_end = cursor;
+                    let mut section_missing = Vec::new();
+                    for (control, index) in controls.iter().zip(assign_unordered(&candidates)) {
+                        match index {
+                            Some(index) => {
+                                used[index] = true;
+                                section_end = section_end.max(index + 1);
+                            }
+                            None => section_missing.push(control.clone()),
+                        }
+                    }
+                    cursor = section_end;
+                    // Missing controls of the section follow the punched ones.
+                    let matched_before = used[..cursor].iter().filter(|used| **used).count();
+                    missing_after.extend(section_missing.iter().map(|_| matched_before));
+                    missing.extend(section_missing);
+                }
+            }
+        }
+
+        let (matched, extra) = punches
+            .iter()
+            .zip(used)
+            .partition::<Vec<_>, _>(|(_, used)| *used);
+        let start = start_time(readout, manual_start);
+        let finish = finish_time(readout);
+
+        let status = match (missing.is_empty(), finish.is_some()) {
+            (false, true) => CourseStatus::Mispunch,
+            (false, false) => CourseStatus::DidNotFinish,
+            (true, _) if start.is_none() => CourseStatus::MissingStart,
+            (true, false) => CourseStatus::MissingFinish,
+            (true, true) => CourseStatus::Ok,
+        };
+
+        CourseResult {
+            status,
+            matched: matched
+                .into_iter()
+                .map(|(punch, _)| punch.clone())
+                .collect(),
+            missing,
+            missing_after,
+            extra: extra.into_iter().map(|(punch, _)| punch.clone()).collect(),
+            start,
+            finish,
+            running_time: start
+                .zip(finish)
+                .map(|(start, finish)| elapsed(start, finish)),
+        }
+    }
+}
+
+/// Assigns a punch to each control of an unordered section, given the indices of the punches
+/// each control accepts. As many controls as possible are matched, and among those
+/// assignments one ending the section earliest is chosen.
+///
+/// Punches are added in the order they were punched, each extending a maximum matching of the
+/// punches before it by an augmenting path. The matching stops growing at the earliest punch
+/// which can end a maximum matching, so later punches are never used.
+fn assign_unordered(candidates: &[Vec<usize>]) -> Vec<Option<usize>> {
+    fn augment(
+        punch: usize,
+        controls: &BTreeMap<usize, Vec<usize>>,
+        assignment: &mut [Option<usize>],
+        visited: &mut [bool],
+    ) -> bool {
+        for &control in &controls[&punch] {
+            if visited[control] {
+                continue;
+            }
+            visited[control] = true;
+            if assignment[control]
+                .is_none_or(|assigned| augment(assigned, controls, assignment, visited))
+            {
+                assignment[control] = Some(punch);
+                return true;
+            }
+        }
+        false
+    }
+
+    // The controls accepting each punch, by punch index.
+    let mut controls = BTreeMap::<usize, Vec<usize>>::new();
+    for (control, punches) in candidates.iter().enumerate() {
+        for &punch in punches {
+            controls.entry(punch).or_default().push(control);
+        }
+    }
+
+    let mut assignment = vec![None; candidates.len()];
+    let mut matched = 0;
+    for &punch in controls.keys() {
+        if matched == candidates.len() {
+            break;
+        }
+        if augment(
+            punch,
+            &controls,
+            &mut assignment,
+            &mut vec![false; candidates.len()],
+        ) {
+            matched += 1;
+        }
+    }
+    assignment
+}
@@ -0,0 +1,49 @@
+use chrono::{NaiveTime, TimeDelta, Timelike};
+
+pub use course::{Course, CourseControl, CourseResult, CourseSection, CourseStatus};
+pub use relay::{Relay, RelayLeg, RelayLegResult, RelayResult, RelayStatus};
+pub use score::{ScoreCourse, ScorePunch, ScoreResult, ScoreStatus};
+pub use splits::{rank_legs, RankedSplit, Split, SplitControl};
+
+use crate::{CardReadout, PunchTime};
+
+mod course;
+mod relay;
+mod score;
+mod splits;
+
+#[cfg(test)]
+mod tests;
+
+const ONE_DAY: TimeDelta = TimeDelta::days(1);
+
+/// The time between two punches in whole seconds, assuming the second one happened less than a
+/// day later. Sub-second precision is dropped so results don't depend on the punch format.
+pub fn elapsed(from: NaiveTime, to: NaiveTime) -> TimeDelta {
+    let elapsed = whole_seconds(to) - whole_seconds(from);
+    if elapsed < TimeDelta::zero() {
+        elapsed + ONE_DAY
+    } else {
+        elapsed
+    }
+}
+
+fn whole_seconds(time: NaiveTime) -> NaiveTime {
+    time.with_nanosecond(0).unwrap_or(time)
+}
+
+/// The start time of a readout, falling back to a manually assigned start time.
+fn start_time(readout: &CardReadout, manual_start: Option<NaiveTime>) -> Option<NaiveTime> {
+    readout.start.as_ref().map(PunchTime::time).or(manual_start)
+}
+
+fn finish_time(readout: &CardReadout) -> Option<NaiveTime> {
+    readout.finish.as_ref().map(PunchTime::time)
+}
+
+fn running_time(readout: &CardReadout, manual_start: Option<NaiveTime>) -> Option<TimeDelta> {
+    Some(elapsed(
+        start_time(readout, manual_start)?,
+        finish_time(readout)?,
+    ))
+}
@@ -0,0 +1,159 @@
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::{elapsed, finish_time, Course, CourseResult, CourseStatus};
+use crate::CardReadout;
+
+/// The start times of a relay, shared by all teams.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Relay {
+    start: NaiveTime,
+    mass_start: Option<NaiveTime>,
+}
+
+/// A leg of a team, run on the team's course variant for that leg.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RelayLeg {
+    pub course: Course,
+    /// The readout of the leg runner's card, `None` until it was read out.
+    pub readout: Option<CardReadout>,
+}
+
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone)]
+pub enum RelayStatus {
+    Ok,
+    /// At least one leg mispunched.
+    Disqualified,
+    /// At least one leg did not finish.
+    DidNotFinish,
+    /// Some cards were not read out yet.
+    Running,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RelayLegResult {
+    /// The validation result, once the card was read out.
+    pub result: Option<CourseResult>,
+    /// When the leg started, `None` while the previous runner is out and there is no mass start.
+    pub start: Option<NaiveTime>,
+    /// Whether the leg runner started in the mass start instead of at a changeover.
+    pub mass_start: bool,
+    pub leg_time: Option<TimeDelta>,
+    /// When the runner finished and handed over to the next leg.
+    pub changeover: Option<NaiveTime>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RelayResult {
+    pub status: RelayStatus,
+    pub legs: Vec<RelayLegResult>,
+    /// The sum of all leg times, once every leg has one.
+    pub team_time: Option<TimeDelta>,
+}
+
+impl Relay {
+    #[must_use]
+    pub const fn new(start: NaiveTime) -> Self {
+        Self {
+            start,
+            mass_start: None,
+        }
+    }
+
+    /// Runners who could not start by `mass_start` because their previous runner had not
+    /// finished, start together at `mass_start`.
+    #[must_use]
+    pub const fn with_mass_start(mut self, mass_start: NaiveTime) -> Self {
+        self.mass_start = Some(mass_start);
+        self
+    }
+
+    /// Evaluates the legs of a team in running order. Each leg starts when the previous runner
+    /// finished, so a leg can be evaluated as soon as its card and the previous card are read out.
+    #[must_use]
+    pub fn evaluate(&self, legs: &[RelayLeg]) -> RelayResult {
+        let mut results = Vec::with_capacity(legs.len());
+        let mut previous_finish = PreviousFinish::Changeover(self.start);
+
+        for leg in legs {
+            let (start, mass_start) = self.leg_start(previous_finish);
+            let result = leg.readout.as_ref().map(|readout| {
+                start.map_or_else(
+                    || leg.course.validate(readout),
+                    |start| leg.course.validate_with_start(readout, start),
+                )
+            });
+            let changeover = leg.readout.as_ref().and_then(finish_time);
+
+            results.push(RelayLegResult {
+                result,
+                start,
+                mass_start,
+                leg_time: start
+                    .zip(changeover)
+                    .map(|(start, finish)| elapsed(start, finish)),
+                changeover,
+            });
+            previous_finish = match (&leg.readout, changeover) {
+                (None, _) => PreviousFinish::NotReadOut,
+                (Some(_), Some(changeover)) => PreviousFinish::Changeover(changeover),
+                (Some(_), None) => PreviousFinish::DidNotFinish,
+            };
+        }
+
+        let status = results
+            .iter()
+            .map(
+                |leg| match leg.result.as_ref().map(|result| result.status) {
+                    // The start is only missing while the previous leg was not read out yet.
+                    None | Some(CourseStatus::MissingStart) => RelayStatus::Running,
+                    Some(CourseStatus::Ok) => RelayStatus::Ok,
+                    Some(CourseStatus::Mispunch) => RelayStatus::Disqualified,
+                    Some(CourseStatus::DidNotFinish | CourseStatus::MissingFinish) => {
+                        RelayStatus::DidNotFinish
+                    }
+                },
+            )
+            .fold(RelayStatus::Ok, |team, leg| match (team, leg) {
+                (RelayStatus::Disqualified, _) | (_, RelayStatus::Disqualified) => {
+                    RelayStatus::Disqualified
+                }
+                (RelayStatus::DidNotFinish, _) | (_, RelayStatus::DidNotFinish) => {
+                    RelayStatus::DidNotFinish
+                }
+                (RelayStatus::Running, _) | (_, RelayStatus::Running) => RelayStatus::Running,
+                (RelayStatus::Ok, RelayStatus::Ok) => RelayStatus::Ok,
+            });
+        let team_time = results
+            .iter()
+            .map(|leg| leg.leg_time)
+            .sum::<Option<TimeDelta>>();
+
+        RelayResult {
+            status,
+            legs: results,
+            team_time,
+        }
+    }
+
+    fn leg_start(&self, previous_finish: PreviousFinish) -> (Option<NaiveTime>, bool) {
+        match (previous_finish, self.mass_start) {
+            // The previous runner may still finish before the mass start.
+            (PreviousFinish::NotReadOut, _) | (PreviousFinish::DidNotFinish, None) => (None, false),
+            (PreviousFinish::Changeover(changeover), Some(mass_start))
+                if elapsed(self.start, changeover) > elapsed(self.start, mass_start) =>
+            {
+                (Some(mass_start), true)
+            }
+            (PreviousFinish::Changeover(changeover), _) => (Some(changeover), false),
+            (PreviousFinish::DidNotFinish, Some(mass_start)) => (Some(mass_start), true),
+        }
+    }
+}
+
+/// How the previous leg of a team ended, as far as known from its readout.
+#[derive(Debug, Copy, Clone)]
+enum PreviousFinish {
+    NotReadOut,
+    DidNotFinish,
+    Changeover(NaiveTime),
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::{finish_time, running_time, start_time};
+use crate::{CardReadout, Punch};
+
+const SECONDS_IN_MINUTE: i64 = 60;
+
+/// A score course, where each control is worth points and finishing late costs points.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScoreCourse {
+    points: BTreeMap<u16, u32>,
+    time_limit: TimeDelta,
+    penalty_per_minute: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScorePunch {
+    pub punch: Punch,
+    pub points: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone)]
+pub enum ScoreStatus {
+    Ok,
+    /// There is no finish punch, so the overtime is unknown.
+    DidNotFinish,
+    /// There is neither a start punch nor a manual start time, so the overtime is unknown.
+    MissingStart,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScoreResult {
+    pub status: ScoreStatus,
+    /// The points of all scored controls, before the penalty.
+    pub points: u32,
+    pub penalty: u32,
+    /// The points after the penalty, never below zero, or `None` unless the status is `Ok`.
+    pub total: Option<u32>,
+    /// The first punch of each control in the scoring table, in the order they were punched.
+    pub scored: Vec<ScorePunch>,
+    /// Repeated punches and punches of controls not in the scoring table.
+    pub ignored: Vec<Punch>,
+    pub running_time: Option<TimeDelta>,
+    pub overtime: TimeDelta,
+}
+
+impl ScoreCourse {
+    /// Every started minute over `time_limit` costs `penalty_per_minute` points.
+    #[must_use]
+    pub const fn new(time_limit: TimeDelta, penalty_per_minute: u32) -> Self {
+        Self {
+            points: BTreeMap::new(),
+            time_limit,
+            penalty_per_minute,
+        }
+    }
+
+    #[must_use]
+    pub fn control(mut self, code: u16, points: u32) -> Self {
+        self.points.insert(code, points);
+        self
+    }
+
+    #[must_use]
+    pub fn points(&self, code: u16) -> Option<u32> {
+        self.points.get(&code).copied()
+    }
+
+    #[must_use]
+    pub const fn time_limit(&self) -> TimeDelta {
+        self.time_limit
+    }
+
+    #[must_use]
+    pub fn evaluate(&self, readout: &CardReadout) -> ScoreResult {
+        self.evaluate_readout(readout, None)
+    }
+
+    /// Evaluates a readout, using `manual_start` when the card has no start punch.
+    #[must_use]
+    pub fn evaluate_with_start(
+        &self,
+        readout: &CardReadout,
+        manual_start: NaiveTime,
+    ) -> ScoreResult {
+        self.evaluate_readout(readout, Some(manual_start))
+    }
+
+    fn evaluate_readout(
+        &self,
+        readout: &CardReadout,
+        manual_start: Option<NaiveTime>,
+    ) -> ScoreResult {
+        let mut scored: Vec<ScorePunch> = Vec::new();
+        let mut ignored = Vec::new();
+
+        for punch in &readout.punches {
+            match self.points(punch.code) {
+                Some(points) if !scored.iter().any(|scored| scored.punch.code == punch.code) => {
+                    scored.push(ScorePunch {
+                        punch: punch.clone(),
+                        points,
+                    });
+                }
+                _ => ignored.push(punch.clone()),
+            }
+        }
+
+        let points = scored.iter().map(|scored| scored.points).sum();
+        let status = match (start_time(readout, manual_start), finish_time(readout)) {
+            (_, None) => ScoreStatus::DidNotFinish,
+            (None, Some(_)) => ScoreStatus::MissingStart,
+            (Some(_), Some(_)) => ScoreStatus::Ok,
+        };
+        let running_time = running_time(readout, manual_start);
+        let overtime = running_time
+            .map_or(TimeDelta::zero(), |running_time| {
+                running_time - self.time_limit
+            })
+            .max(TimeDelta::zero());
+        let started_minutes = (overtime.num_seconds() + SECONDS_IN_MINUTE - 1) / SECONDS_IN_MINUTE;
+        let penalty = u32::try_from(started_minutes)
+            .unwrap_or(u32::MAX)
+            .saturating_mul(self.penalty_per_minute);
+
+        ScoreResult {
+            status,
+            points,
+            penalty,
+            total: (status == ScoreStatus::Ok).then(|| points.saturating_sub(penalty)),
+            scored,
+            ignored,
+            running_time,
+            overtime,
+        }
+    }
+}
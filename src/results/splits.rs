@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use chrono::TimeDelta;
+
+use crate::results::{elapsed, CourseResult};
+
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub enum SplitControl {
+    Start,
+    Control(u16),
+    Finish,
+}
+
+/// The leg between two consecutive punches of a course.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Split {
+    pub from: SplitControl,
+    pub to: SplitControl,
+    pub leg_time: TimeDelta,
+    /// The time since the start.
+    pub split_time: TimeDelta,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RankedSplit {
+    pub split: Split,
+    /// The rank of the leg time among all competitors who ran the same leg, starting at 1.
+    pub leg_rank: usize,
+    /// The difference to the best leg time.
+    pub time_lost: TimeDelta,
+}
+
+impl CourseResult {
+    /// Splits of the matched punches in the order they were punched, in whole seconds. Legs to
+    /// missing controls are skipped, and there are no splits without a start time.
+    #[must_use]
+    pub fn splits(&self) -> Vec<Split> {
+        let Some(start) = self.start else {
+            return Vec::new();
+        };
+
+        let controls = self
+            .matched
+            .iter()
+            .map(|punch| (SplitControl::Control(punch.code), punch.time))
+            .chain(self.finish.map(|finish| (SplitControl::Finish, finish)));
+
+        let mut splits = Vec::new();
+        let mut previous = (SplitControl::Start, TimeDelta::zero());
+        for (control, time) in controls {
+            let split_time = elapsed(start, time);
+            splits.push(Split {
+                from: previous.0,
+                to: control,
+                leg_time: split_time - previous.1,
+                split_time,
+            });
+            previous = (control, split_time);
+        }
+        splits
+    }
+}
+
+/// Ranks the legs of all competitors of a class, where legs are identified by the controls they
+/// connect. Competitors with equal leg times share a rank.
+#[must_use]
+pub fn rank_legs(class: &[Vec<Split>]) -> Vec<Vec<RankedSplit>> {
+    let mut leg_times = BTreeMap::<_, Vec<TimeDelta>>::new();
+    for split in class.iter().flatten() {
+        leg_times
+            .entry((split.from, split.to))
+            .or_default()
+            .push(split.leg_time);
+    }
+
+    class
+        .iter()
+        .map(|splits| {
+            splits
+                .iter()
+                .map(|split| {
+                    let times = &leg_times[&(split.from, split.to)];
+                    let best = times.iter().min().copied().unwrap_or(split.leg_time);
+                    RankedSplit {
+                        split: split.clone(),
+                        leg_rank: times.iter().filter(|time| **time < split.leg_time).count() + 1,
+                        time_lost: split.leg_time - best,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
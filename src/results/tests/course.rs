@@ -0,0 +1,205 @@
+use std::time::{Duration, Instant};
+
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::tests::readout;
+use crate::results::{Course, CourseControl, CourseStatus};
+use crate::test_support::punch;
+
+fn course() -> Course {
+    Course::new()
+        .control(31)
+        .one_of([32, 42])
+        .unordered([33, 34])
+        .control(35)
+}
+
+#[test]
+fn ok() {
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:30:15"),
+        &[
+            (31, "10:05:00"),
+            (42, "10:10:00"),
+            (34, "10:15:00"),
+            (33, "10:20:00"),
+            (35, "10:25:00"),
+        ],
+    );
+    let result = course().validate(&readout);
+
+    assert_eq!(result.status, CourseStatus::Ok);
+    assert_eq!(result.matched, readout.punches);
+    assert!(result.missing.is_empty());
+    assert!(result.extra.is_empty());
+    assert_eq!(
+        result.running_time,
+        Some(TimeDelta::minutes(30) + TimeDelta::seconds(15))
+    );
+}
+
+#[test]
+fn extra_and_repeated_punches() {
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:30:00"),
+        &[
+            (31, "10:05:00"),
+            (31, "10:05:02"),
+            (50, "10:07:00"),
+            (32, "10:10:00"),
+            (33, "10:15:00"),
+            (34, "10:20:00"),
+            (35, "10:25:00"),
+        ],
+    );
+    let result = course().validate(&readout);
+
+    assert_eq!(result.status, CourseStatus::Ok);
+    assert_eq!(
+        result.extra,
+        vec![punch(31, "10:05:02"), punch(50, "10:07:00")]
+    );
+}
+
+#[test]
+fn mispunch() {
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:30:00"),
+        &[
+            (32, "10:10:00"),
+            (31, "10:12:00"),
+            (33, "10:15:00"),
+            (35, "10:25:00"),
+        ],
+    );
+    let result = course().validate(&readout);
+
+    assert_eq!(result.status, CourseStatus::Mispunch);
+    assert_eq!(
+        result.missing,
+        vec![
+            CourseControl::OneOf(vec![32, 42]),
+            CourseControl::Control(34)
+        ]
+    );
+    assert_eq!(result.missing_after, [1, 2]);
+    assert_eq!(result.extra, vec![punch(32, "10:10:00")]);
+}
+
+#[test]
+fn unordered_one_of_before_control() {
+    let course = Course::new()
+        .unordered([
+            CourseControl::OneOf(vec![31, 32]),
+            CourseControl::Control(31),
+        ])
+        .control(35);
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:30:00"),
+        &[(31, "10:05:00"), (32, "10:10:00"), (35, "10:25:00")],
+    );
+    let result = course.validate(&readout);
+
+    assert_eq!(result.status, CourseStatus::Ok);
+    assert_eq!(result.matched, readout.punches);
+    assert!(result.extra.is_empty());
+}
+
+#[test]
+fn unordered_section_ends_early() {
+    let course = Course::new().unordered([31, 32]).control(31);
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:30:00"),
+        &[(31, "10:05:00"), (32, "10:10:00"), (31, "10:15:00")],
+    );
+    let result = course.validate(&readout);
+
+    assert_eq!(result.status, CourseStatus::Ok);
+    assert_eq!(result.matched, readout.punches);
+}
+
+#[test]
+fn large_unordered_section() {
+    let course = Course::new().unordered(31..95).control(200);
+    // Every control but 94 punched twice, in reverse order.
+    let punches = (0..2)
+        .flat_map(|_| (31..94).rev())
+        .enumerate()
+        .map(|(index, code)| (code, format!("10:{:02}:{:02}", index / 60, index % 60)))
+        .chain([(200, "10:59:00".to_string())])
+        .collect::<Vec<_>>();
+    let punches = punches
+        .iter()
+        .map(|(code, time)| (*code, time.as_str()))
+        .collect::<Vec<_>>();
+    let readout = readout(Some("10:00:00"), Some("11:00:00"), &punches);
+
+    let started = Instant::now();
+    let result = course.validate(&readout);
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(result.status, CourseStatus::Mispunch);
+    assert_eq!(result.missing, [CourseControl::Control(94)]);
+    assert_eq!(result.missing_after, [63]);
+    assert_eq!(result.matched[..63], readout.punches[..63]);
+    assert_eq!(result.matched[63], readout.punches[126]);
+    assert_eq!(result.extra, readout.punches[63..126]);
+}
+
+#[test]
+fn did_not_finish() {
+    let readout = readout(Some("10:00:00"), None, &[(31, "10:05:00")]);
+    let result = course().validate(&readout);
+
+    assert_eq!(result.status, CourseStatus::DidNotFinish);
+    assert_eq!(result.running_time, None);
+}
+
+#[test]
+fn missing_start_and_finish() {
+    let punches = [
+        (31, "10:05:00"),
+        (32, "10:10:00"),
+        (33, "10:15:00"),
+        (34, "10:20:00"),
+        (35, "10:25:00"),
+    ];
+
+    assert_eq!(
+        course()
+            .validate(&readout(None, Some("10:30:00"), &punches))
+            .status,
+        CourseStatus::MissingStart
+    );
+    assert_eq!(
+        course()
+            .validate(&readout(Some("10:00:00"), None, &punches))
+            .status,
+        CourseStatus::MissingFinish
+    );
+}
+
+#[test]
+fn manual_start() {
+    let readout = readout(
+        None,
+        Some("00:20:00"),
+        &[
+            (31, "23:50:00"),
+            (32, "23:55:00"),
+            (33, "00:05:00"),
+            (34, "00:10:00"),
+            (35, "00:15:00"),
+        ],
+    );
+    let result =
+        course().validate_with_start(&readout, NaiveTime::from_hms_opt(23, 45, 0).unwrap());
+
+    assert_eq!(result.status, CourseStatus::Ok);
+    assert_eq!(result.running_time, Some(TimeDelta::minutes(35)));
+}
@@ -0,0 +1,25 @@
+#![allow(clippy::pedantic)]
+
+use crate::test_support::punch;
+use crate::CardReadout;
+use crate::CardType::Si10;
+use crate::StartOrFinishPunch::Normal;
+
+mod course;
+mod relay;
+mod score;
+mod splits;
+
+fn readout(start: Option<&str>, finish: Option<&str>, punches: &[(u16, &str)]) -> CardReadout {
+    CardReadout {
+        card_number: 7_001_234,
+        card_type: Si10,
+        start: start.map(|time| Normal(punch(1, time))),
+        finish: finish.map(|time| Normal(punch(2, time))),
+        check: None,
+        punches: punches
+            .iter()
+            .map(|(code, time)| punch(*code, time))
+            .collect(),
+    }
+}
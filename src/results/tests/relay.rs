@@ -0,0 +1,126 @@
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::tests::readout;
+use crate::results::{Course, Relay, RelayLeg, RelayStatus};
+
+fn time(time: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap()
+}
+
+fn leg(course: Course, finish: Option<&str>, punches: &[(u16, &str)]) -> RelayLeg {
+    RelayLeg {
+        course,
+        readout: Some(readout(None, finish, punches)),
+    }
+}
+
+fn relay() -> Relay {
+    Relay::new(time("10:00:00")).with_mass_start(time("11:00:00"))
+}
+
+#[test]
+fn changeovers() {
+    let legs = [
+        leg(
+            Course::new().control(31),
+            Some("10:30:00"),
+            &[(31, "10:10:00")],
+        ),
+        leg(
+            Course::new().control(32),
+            Some("10:55:00"),
+            &[(32, "10:40:00")],
+        ),
+    ];
+    let result = relay().evaluate(&legs);
+
+    assert_eq!(result.status, RelayStatus::Ok);
+    assert_eq!(result.legs[1].start, Some(time("10:30:00")));
+    assert!(!result.legs[1].mass_start);
+    assert_eq!(result.legs[0].changeover, Some(time("10:30:00")));
+    assert_eq!(result.legs[1].leg_time, Some(TimeDelta::minutes(25)));
+    assert_eq!(result.team_time, Some(TimeDelta::minutes(55)));
+}
+
+#[test]
+fn mass_start() {
+    let legs = [
+        leg(
+            Course::new().control(31),
+            Some("11:10:00"),
+            &[(31, "10:10:00")],
+        ),
+        leg(
+            Course::new().control(32),
+            Some("11:30:00"),
+            &[(32, "11:20:00")],
+        ),
+    ];
+    let result = relay().evaluate(&legs);
+
+    assert_eq!(result.status, RelayStatus::Ok);
+    assert_eq!(result.legs[1].start, Some(time("11:00:00")));
+    assert!(result.legs[1].mass_start);
+    assert_eq!(result.team_time, Some(TimeDelta::minutes(100)));
+}
+
+#[test]
+fn mispunched_leg() {
+    let legs = [
+        leg(
+            Course::new().control(31),
+            Some("10:30:00"),
+            &[(33, "10:10:00")],
+        ),
+        leg(
+            Course::new().control(32),
+            Some("10:55:00"),
+            &[(32, "10:40:00")],
+        ),
+    ];
+
+    assert_eq!(relay().evaluate(&legs).status, RelayStatus::Disqualified);
+}
+
+#[test]
+fn read_out_before_previous_leg() {
+    let legs = [
+        RelayLeg {
+            course: Course::new().control(31),
+            readout: None,
+        },
+        leg(
+            Course::new().control(32),
+            Some("10:55:00"),
+            &[(32, "10:40:00")],
+        ),
+    ];
+    for relay in [Relay::new(time("10:00:00")), relay()] {
+        let result = relay.evaluate(&legs);
+
+        assert_eq!(result.status, RelayStatus::Running);
+        assert_eq!(result.legs[1].start, None);
+        assert!(!result.legs[1].mass_start);
+        assert_eq!(result.legs[1].leg_time, None);
+        assert_eq!(result.legs[1].changeover, Some(time("10:55:00")));
+        assert_eq!(result.team_time, None);
+    }
+}
+
+#[test]
+fn did_not_finish_before_mass_start() {
+    let legs = [
+        leg(Course::new().control(31), None, &[(31, "10:10:00")]),
+        leg(
+            Course::new().control(32),
+            Some("11:30:00"),
+            &[(32, "11:20:00")],
+        ),
+    ];
+    let result = relay().evaluate(&legs);
+
+    assert_eq!(result.status, RelayStatus::DidNotFinish);
+    assert_eq!(result.legs[1].start, Some(time("11:00:00")));
+    assert!(result.legs[1].mass_start);
+    assert_eq!(result.legs[1].leg_time, Some(TimeDelta::minutes(30)));
+}
@@ -0,0 +1,99 @@
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::tests::readout;
+use crate::results::{ScoreCourse, ScorePunch, ScoreStatus};
+use crate::test_support::punch;
+
+fn score_course() -> ScoreCourse {
+    ScoreCourse::new(TimeDelta::minutes(60), 2)
+        .control(31, 10)
+        .control(32, 20)
+        .control(33, 30)
+}
+
+#[test]
+fn within_time_limit() {
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:59:59"),
+        &[
+            (32, "10:10:00"),
+            (50, "10:15:00"),
+            (31, "10:20:00"),
+            (32, "10:30:00"),
+        ],
+    );
+    let result = score_course().evaluate(&readout);
+
+    assert_eq!(result.points, 30);
+    assert_eq!(result.penalty, 0);
+    assert_eq!(result.status, ScoreStatus::Ok);
+    assert_eq!(result.total, Some(30));
+    assert_eq!(
+        result.scored,
+        vec![
+            ScorePunch {
+                punch: punch(32, "10:10:00"),
+                points: 20
+            },
+            ScorePunch {
+                punch: punch(31, "10:20:00"),
+                points: 10
+            },
+        ]
+    );
+    assert_eq!(
+        result.ignored,
+        vec![punch(50, "10:15:00"), punch(32, "10:30:00")]
+    );
+    assert_eq!(result.overtime, TimeDelta::zero());
+}
+
+#[test]
+fn overtime() {
+    let readout = readout(
+        Some("10:00:00"),
+        Some("11:02:01"),
+        &[(31, "10:10:00"), (33, "10:20:00")],
+    );
+    let result = score_course().evaluate(&readout);
+
+    assert_eq!(result.points, 40);
+    assert_eq!(result.overtime, TimeDelta::seconds(121));
+    assert_eq!(result.penalty, 6);
+    assert_eq!(result.total, Some(34));
+}
+
+#[test]
+fn penalty_exceeds_points() {
+    let readout = readout(None, Some("11:30:00"), &[(31, "10:10:00")]);
+    let result =
+        score_course().evaluate_with_start(&readout, NaiveTime::from_hms_opt(10, 0, 0).unwrap());
+
+    assert_eq!(result.penalty, 60);
+    assert_eq!(result.total, Some(0));
+}
+
+#[test]
+fn no_finish() {
+    let readout = readout(
+        Some("10:00:00"),
+        None,
+        &[(31, "10:10:00"), (33, "12:20:00")],
+    );
+    let result = score_course().evaluate(&readout);
+
+    assert_eq!(result.status, ScoreStatus::DidNotFinish);
+    assert_eq!(result.points, 40);
+    assert_eq!(result.running_time, None);
+    assert_eq!(result.total, None);
+}
+
+#[test]
+fn no_start() {
+    let readout = readout(None, Some("10:50:00"), &[(31, "10:10:00")]);
+    let result = score_course().evaluate(&readout);
+
+    assert_eq!(result.status, ScoreStatus::MissingStart);
+    assert_eq!(result.total, None);
+}
@@ -0,0 +1,116 @@
+use chrono::{NaiveTime, TimeDelta};
+
+use crate::results::tests::readout;
+use crate::results::{rank_legs, Course, Split, SplitControl};
+use crate::test_support::punch;
+use crate::StartOrFinishPunch::SubSecond;
+use crate::SubSecondPunch;
+
+fn course() -> Course {
+    Course::new().control(31).control(32)
+}
+
+fn split(from: SplitControl, to: SplitControl, leg_time: i64, split_time: i64) -> Split {
+    Split {
+        from,
+        to,
+        leg_time: TimeDelta::seconds(leg_time),
+        split_time: TimeDelta::seconds(split_time),
+    }
+}
+
+#[test]
+fn splits() {
+    let readout = readout(
+        Some("10:00:00"),
+        Some("10:12:30"),
+        &[(31, "10:04:10"), (32, "10:10:00")],
+    );
+
+    assert_eq!(
+        course().validate(&readout).splits(),
+        vec![
+            split(SplitControl::Start, SplitControl::Control(31), 250, 250),
+            split(
+                SplitControl::Control(31),
+                SplitControl::Control(32),
+                350,
+                600
+            ),
+            split(SplitControl::Control(32), SplitControl::Finish, 150, 750),
+        ]
+    );
+}
+
+#[test]
+fn sub_second_start_and_finish() {
+    let normal = readout(
+        Some("10:00:00"),
+        Some("10:12:30"),
+        &[(31, "10:04:10"), (32, "10:10:00")],
+    );
+    let mut sub_second = normal.clone();
+    let sub_second_punch = |time: &str| {
+        let punch = punch(1, time);
+        SubSecond(SubSecondPunch {
+            time: NaiveTime::parse_from_str(time, "%H:%M:%S%.f").unwrap(),
+            day_of_week: punch.day_of_week,
+            week_counter: punch.week_counter,
+        })
+    };
+    sub_second.start = Some(sub_second_punch("10:00:00.75"));
+    sub_second.finish = Some(sub_second_punch("10:12:30.25"));
+
+    let normal = course().validate(&normal);
+    let sub_second = course().validate(&sub_second);
+
+    assert_eq!(sub_second.splits(), normal.splits());
+    assert_eq!(sub_second.running_time, normal.running_time);
+}
+
+#[test]
+fn missing_control() {
+    let readout = readout(Some("10:00:00"), Some("10:12:30"), &[(32, "10:10:00")]);
+
+    assert_eq!(
+        course().validate(&readout).splits(),
+        vec![
+            split(SplitControl::Start, SplitControl::Control(32), 600, 600),
+            split(SplitControl::Control(32), SplitControl::Finish, 150, 750),
+        ]
+    );
+}
+
+#[test]
+fn leg_ranking() {
+    let class = [
+        ("10:00:00", "10:12:30", [(31, "10:04:10"), (32, "10:10:00")]),
+        ("11:00:00", "11:11:00", [(31, "11:03:00"), (32, "11:09:00")]),
+        ("12:00:00", "12:12:00", [(31, "12:03:00"), (32, "12:10:30")]),
+    ]
+    .map(|(start, finish, punches)| {
+        course()
+            .validate(&readout(Some(start), Some(finish), &punches))
+            .splits()
+    });
+
+    let ranked = rank_legs(&class);
+
+    let ranks = ranked
+        .iter()
+        .map(|splits| {
+            splits
+                .iter()
+                .map(|split| (split.leg_rank, split.time_lost.num_seconds()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranks,
+        vec![
+            vec![(3, 70), (1, 0), (3, 60)],
+            vec![(1, 0), (2, 10), (2, 30)],
+            vec![(1, 0), (3, 100), (1, 0)],
+        ]
+    );
+}
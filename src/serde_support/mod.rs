@@ -0,0 +1,72 @@
+//! Helpers for the `serde` representation of types which have none in their own crates.
+
+#[cfg(test)]
+mod tests;
+
+/// Durations as ISO 8601 strings in seconds, e.g. `PT3600S` or `PT1.25S`.
+pub mod duration {
+    use chrono::TimeDelta;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const NANOSECONDS_IN_SECOND: i64 = 1_000_000_000;
+
+    pub fn serialize<S: Serializer>(
+        duration: &TimeDelta,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let sign = if *duration < TimeDelta::zero() {
+            "-"
+        } else {
+            ""
+        };
+        let duration = duration.abs();
+        let seconds = duration.num_seconds();
+        let nanoseconds = duration.subsec_nanos();
+
+        let text = if nanoseconds == 0 {
+            format!("{sign}PT{seconds}S")
+        } else {
+            let fraction = format!("{nanoseconds:09}");
+            format!("{sign}PT{seconds}.{}S", fraction.trim_end_matches('0'))
+        };
+        serializer.serialize_str(&text)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TimeDelta, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse(&text).ok_or_else(|| D::Error::custom(format!("invalid duration {text}")))
+    }
+
+    /// Parses `[-]PT[nH][nM][n[.f]S]`, the subset of ISO 8601 durations without calendar units.
+    pub fn parse(text: &str) -> Option<TimeDelta> {
+        let (negative, text) = text
+            .strip_prefix('-')
+            .map_or((false, text), |text| (true, text));
+        let mut rest = text.strip_prefix("PT")?;
+        if rest.is_empty() {
+            return None;
+        }
+
+        let mut duration = TimeDelta::zero();
+        for (unit, seconds) in [('H', 3600), ('M', 60)] {
+            if let Some((value, tail)) = rest.split_once(unit) {
+                duration += TimeDelta::seconds(value.parse::<i64>().ok()?.checked_mul(seconds)?);
+                rest = tail;
+            }
+        }
+        if let Some(value) = rest.strip_suffix('S') {
+            let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+            if fraction.len() > 9 || !fraction.bytes().all(|digit| digit.is_ascii_digit()) {
+                return None;
+            }
+            let nanoseconds = format!("{fraction:0<9}").parse::<i64>().ok()?;
+            duration += TimeDelta::seconds(whole.parse().ok()?)
+                + TimeDelta::nanoseconds(nanoseconds % NANOSECONDS_IN_SECOND);
+        } else if !rest.is_empty() {
+            return None;
+        }
+
+        Some(if negative { -duration } else { duration })
+    }
+}
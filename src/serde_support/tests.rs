@@ -0,0 +1,143 @@
+#![allow(clippy::pedantic)]
+
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use serde_json::json;
+
+use crate::serde_support::duration;
+use crate::test_support::punch;
+use crate::CardType::Si10;
+use crate::DayOfWeek::Saturday;
+use crate::StartOrFinishPunch::{Normal, SubSecond};
+use crate::WeekCounter::{First, Second};
+use crate::{
+    CardPunch, CardReadout, Model, ProtocolConfiguration, PunchFeedback, PunchSource,
+    SI6CardBlocks, SRRChannel, SRRConfiguration, StationMode, StationProgram, SubSecondPunch,
+    SystemConfiguration,
+};
+
+#[test]
+fn readout() {
+    let readout = CardReadout {
+        card_number: 7_001_234,
+        card_type: Si10,
+        start: Some(SubSecond(SubSecondPunch {
+            time: NaiveTime::from_hms_milli_opt(10, 0, 0, 250).unwrap(),
+            day_of_week: Saturday,
+            week_counter: Second,
+        })),
+        finish: Some(Normal(punch(2, "10:12:30"))),
+        check: None,
+        punches: vec![punch(31, "10:04:10")],
+    };
+    let expected = json!({
+        "card_number": 7001234,
+        "card_type": "Si10",
+        "start": {"SubSecond": {"time": "10:00:00.250", "day_of_week": "Saturday", "week_counter": "Second"}},
+        "finish": {"Normal": {"time": "10:12:30", "day_of_week": "Saturday", "week_counter": "First", "code": 2}},
+        "check": null,
+        "punches": [{"time": "10:04:10", "day_of_week": "Saturday", "week_counter": "First", "code": 31}],
+    });
+
+    assert_eq!(serde_json::to_value(&readout).unwrap(), expected);
+    assert_eq!(
+        serde_json::from_value::<CardReadout>(expected).unwrap(),
+        readout
+    );
+}
+
+#[test]
+fn card_punch() {
+    let card_punch = CardPunch {
+        punch: SubSecondPunch {
+            time: NaiveTime::from_hms_milli_opt(10, 4, 10, 500).unwrap(),
+            day_of_week: Saturday,
+            week_counter: First,
+        },
+        card: crate::Card::new(7_001_234).unwrap(),
+        station_code: 31,
+        source: PunchSource::Radio(SRRChannel::Blue),
+        backup_address: Some(0x1f8),
+    };
+    let value = serde_json::to_value(&card_punch).unwrap();
+
+    assert_eq!(value["source"], json!({"Radio": "Blue"}));
+    assert_eq!(
+        value["card"],
+        json!({"card_type": "Si10", "number": 7001234})
+    );
+    assert_eq!(
+        serde_json::from_value::<CardPunch>(value).unwrap(),
+        card_punch
+    );
+}
+
+#[test]
+fn system_configuration() {
+    let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    let configuration = SystemConfiguration {
+        serial_number: 501_234,
+        srr_configuration: SRRConfiguration::RED_CHANNEL,
+        firmware: *b"656",
+        build_date: date,
+        model: Model::BSF8V2,
+        mem_kilobytes: 128,
+        battery_date: date,
+        battery_capacity_milliampere_hour: 1000,
+        backup_pointer_high: 0,
+        backup_pointer_low: 0x1f8,
+        si6_card_blocks: SI6CardBlocks::all(),
+        srr_channel: SRRChannel::Red,
+        used_battery_capacity_percentage: 12.5,
+        memory_overflow: false,
+        battery_voltage: 3.5,
+        station_program: StationProgram::Competition,
+        mode: StationMode::Control,
+        station_code: 31,
+        punch_feedback: PunchFeedback::OPTICAL | PunchFeedback::AUDIBLE,
+        protocol_configuration: ProtocolConfiguration::EXTENDED_PROTOCOL
+            | ProtocolConfiguration::AUTO_SEND_OUT,
+        wakeup_date: date,
+        active_duration: TimeDelta::hours(2),
+    };
+    let value = serde_json::to_value(&configuration).unwrap();
+
+    assert_eq!(value["model"], "BSF8V2");
+    assert_eq!(value["mode"], "Control");
+    assert_eq!(value["build_date"], "2024-06-01");
+    assert_eq!(value["punch_feedback"], "OPTICAL | AUDIBLE");
+    assert_eq!(
+        value["protocol_configuration"],
+        "EXTENDED_PROTOCOL | AUTO_SEND_OUT"
+    );
+    assert_eq!(value["active_duration"], "PT7200S");
+
+    let deserialized = serde_json::from_value::<SystemConfiguration>(value).unwrap();
+    assert_eq!(deserialized.active_duration, TimeDelta::hours(2));
+    assert_eq!(
+        deserialized.protocol_configuration.bits(),
+        configuration.protocol_configuration.bits()
+    );
+}
+
+#[test]
+fn durations() {
+    assert_eq!(duration::parse("PT7200S"), Some(TimeDelta::hours(2)));
+    assert_eq!(
+        duration::parse("PT1H2M3.25S"),
+        Some(TimeDelta::seconds(3723) + TimeDelta::milliseconds(250))
+    );
+    assert_eq!(duration::parse("-PT5S"), Some(TimeDelta::seconds(-5)));
+    assert_eq!(duration::parse("PT"), None);
+    assert_eq!(duration::parse("P1D"), None);
+    assert_eq!(duration::parse("PT1.2.3S"), None);
+}
+
+#[test]
+fn invalid_card() {
+    for card in [
+        json!({"card_type": "Si10", "number": 3_000_000}),
+        json!({"card_type": "Si8", "number": 7_001_234}),
+    ] {
+        assert!(serde_json::from_value::<crate::Card>(card).is_err());
+    }
+}
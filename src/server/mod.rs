@@ -0,0 +1,329 @@
+//! A local HTTP and WebSocket API over a [`Reader`], for browser-based results desks.
+//!
+//! - `GET /station`: the system configuration of the station.
+//! - `GET /readouts?limit=N`: the last `N` readouts (all kept readouts by default), oldest first.
+//! - `GET /readouts/{card_number}`: the last readout of a card.
+//! - `GET /events`: a WebSocket pushing every [`LiveEvent`] as JSON, and accepting
+//!   `{"command": "beep"}` or `{"command": "refresh_station"}`.
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::{
+    Card, CardImage, CardOwnerData, CardPunch, CardReadout, Error, FromCardBlocks, Reader, Readout,
+    StationMode, SystemConfiguration,
+};
+
+#[cfg(all(test, feature = "simulator"))]
+mod tests;
+
+const DEFAULT_HISTORY: usize = 1000;
+const EVENT_CAPACITY: usize = 256;
+
+/// Serves the API of one reader.
+pub struct Server {
+    reader: Reader,
+    history: usize,
+}
+
+/// An event pushed to every WebSocket client.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    Readout {
+        readout: Readout<CardReadout>,
+    },
+    /// Sent after the readout of a card which holds owner data.
+    OwnerData {
+        card: Card,
+        owner_data: CardOwnerData,
+    },
+    Punch {
+        punch: CardPunch,
+    },
+    /// The result of a command, sent only to the client which sent it.
+    CommandResult {
+        command: LiveCommand,
+        error: Option<String>,
+    },
+    /// A message which is not a command, sent only to the client which sent it.
+    InvalidCommand {
+        error: String,
+    },
+    /// The reader failed, no more readouts or punches follow.
+    ReaderStopped {
+        error: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveCommand {
+    /// Beep and wait until the card is removed.
+    Beep,
+    /// Read the system configuration of the station again.
+    RefreshStation,
+}
+
+#[derive(Deserialize)]
+struct CommandRequest {
+    command: LiveCommand,
+}
+
+#[derive(Deserialize)]
+struct ReadoutsQuery {
+    limit: Option<usize>,
+}
+
+type CommandReply = oneshot::Sender<Result<(), String>>;
+
+struct ServerState {
+    station: RwLock<SystemConfiguration>,
+    readouts: Mutex<VecDeque<Readout<CardReadout>>>,
+    history: usize,
+    events: broadcast::Sender<LiveEvent>,
+    commands: mpsc::Sender<(LiveCommand, CommandReply)>,
+}
+
+impl Server {
+    #[must_use]
+    pub const fn new(reader: Reader) -> Self {
+        Self {
+            reader,
+            history: DEFAULT_HISTORY,
+        }
+    }
+
+    /// Keep the last `readouts` readouts for the REST endpoints, 1000 by default.
+    #[must_use]
+    pub const fn with_history(mut self, readouts: usize) -> Self {
+        self.history = readouts;
+        self
+    }
+
+    /// Polls the reader and serves the API on `listener` until the listener fails.
+    pub async fn serve(self, listener: TcpListener) -> crate::Result<()> {
+        let (commands, command_receiver) = mpsc::channel(16);
+        let state = Arc::new(ServerState {
+            station: RwLock::new(self.reader.system_configuration().clone()),
+            readouts: Mutex::new(VecDeque::new()),
+            history: self.history,
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            commands,
+        });
+        tokio::spawn(run_reader(self.reader, state.clone(), command_receiver));
+
+        let router = Router::new()
+            .route("/station", get(station))
+            .route("/readouts", get(readouts))
+            .route("/readouts/{card_number}", get(readout))
+            .route("/events", get(events))
+            .with_state(state);
+        axum::serve(listener, router).await?;
+
+        Ok(())
+    }
+}
+
+impl ServerState {
+    fn publish(&self, event: LiveEvent) {
+        if let LiveEvent::Readout { readout } = &event {
+            let mut readouts = self.readouts.lock().unwrap_or_else(PoisonError::into_inner);
+            readouts.push_back(readout.clone());
+            while readouts.len() > self.history {
+                readouts.pop_front();
+            }
+            drop(readouts);
+        }
+        let _ = self.events.send(event);
+    }
+}
+
+async fn run_reader(
+    mut reader: Reader,
+    state: Arc<ServerState>,
+    mut commands: mpsc::Receiver<(LiveCommand, CommandReply)>,
+) {
+    loop {
+        // Polling is not cancel safe, so commands are only accepted until a card is inserted or
+        // a punch arrives.
+        let received = tokio::select! {
+            Some((command, reply)) = commands.recv() => {
+                let result = execute(&mut reader, command, &state).await;
+                let _ = reply.send(result.map_err(|error| error.to_string()));
+                continue;
+            }
+            received = reader.wait_for_frame() => received,
+        };
+        let result = match received {
+            Ok(()) => poll(&mut reader).await,
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(events) => events.into_iter().for_each(|event| state.publish(event)),
+            Err(Error::CardRemovedWhileReadingData) => {}
+            Err(error) => {
+                state.publish(LiveEvent::ReaderStopped {
+                    error: error.to_string(),
+                });
+                return;
+            }
+        }
+    }
+}
+
+/// Waits for the next readout or punch, depending on the mode of the station.
+async fn poll(reader: &mut Reader) -> crate::Result<Vec<LiveEvent>> {
+    if reader.system_configuration().mode != StationMode::Readout {
+        return Ok(vec![LiveEvent::Punch {
+            punch: reader.poll_punch().await?,
+        }]);
+    }
+
+    let Readout {
+        station_code,
+        card,
+        data: mut image,
+    } = reader.poll_readout::<CardImage>().await?;
+    let data = CardReadout::from_card_blocks(&mut image, card.card_type).await?;
+    let owner_data = CardOwnerData::from_card_blocks(&mut image, card.card_type).await;
+
+    let mut events = vec![LiveEvent::Readout {
+        readout: Readout {
+            station_code,
+            card: card.clone(),
+            data,
+        },
+    }];
+    if let Ok(owner_data) = owner_data {
+        events.push(LiveEvent::OwnerData { card, owner_data });
+    }
+    Ok(events)
+}
+
+async fn execute(
+    reader: &mut Reader,
+    command: LiveCommand,
+    state: &ServerState,
+) -> crate::Result<()> {
+    match command {
+        LiveCommand::Beep => reader.beep_until_card_removed().await,
+        LiveCommand::RefreshStation => {
+            let configuration = reader.refresh_system_configuration().await?.clone();
+            *state
+                .station
+                .write()
+                .unwrap_or_else(PoisonError::into_inner) = configuration;
+            Ok(())
+        }
+    }
+}
+
+async fn station(State(state): State<Arc<ServerState>>) -> Json<SystemConfiguration> {
+    Json(
+        state
+            .station
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone(),
+    )
+}
+
+async fn readouts(
+    State(state): State<Arc<ServerState>>,
+    Query(query): Query<ReadoutsQuery>,
+) -> Json<Vec<Readout<CardReadout>>> {
+    let readouts = state
+        .readouts
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let skip = query
+        .limit
+        .map_or(0, |limit| readouts.len().saturating_sub(limit));
+    Json(readouts.iter().skip(skip).cloned().collect())
+}
+
+async fn readout(
+    State(state): State<Arc<ServerState>>,
+    Path(card_number): Path<u32>,
+) -> Result<Json<Readout<CardReadout>>, StatusCode> {
+    state
+        .readouts
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .rev()
+        .find(|readout| readout.card.number == card_number)
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn events(websocket: WebSocketUpgrade, State(state): State<Arc<ServerState>>) -> Response {
+    // Subscribe before the upgrade, so no event is missed once the client is connected.
+    let events = state.events.subscribe();
+    websocket.on_upgrade(move |socket| forward_events(socket, state, events))
+}
+
+async fn forward_events(
+    mut socket: WebSocket,
+    state: Arc<ServerState>,
+    mut events: broadcast::Receiver<LiveEvent>,
+) {
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => run_command(&state, &text).await,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        let Ok(json) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if socket.send(Message::Text(json.into())).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn run_command(state: &ServerState, text: &str) -> LiveEvent {
+    let request = match serde_json::from_str::<CommandRequest>(text) {
+        Ok(request) => request,
+        Err(error) => {
+            return LiveEvent::InvalidCommand {
+                error: error.to_string(),
+            }
+        }
+    };
+
+    let (reply, result) = oneshot::channel();
+    let error = match state.commands.send((request.command, reply)).await {
+        Ok(()) => result
+            .await
+            .unwrap_or_else(|_| Err("The reader stopped".to_string()))
+            .err(),
+        Err(_) => Some("The reader stopped".to_string()),
+    };
+    LiveEvent::CommandResult {
+        command: request.command,
+        error,
+    }
+}
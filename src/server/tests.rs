@@ -0,0 +1,230 @@
+#![allow(clippy::pedantic)]
+
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::server::{LiveCommand, LiveEvent, Server};
+use crate::simulator::tests::{si8_image, si8_readout};
+use crate::simulator::SimulatedStation;
+use crate::{Card, CardPunch, PunchSource, Readout, StationMode, SubSecondPunch};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn start(mode: StationMode) -> (SimulatedStation, String) {
+    let (station, reader) = SimulatedStation::connect(mode, 10).await.unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    tokio::spawn(Server::new(reader).with_history(2).serve(listener));
+    (station, address)
+}
+
+/// Sends a GET request, returning the status code and the body.
+async fn get(address: &str, path: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+async fn connect(address: &str) -> Socket {
+    connect_async(format!("ws://{address}/events"))
+        .await
+        .unwrap()
+        .0
+}
+
+async fn next_event(socket: &mut Socket) -> LiveEvent {
+    loop {
+        if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+            return serde_json::from_str(text.as_str()).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn station() {
+    let (_station, address) = start(StationMode::Readout).await;
+
+    let (status, body) = get(&address, "/station").await;
+
+    assert_eq!(status, 200);
+    let station = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    assert_eq!(station["station_code"], 10);
+    assert_eq!(station["mode"], "Readout");
+}
+
+#[tokio::test]
+async fn live_readouts() {
+    let (station, address) = start(StationMode::Readout).await;
+    let mut socket = connect(&address).await;
+    let expected = si8_readout().await;
+    let card = Card::new(expected.card_number).unwrap();
+
+    station.insert_card(expected.card_number, si8_image());
+
+    let expected_readout = Readout {
+        station_code: 10,
+        card: card.clone(),
+        data: expected.clone(),
+    };
+    assert_eq!(
+        next_event(&mut socket).await,
+        LiveEvent::Readout {
+            readout: expected_readout.clone()
+        }
+    );
+    let LiveEvent::OwnerData {
+        card: owner_card,
+        owner_data,
+    } = next_event(&mut socket).await
+    else {
+        panic!("expected owner data");
+    };
+    assert_eq!(owner_card, card);
+    assert_eq!(owner_data.first_name, "Dafna");
+
+    let (status, body) = get(&address, &format!("/readouts/{}", card.number)).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        serde_json::from_str::<Readout<crate::CardReadout>>(&body).unwrap(),
+        expected_readout
+    );
+    assert_eq!(get(&address, "/readouts/7001234").await.0, 404);
+
+    for _ in 0..2 {
+        station.insert_card(expected.card_number, si8_image());
+        next_event(&mut socket).await;
+        next_event(&mut socket).await;
+    }
+    let readouts = |body: String| {
+        serde_json::from_str::<Vec<Readout<crate::CardReadout>>>(&body)
+            .unwrap()
+            .len()
+    };
+    assert_eq!(readouts(get(&address, "/readouts").await.1), 2);
+    assert_eq!(readouts(get(&address, "/readouts?limit=1").await.1), 1);
+}
+
+#[tokio::test]
+async fn live_punches() {
+    let (station, address) = start(StationMode::Control).await;
+    let mut socket = connect(&address).await;
+    let punch = CardPunch {
+        punch: SubSecondPunch {
+            time: chrono::NaiveTime::from_hms_opt(10, 4, 10).unwrap(),
+            day_of_week: crate::DayOfWeek::Saturday,
+            week_counter: crate::WeekCounter::First,
+        },
+        card: Card::new(7_001_234).unwrap(),
+        station_code: 10,
+        source: PunchSource::Direct,
+        backup_address: None,
+    };
+
+    station.punch(punch.clone());
+
+    assert_eq!(next_event(&mut socket).await, LiveEvent::Punch { punch });
+}
+
+#[tokio::test]
+async fn commands() {
+    let (station, address) = start(StationMode::Readout).await;
+    let mut socket = connect(&address).await;
+
+    socket
+        .send(Message::text(r#"{"command": "beep"}"#))
+        .await
+        .unwrap();
+    assert_eq!(
+        next_event(&mut socket).await,
+        LiveEvent::CommandResult {
+            command: LiveCommand::Beep,
+            error: None
+        }
+    );
+    assert_eq!(station.beeps(), 1);
+
+    socket
+        .send(Message::text(r#"{"command": "refresh_station"}"#))
+        .await
+        .unwrap();
+    assert_eq!(
+        next_event(&mut socket).await,
+        LiveEvent::CommandResult {
+            command: LiveCommand::RefreshStation,
+            error: None
+        }
+    );
+
+    socket
+        .send(Message::text(r#"{"command": "format"}"#))
+        .await
+        .unwrap();
+    assert!(matches!(
+        next_event(&mut socket).await,
+        LiveEvent::InvalidCommand { .. }
+    ));
+}
+
+#[tokio::test]
+async fn command_during_readout() {
+    let (station, address) = start(StationMode::Readout).await;
+    let mut socket = connect(&address).await;
+    let expected = si8_readout().await;
+
+    station.insert_card(expected.card_number, si8_image());
+    socket
+        .send(Message::text(r#"{"command": "refresh_station"}"#))
+        .await
+        .unwrap();
+
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.push(next_event(&mut socket).await);
+    }
+    assert!(events.contains(&LiveEvent::CommandResult {
+        command: LiveCommand::RefreshStation,
+        error: None
+    }));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        LiveEvent::Readout { readout } if readout.data == expected
+    )));
+}
+
+#[tokio::test]
+async fn command_after_card_removed() {
+    let (station, address) = start(StationMode::Readout).await;
+    let mut socket = connect(&address).await;
+    let expected = si8_readout().await;
+
+    station.insert_card(expected.card_number, si8_image());
+    next_event(&mut socket).await;
+    next_event(&mut socket).await;
+    station.remove_card();
+    socket
+        .send(Message::text(r#"{"command": "refresh_station"}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        timeout(Duration::from_secs(3), next_event(&mut socket))
+            .await
+            .unwrap(),
+        LiveEvent::CommandResult {
+            command: LiveCommand::RefreshStation,
+            error: None
+        }
+    );
+}
@@ -0,0 +1,378 @@
+//! A station in memory, for trying out and testing code built on [`Reader`] without hardware.
+//!
+//! The simulated station answers the commands the reader sends (master/slave, system
+//! configuration, system values, backup memory, card data and beep) and sends card insertions
+//! and punches on request. Other commands are ignored. In slave mode, system configuration,
+//! system value and backup memory commands reach the station placed on its coupling coil with
+//! [`SimulatedStation::couple`].
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::backup::RECORD_SIZE;
+use crate::protocol::{
+    crc, SetMasterSlave, BACKUP_POINTER_HIGH_OFFSET, BACKUP_POINTER_LOW_OFFSET, BACKUP_RECORD_SIZE,
+    END, MEMORY_OVERFLOW_OFFSET, PROTOCOL_CONFIGURATION_OFFSET, START, STATION_CODE_OFFSET,
+    STATION_MODE_OFFSET, WAKEUP,
+};
+use crate::{
+    CardImage, CardPunch, Model, ProtocolConfiguration, Reader, StationMode, BACKUP_MEMORY_START,
+    BLOCK_SIZE,
+};
+
+#[cfg(test)]
+pub(crate) mod tests;
+
+const SYSTEM_MEMORY_SIZE: usize = 128;
+const MEMORY_KILOBYTES: u8 = 128;
+const DUPLEX_BUFFER_SIZE: usize = 4096;
+
+/// A handle to a simulated station, which runs until the handle and the reader are dropped.
+#[derive(Debug, Clone)]
+pub struct SimulatedStation {
+    actions: UnboundedSender<Action>,
+    beeps: Arc<AtomicUsize>,
+    memory: Arc<Mutex<Memory>>,
+    coupled: Arc<Mutex<Option<Arc<Mutex<Memory>>>>>,
+}
+
+#[derive(Debug)]
+enum Action {
+    InsertCard(u32, CardImage),
+    RemoveCard,
+    Punch(CardPunch),
+}
+
+/// The system and backup memory of a station.
+#[derive(Debug)]
+struct Memory {
+    system: [u8; SYSTEM_MEMORY_SIZE],
+    backup: Vec<u8>,
+}
+
+struct Station {
+    memory: Arc<Mutex<Memory>>,
+    coupled: Arc<Mutex<Option<Arc<Mutex<Memory>>>>>,
+    slave: bool,
+    card: Option<(u32, CardImage)>,
+    beeps: Arc<AtomicUsize>,
+}
+
+impl SimulatedStation {
+    /// Starts a BSM7 station in extended protocol with auto send, returning the stream to
+    /// pass to [`Reader::from_transport`].
+    #[must_use]
+    pub fn spawn(mode: StationMode, station_code: u16) -> (Self, DuplexStream) {
+        let (actions, receiver) = mpsc::unbounded_channel();
+        let beeps = Arc::new(AtomicUsize::new(0));
+        let memory = Arc::new(Mutex::new(Memory {
+            system: system_memory(mode, station_code),
+            backup: vec![0xff; usize::from(MEMORY_KILOBYTES) * 1024],
+        }));
+        let coupled = Arc::new(Mutex::new(None));
+        let (transport, stream) = tokio::io::duplex(DUPLEX_BUFFER_SIZE);
+
+        let station = Station {
+            memory: memory.clone(),
+            coupled: coupled.clone(),
+            slave: false,
+            card: None,
+            beeps: beeps.clone(),
+        };
+        tokio::spawn(station.run(stream, receiver));
+
+        (
+            Self {
+                actions,
+                beeps,
+                memory,
+                coupled,
+            },
+            transport,
+        )
+    }
+
+    /// Starts a station and connects a reader to it.
+    pub async fn connect(mode: StationMode, station_code: u16) -> crate::Result<(Self, Reader)> {
+        let (station, transport) = Self::spawn(mode, station_code);
+        let reader = Reader::from_transport(transport).await?;
+
+        Ok((station, reader))
+    }
+
+    /// Inserts a card, whose blocks are read from `image`.
+    pub fn insert_card(&self, card_number: u32, image: CardImage) {
+        let _ = self.actions.send(Action::InsertCard(card_number, image));
+    }
+
+    pub fn remove_card(&self) {
+        let _ = self.actions.send(Action::RemoveCard);
+    }
+
+    /// Sends a punch, as a station in auto send mode or an SRR dongle does.
+    pub fn punch(&self, punch: CardPunch) {
+        let _ = self.actions.send(Action::Punch(punch));
+    }
+
+    /// Places `remote` on the coupling coil, so commands sent in slave mode reach it.
+    pub fn couple(&self, remote: &Self) {
+        *lock(&self.coupled) = Some(remote.memory.clone());
+    }
+
+    /// Takes the station off the coupling coil.
+    pub fn decouple(&self) {
+        *lock(&self.coupled) = None;
+    }
+
+    /// Stores a punch in the backup memory at the backup pointer without sending it, as for
+    /// a punch that never reached the reader.
+    pub fn store_punch(&self, punch: &CardPunch) {
+        let (_, data) = punch.encode();
+        let mut memory = lock(&self.memory);
+        let address = memory.backup_pointer();
+        let start = address as usize;
+        memory.backup[start..start + BACKUP_RECORD_SIZE]
+            .copy_from_slice(&data[..BACKUP_RECORD_SIZE]);
+
+        let mut next_address = address + u32::from(RECORD_SIZE);
+        if next_address as usize >= memory.backup.len() {
+            next_address = BACKUP_MEMORY_START;
+            memory.system[usize::from(MEMORY_OVERFLOW_OFFSET)] = 1;
+        }
+        memory.set_backup_pointer(next_address);
+    }
+
+    /// The number of beep commands received.
+    #[must_use]
+    pub fn beeps(&self) -> usize {
+        self.beeps.load(Ordering::SeqCst)
+    }
+}
+
+impl Memory {
+    fn backup_pointer(&self) -> u32 {
+        let high = usize::from(BACKUP_POINTER_HIGH_OFFSET);
+        let low = usize::from(BACKUP_POINTER_LOW_OFFSET);
+        u32::from_be_bytes([
+            self.system[high],
+            self.system[high + 1],
+            self.system[low],
+            self.system[low + 1],
+        ])
+    }
+
+    fn set_backup_pointer(&mut self, address: u32) {
+        let [high_1, high_0, low_1, low_0] = address.to_be_bytes();
+        let high = usize::from(BACKUP_POINTER_HIGH_OFFSET);
+        let low = usize::from(BACKUP_POINTER_LOW_OFFSET);
+        self.system[high..high + 2].copy_from_slice(&[high_1, high_0]);
+        self.system[low..low + 2].copy_from_slice(&[low_1, low_0]);
+    }
+
+    /// Answers the system configuration, system value and backup memory commands.
+    fn handle_command(&mut self, command: u8, parameters: &[u8]) -> Option<Vec<u8>> {
+        let station = self.station_bytes();
+        match (command, parameters) {
+            (0x83, [address, length, ..]) => {
+                let start = usize::from(*address).min(SYSTEM_MEMORY_SIZE);
+                let end = (start + usize::from(*length)).min(SYSTEM_MEMORY_SIZE);
+                let mut data = vec![*address];
+                data.extend_from_slice(&self.system[start..end]);
+                Some(frame(0x83, station, &data))
+            }
+            (0x82, [offset, values @ ..]) => {
+                for (index, value) in values.iter().enumerate() {
+                    if let Some(byte) = self.system.get_mut(usize::from(*offset) + index) {
+                        *byte = *value;
+                    }
+                }
+                Some(frame(0x82, self.station_bytes(), &[*offset]))
+            }
+            (0x81, [address_2, address_1, address_0, length, ..]) => {
+                let start = u32::from_be_bytes([0, *address_2, *address_1, *address_0]) as usize;
+                let end = (start + usize::from(*length)).min(self.backup.len());
+                let mut data = vec![*address_2, *address_1, *address_0];
+                data.extend_from_slice(self.backup.get(start..end).unwrap_or_default());
+                Some(frame(0x81, station, &data))
+            }
+            (0xf5, _) => {
+                self.erase_backup();
+                Some(frame(0xf5, station, &[]))
+            }
+            _ => None,
+        }
+    }
+
+    fn erase_backup(&mut self) {
+        self.backup.fill(0xff);
+        self.set_backup_pointer(BACKUP_MEMORY_START);
+        self.system[usize::from(MEMORY_OVERFLOW_OFFSET)] = 0;
+    }
+
+    fn station_bytes(&self) -> [u8; 2] {
+        let code_offset = usize::from(STATION_CODE_OFFSET);
+        [self.system[code_offset + 1] >> 6, self.system[code_offset]]
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Station {
+    async fn run(mut self, mut stream: DuplexStream, mut actions: UnboundedReceiver<Action>) {
+        let mut buffer = Vec::new();
+        let mut handle_dropped = false;
+        loop {
+            // Cards and punches requested before a command are sent before its response, as
+            // a station does when they happen first.
+            let responses = tokio::select! {
+                biased;
+                action = actions.recv(), if !handle_dropped => {
+                    let Some(action) = action else {
+                        handle_dropped = true;
+                        continue;
+                    };
+                    self.handle_action(action)
+                }
+                read = stream.read_buf(&mut buffer) => match read {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) => self.handle_commands(&mut buffer),
+                },
+            };
+            for response in responses {
+                if stream.write_all(&response).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn station_bytes(&self) -> [u8; 2] {
+        lock(&self.memory).station_bytes()
+    }
+
+    fn handle_action(&mut self, action: Action) -> Vec<Vec<u8>> {
+        match action {
+            Action::InsertCard(card_number, image) => {
+                self.card = Some((card_number, image));
+                vec![self.card_frame(0xe8, card_number)]
+            }
+            Action::RemoveCard => self.remove_card().into_iter().collect(),
+            Action::Punch(punch) => {
+                let (station, data) = punch.encode();
+                vec![frame(0xd3, station, &data)]
+            }
+        }
+    }
+
+    /// Handles the complete commands at the start of `buffer`, leaving incomplete ones.
+    fn handle_commands(&mut self, buffer: &mut Vec<u8>) -> Vec<Vec<u8>> {
+        let mut responses = Vec::new();
+        loop {
+            let start = buffer
+                .iter()
+                .position(|byte| *byte != WAKEUP)
+                .unwrap_or(buffer.len());
+            buffer.drain(..start);
+            if buffer.first().is_some_and(|byte| *byte != START) {
+                buffer.remove(0);
+                continue;
+            }
+            let Some(&length) = buffer.get(2) else {
+                return responses;
+            };
+            let frame_length = 3 + usize::from(length) + 3;
+            if buffer.len() < frame_length {
+                return responses;
+            }
+
+            let command = buffer[1];
+            let parameters = buffer[3..3 + usize::from(length)].to_vec();
+            buffer.drain(..frame_length);
+            responses.extend(self.handle_command(command, &parameters));
+        }
+    }
+
+    fn handle_command(&mut self, command: u8, parameters: &[u8]) -> Option<Vec<u8>> {
+        let station = self.station_bytes();
+        match (command, parameters) {
+            (0xf0, [mode, ..]) => {
+                self.slave = *mode == SetMasterSlave::Slave as u8;
+                Some(frame(0xf0, station, &[*mode]))
+            }
+            (0x06, _) => {
+                self.beeps.fetch_add(1, Ordering::SeqCst);
+                self.remove_card()
+            }
+            (0xef, [block, ..]) => match &self.card {
+                Some((_, image)) => {
+                    let start = usize::from(*block) * BLOCK_SIZE;
+                    let mut data = vec![*block];
+                    data.extend(
+                        (start..start + BLOCK_SIZE)
+                            .map(|index| image.data.get(index).copied().unwrap_or(0xee)),
+                    );
+                    Some(frame(0xef, station, &data))
+                }
+                None => Some(self.card_frame(0xe7, 0)),
+            },
+            _ if self.slave => {
+                let coupled = lock(&self.coupled).clone()?;
+                let mut memory = lock(&coupled);
+                memory.handle_command(command, parameters)
+            }
+            _ => lock(&self.memory).handle_command(command, parameters),
+        }
+    }
+
+    fn remove_card(&mut self) -> Option<Vec<u8>> {
+        let (card_number, _) = self.card.take()?;
+        Some(self.card_frame(0xe7, card_number))
+    }
+
+    fn card_frame(&self, command: u8, card_number: u32) -> Vec<u8> {
+        let [_, card_2, card_1, card_0] = card_number.to_be_bytes();
+        frame(command, self.station_bytes(), &[0, card_2, card_1, card_0])
+    }
+}
+
+/// A frame in the extended protocol: STX, command, length, CN1, CN0, data, CRC and ETX.
+fn frame(command: u8, station: [u8; 2], data: &[u8]) -> Vec<u8> {
+    let mut body = vec![command, 0];
+    body.extend_from_slice(&station);
+    body.extend_from_slice(data);
+    body[1] = u8::try_from(body.len() - 2).unwrap_or(u8::MAX);
+
+    let mut frame = vec![START];
+    frame.extend_from_slice(&body);
+    frame.extend_from_slice(&crc(&body).to_be_bytes());
+    frame.push(END);
+    frame
+}
+
+/// The system memory of a station, as read by the system configuration command.
+fn system_memory(mode: StationMode, station_code: u16) -> [u8; SYSTEM_MEMORY_SIZE] {
+    const DATE: [u8; 3] = [24, 1, 1];
+
+    let mut memory = [0; SYSTEM_MEMORY_SIZE];
+    memory[0..4].copy_from_slice(&1u32.to_le_bytes());
+    memory[5..8].copy_from_slice(b"656");
+    memory[8..11].copy_from_slice(&DATE);
+    memory[11..13].copy_from_slice(&(Model::BSM7RS232 as u16).to_be_bytes());
+    memory[13] = MEMORY_KILOBYTES;
+    memory[21..24].copy_from_slice(&DATE);
+    memory[usize::from(STATION_MODE_OFFSET)] = mode as u8;
+    let [code_high, code_low] = station_code.to_be_bytes();
+    memory[usize::from(STATION_CODE_OFFSET)] = code_low;
+    memory[usize::from(STATION_CODE_OFFSET) + 1] = (code_high & 0b11) << 6;
+    memory[usize::from(PROTOCOL_CONFIGURATION_OFFSET)] =
+        (ProtocolConfiguration::EXTENDED_PROTOCOL | ProtocolConfiguration::AUTO_SEND_OUT).bits();
+    memory[117..120].copy_from_slice(&DATE);
+    let [_, _, pointer_high, pointer_low] = BACKUP_MEMORY_START.to_be_bytes();
+    memory[usize::from(BACKUP_POINTER_LOW_OFFSET)..][..2]
+        .copy_from_slice(&[pointer_high, pointer_low]);
+    memory
+}
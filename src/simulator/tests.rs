@@ -0,0 +1,186 @@
+#![allow(clippy::pedantic)]
+
+use std::time::Duration;
+
+use chrono::NaiveTime;
+
+use crate::simulator::{lock, SimulatedStation};
+use crate::CardType::Si8;
+use crate::DayOfWeek::{Saturday, Sunday};
+use crate::WeekCounter::{First, Third};
+use crate::{
+    Card, CardImage, CardPunch, CardReadout, Error, FromCardBlocks, Model, PunchSource, SRRChannel,
+    StationMode, SubSecondPunch,
+};
+
+pub fn si8_image() -> CardImage {
+    CardImage {
+        card_type: Si8,
+        data: hex::decode("05760c87eaeaeaea1a01a23a2a01a2331a01a244000000b3021f9b2a0cffcbfe4461666e613b596f6765763b4150484e413b0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee").unwrap(),
+    }
+}
+
+/// A punch on the connected station, as sent in auto send mode.
+pub fn direct_punch(card_number: u32, station_code: u16, time: NaiveTime) -> CardPunch {
+    CardPunch {
+        punch: SubSecondPunch {
+            time,
+            day_of_week: Saturday,
+            week_counter: First,
+        },
+        card: Card::new(card_number).unwrap(),
+        station_code,
+        source: PunchSource::Direct,
+        backup_address: None,
+    }
+}
+
+/// Gives `station` a backup memory of `kilobytes`, with its backup pointer at `pointer`.
+pub fn configure_backup_memory(station: &SimulatedStation, kilobytes: u8, pointer: u32) {
+    let mut memory = lock(&station.memory);
+    memory.system[13] = kilobytes;
+    memory.backup = vec![0xff; usize::from(kilobytes) * 1024];
+    memory.set_backup_pointer(pointer);
+}
+
+pub async fn si8_readout() -> CardReadout {
+    CardReadout::from_card_blocks(&mut si8_image(), Si8)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn system_configuration() {
+    let (_, mut reader) = SimulatedStation::connect(StationMode::Readout, 300)
+        .await
+        .unwrap();
+
+    let configuration = reader.system_configuration();
+    assert_eq!(configuration.mode, StationMode::Readout);
+    assert_eq!(configuration.station_code, 300);
+    assert!(matches!(configuration.model, Model::BSM7RS232));
+    assert!(configuration.protocol_configuration.is_extended_protocol());
+
+    reader.set_station_code(31).await.unwrap();
+    reader.set_station_mode(StationMode::Control).await.unwrap();
+    let configuration = reader.refresh_system_configuration().await.unwrap();
+    assert_eq!(configuration.station_code, 31);
+    assert_eq!(configuration.mode, StationMode::Control);
+}
+
+#[tokio::test]
+async fn invalid_station_code() {
+    let (_station, mut reader) = SimulatedStation::connect(StationMode::Control, 31)
+        .await
+        .unwrap();
+
+    reader.set_station_code(0x3ff).await.unwrap();
+    assert_eq!(reader.system_configuration().station_code, 0x3ff);
+
+    let result = reader.set_station_code(0x400).await;
+    assert!(matches!(result, Err(Error::InvalidStationCode(0x400))));
+    let configuration = reader.refresh_system_configuration().await.unwrap();
+    assert_eq!(configuration.station_code, 0x3ff);
+}
+
+#[tokio::test]
+async fn readout() {
+    let (station, mut reader) = SimulatedStation::connect(StationMode::Readout, 10)
+        .await
+        .unwrap();
+    let expected = si8_readout().await;
+
+    station.insert_card(expected.card_number, si8_image());
+    let readout = reader.poll_readout::<CardReadout>().await.unwrap();
+    assert_eq!(readout.station_code, 10);
+    assert_eq!(readout.card.number, expected.card_number);
+    assert_eq!(readout.data, expected);
+
+    reader.beep_until_card_removed().await.unwrap();
+    while station.beeps() == 0 {
+        tokio::task::yield_now().await;
+    }
+    station.insert_card(expected.card_number, si8_image());
+    let image = reader.poll_readout::<CardImage>().await.unwrap();
+    assert_eq!(image.data, si8_image());
+    assert_eq!(station.beeps(), 1);
+}
+
+#[tokio::test]
+async fn wait_for_frame() {
+    let (station, mut reader) = SimulatedStation::connect(StationMode::Readout, 10)
+        .await
+        .unwrap();
+    let expected = si8_readout().await;
+
+    let waiting = tokio::time::timeout(Duration::from_millis(10), reader.wait_for_frame());
+    assert!(waiting.await.is_err());
+    station.insert_card(expected.card_number, si8_image());
+    reader.wait_for_frame().await.unwrap();
+    reader.wait_for_frame().await.unwrap();
+    reader.refresh_system_configuration().await.unwrap();
+
+    let readout = reader.poll_readout::<CardReadout>().await.unwrap();
+    assert_eq!(readout.data, expected);
+}
+
+#[tokio::test]
+async fn punches() {
+    let (station, mut reader) = SimulatedStation::connect(StationMode::Control, 31)
+        .await
+        .unwrap();
+    let punches = [
+        CardPunch {
+            punch: SubSecondPunch {
+                time: NaiveTime::from_hms_opt(10, 4, 10).unwrap(),
+                day_of_week: Saturday,
+                week_counter: First,
+            },
+            card: Card::new(7_001_234).unwrap(),
+            station_code: 31,
+            source: PunchSource::Direct,
+            backup_address: None,
+        },
+        CardPunch {
+            punch: SubSecondPunch {
+                time: NaiveTime::from_hms_opt(23, 59, 58).unwrap(),
+                day_of_week: Sunday,
+                week_counter: Third,
+            },
+            card: Card::new(2_071_338).unwrap(),
+            station_code: 513,
+            source: PunchSource::Radio(SRRChannel::Blue),
+            backup_address: Some(0x0012_3456),
+        },
+    ];
+
+    for punch in &punches {
+        station.punch(punch.clone());
+        assert_eq!(&reader.poll_punch().await.unwrap(), punch);
+    }
+}
+
+#[tokio::test]
+async fn remote_station() {
+    let (station, mut reader) = SimulatedStation::connect(StationMode::Readout, 10)
+        .await
+        .unwrap();
+    let (remote, _) = SimulatedStation::spawn(StationMode::Control, 31);
+    station.couple(&remote);
+
+    let mut coupled = reader.remote_station().await.unwrap();
+    let configuration = coupled.system_configuration();
+    assert_eq!(configuration.station_code, 31);
+    assert_eq!(configuration.mode, StationMode::Control);
+    coupled.set_station_code(32).await.unwrap();
+    assert_eq!(coupled.system_configuration().station_code, 32);
+    coupled.close().await.unwrap();
+
+    assert_eq!(reader.system_configuration().station_code, 10);
+    let configuration = reader.refresh_system_configuration().await.unwrap();
+    assert_eq!(configuration.station_code, 10);
+    assert_eq!(configuration.mode, StationMode::Readout);
+
+    let coupled = reader.remote_station().await.unwrap();
+    assert_eq!(coupled.system_configuration().station_code, 32);
+}
@@ -0,0 +1,432 @@
+//! Online punches over SIRAP, the TCP protocol event software such as `MeOS` and OE
+//! accepts radio punches on.
+//!
+//! A connection carries records back to back, all integers are little-endian:
+//!
+//! | Bytes | Punch record                   | Readout record                    |
+//! |-------|--------------------------------|-----------------------------------|
+//! | 1     | type `0`                       | type `64`                         |
+//! | 2     | station code                   | number of punches                 |
+//! | 4     | card number                    | card number                       |
+//! | 4     | date, in days since 1970-01-01 | finish date                       |
+//! | 4     | time, in tenths of seconds     | finish time                       |
+//! |       |                                | start date and time (`0xffffffff` |
+//! |       |                                | both when missing), then code,    |
+//! |       |                                | date and time of every punch      |
+//!
+//! [`SirapClient`] forwards punches and finish readouts to a receiver, [`SirapListener`]
+//! receives them, e.g. from the readers of remote laptops.
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use chrono::{DateTime, Days, Local, NaiveDateTime, NaiveTime, Timelike};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+
+use crate::{CardPunch, CardReadout, Error, PunchTime, PunchTimeResolver, Reader, StationMode};
+
+#[cfg(test)]
+mod tests;
+
+const PUNCH_RECORD: u8 = 0;
+const READOUT_RECORD: u8 = 64;
+const HEADER_SIZE: usize = 15;
+const MISSING_TIME: [u8; 8] = [0xff; 8];
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+// `Duration::from_mins` needs Rust 1.91.
+#[allow(clippy::duration_suboptimal_units)]
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// A punch or a finish readout, with absolute local date-times.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SirapRecord {
+    Punch {
+        card_number: u32,
+        station_code: u16,
+        time: NaiveDateTime,
+    },
+    Readout {
+        card_number: u32,
+        start: Option<NaiveDateTime>,
+        finish: NaiveDateTime,
+        /// The code and time of every punch, in card order.
+        punches: Vec<(u16, NaiveDateTime)>,
+    },
+}
+
+/// Forwards records to a SIRAP receiver in the background.
+///
+/// Records are buffered in memory while the receiver is down, the connection is retried with an
+/// exponential backoff.
+#[derive(Debug)]
+pub struct SirapClient {
+    records: UnboundedSender<SirapRecord>,
+    sender: JoinHandle<Vec<SirapRecord>>,
+    /// Resolves punch times, around the time each record is sent when `None`.
+    resolver: Option<PunchTimeResolver<Local>>,
+}
+
+/// Receives records from any number of SIRAP clients.
+#[derive(Debug)]
+pub struct SirapListener {
+    local_address: SocketAddr,
+    records: UnboundedReceiver<SirapRecord>,
+    acceptor: JoinHandle<()>,
+}
+
+struct Sender {
+    address: String,
+    pending: VecDeque<SirapRecord>,
+    stream: Option<TcpStream>,
+}
+
+impl SirapRecord {
+    #[must_use]
+    pub fn from_punch(punch: &CardPunch, resolver: &PunchTimeResolver<Local>) -> Self {
+        Self::Punch {
+            card_number: punch.card.number,
+            station_code: punch.station_code,
+            time: resolve(&punch.punch, resolver),
+        }
+    }
+
+    /// The record of a readout, `None` when the card holds no finish punch.
+    #[must_use]
+    pub fn from_readout(
+        readout: &CardReadout,
+        resolver: &PunchTimeResolver<Local>,
+    ) -> Option<Self> {
+        Some(Self::Readout {
+            card_number: readout.card_number,
+            start: readout.start.as_ref().map(|start| resolve(start, resolver)),
+            finish: resolve(readout.finish.as_ref()?, resolver),
+            punches: readout
+                .punches
+                .iter()
+                .map(|punch| (punch.code, resolve(punch, resolver)))
+                .collect(),
+        })
+    }
+
+    #[must_use]
+    pub const fn card_number(&self) -> u32 {
+        match self {
+            Self::Punch { card_number, .. } | Self::Readout { card_number, .. } => *card_number,
+        }
+    }
+
+    pub fn encode(&self) -> crate::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        match self {
+            Self::Punch {
+                card_number,
+                station_code,
+                time,
+            } => {
+                bytes.push(PUNCH_RECORD);
+                bytes.extend_from_slice(&station_code.to_le_bytes());
+                bytes.extend_from_slice(&card_number.to_le_bytes());
+                bytes.extend_from_slice(&encode_time(time)?);
+            }
+            Self::Readout {
+                card_number,
+                start,
+                finish,
+                punches,
+            } => {
+                let count =
+                    u16::try_from(punches.len()).map_err(|_| invalid_record("Too many punches"))?;
+                bytes.push(READOUT_RECORD);
+                bytes.extend_from_slice(&count.to_le_bytes());
+                bytes.extend_from_slice(&card_number.to_le_bytes());
+                bytes.extend_from_slice(&encode_time(finish)?);
+                match start {
+                    Some(start) => bytes.extend_from_slice(&encode_time(start)?),
+                    None => bytes.extend_from_slice(&MISSING_TIME),
+                }
+                for (code, time) in punches {
+                    bytes.extend_from_slice(&code.to_le_bytes());
+                    bytes.extend_from_slice(&encode_time(time)?);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Reads the next record, `None` when the stream ends between records.
+    pub async fn read(stream: &mut (impl AsyncRead + Unpin)) -> crate::Result<Option<Self>> {
+        let mut header = [0; HEADER_SIZE];
+        let read = stream.read(&mut header).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        stream.read_exact(&mut header[read..]).await?;
+
+        let count = u16::from_le_bytes([header[1], header[2]]);
+        let card_number = u32::from_le_bytes([header[3], header[4], header[5], header[6]]);
+        let time = decode_time(&header[7..])?;
+        match header[0] {
+            PUNCH_RECORD => Ok(Some(Self::Punch {
+                card_number,
+                station_code: count,
+                time,
+            })),
+            READOUT_RECORD => {
+                let mut start = [0; 8];
+                stream.read_exact(&mut start).await?;
+                let mut punches = Vec::with_capacity(usize::from(count));
+                for _ in 0..count {
+                    let mut punch = [0; 10];
+                    stream.read_exact(&mut punch).await?;
+                    punches.push((
+                        u16::from_le_bytes([punch[0], punch[1]]),
+                        decode_time(&punch[2..])?,
+                    ));
+                }
+                Ok(Some(Self::Readout {
+                    card_number,
+                    start: if start == MISSING_TIME {
+                        None
+                    } else {
+                        Some(decode_time(&start)?)
+                    },
+                    finish: time,
+                    punches,
+                }))
+            }
+            record_type => Err(invalid_record(&format!(
+                "Unknown record type {record_type}"
+            ))),
+        }
+    }
+}
+
+impl SirapClient {
+    /// Starts forwarding to `address` (`host:port`), resolving punch times around the time
+    /// they are sent.
+    #[must_use]
+    pub fn connect(address: impl Into<String>) -> Self {
+        let (records, receiver) = mpsc::unbounded_channel();
+        let sender = Sender {
+            address: address.into(),
+            pending: VecDeque::new(),
+            stream: None,
+        };
+
+        Self {
+            records,
+            sender: tokio::spawn(sender.run(receiver)),
+            resolver: None,
+        }
+    }
+
+    /// Resolve punch times with `resolver`, e.g. [`PunchTimeResolver::for_event_date`].
+    #[must_use]
+    pub const fn with_resolver(mut self, resolver: PunchTimeResolver<Local>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    pub fn send(&self, record: SirapRecord) {
+        let _ = self.records.send(record);
+    }
+
+    pub fn send_punch(&self, punch: &CardPunch) {
+        self.send(SirapRecord::from_punch(punch, &self.resolver()));
+    }
+
+    /// Sends a readout, unless the card holds no finish punch.
+    pub fn send_readout(&self, readout: &CardReadout) {
+        if let Some(record) = SirapRecord::from_readout(readout, &self.resolver()) {
+            self.send(record);
+        }
+    }
+
+    /// Forwards every punch, or every readout when the station is in readout mode, until the
+    /// reader fails. The reader beeps after each readout.
+    pub async fn forward(&self, reader: &mut Reader) -> crate::Result<()> {
+        loop {
+            if reader.system_configuration().mode != StationMode::Readout {
+                self.send_punch(&reader.poll_punch().await?);
+                continue;
+            }
+
+            match reader.poll_readout::<CardReadout>().await {
+                Ok(readout) => self.send_readout(&readout.data),
+                Err(Error::CardRemovedWhileReadingData) => continue,
+                Err(error) => return Err(error),
+            }
+            reader.beep_until_card_removed().await?;
+        }
+    }
+
+    fn resolver(&self) -> PunchTimeResolver<Local> {
+        self.resolver
+            .clone()
+            .unwrap_or_else(|| PunchTimeResolver::new(Local::now()))
+    }
+
+    /// Sends the buffered records if the receiver is up, returning those which could not be
+    /// sent.
+    pub async fn close(self) -> Vec<SirapRecord> {
+        drop(self.records);
+        self.sender.await.unwrap_or_default()
+    }
+}
+
+impl SirapListener {
+    pub async fn bind(address: impl ToSocketAddrs) -> crate::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let local_address = listener.local_addr()?;
+        let (sender, records) = mpsc::unbounded_channel();
+
+        Ok(Self {
+            local_address,
+            records,
+            acceptor: tokio::spawn(accept(listener, sender)),
+        })
+    }
+
+    #[must_use]
+    pub const fn local_address(&self) -> SocketAddr {
+        self.local_address
+    }
+
+    /// The next record received on any connection. Connections sending invalid records are
+    /// closed.
+    pub async fn next_record(&mut self) -> Option<SirapRecord> {
+        self.records.recv().await
+    }
+}
+
+impl Drop for SirapListener {
+    fn drop(&mut self) {
+        self.acceptor.abort();
+    }
+}
+
+impl Sender {
+    async fn run(mut self, mut records: UnboundedReceiver<SirapRecord>) -> Vec<SirapRecord> {
+        let mut retry_delay = MIN_RETRY_DELAY;
+        let mut retry_at = None;
+
+        loop {
+            if retry_at.is_none_or(|retry_at| Instant::now() >= retry_at) {
+                retry_at = None;
+                if self.flush().await {
+                    retry_delay = MIN_RETRY_DELAY;
+                } else {
+                    retry_at = Some(Instant::now() + retry_delay);
+                    retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+
+            let record = match retry_at {
+                Some(retry_at) => tokio::select! {
+                    record = records.recv() => record,
+                    () = tokio::time::sleep_until(retry_at) => continue,
+                },
+                None => records.recv().await,
+            };
+            match record {
+                Some(record) => self.pending.push_back(record),
+                None => break,
+            }
+        }
+
+        self.flush().await;
+        if let Some(mut stream) = self.stream.take() {
+            let _ = stream.shutdown().await;
+        }
+        self.pending.into()
+    }
+
+    /// Sends the pending records, returning whether all of them were sent.
+    async fn flush(&mut self) -> bool {
+        while let Some(record) = self.pending.front() {
+            let Ok(bytes) = record.encode() else {
+                self.pending.pop_front();
+                continue;
+            };
+            if self.write(&bytes).await.is_err() {
+                self.stream = None;
+                return false;
+            }
+            self.pending.pop_front();
+        }
+        true
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        // Receivers never send anything, a readable stream was closed by the receiver.
+        if let Some(stream) = &self.stream {
+            match stream.try_read(&mut [0]) {
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                _ => self.stream = None,
+            }
+        }
+
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => self.stream.insert(TcpStream::connect(&self.address).await?),
+        };
+        stream.write_all(bytes).await
+    }
+}
+
+/// Accepts connections until aborted, which also closes the accepted connections.
+async fn accept(listener: TcpListener, records: UnboundedSender<SirapRecord>) {
+    let mut connections = JoinSet::new();
+    while let Ok((mut stream, _)) = listener.accept().await {
+        while connections.try_join_next().is_some() {}
+        let records = records.clone();
+        connections.spawn(async move {
+            while let Ok(Some(record)) = SirapRecord::read(&mut stream).await {
+                if records.send(record).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+fn resolve(punch: &impl PunchTime, resolver: &PunchTimeResolver<Local>) -> NaiveDateTime {
+    resolver.resolve(punch).naive_local()
+}
+
+fn encode_time(time: &NaiveDateTime) -> crate::Result<[u8; 8]> {
+    let days = time
+        .date()
+        .signed_duration_since(DateTime::UNIX_EPOCH.date_naive())
+        .num_days();
+    let days = u32::try_from(days).map_err(|_| invalid_record("Date before 1970"))?;
+    let tenths = time.num_seconds_from_midnight() * 10 + time.nanosecond() / 100_000_000;
+
+    let mut bytes = [0; 8];
+    bytes[..4].copy_from_slice(&days.to_le_bytes());
+    bytes[4..].copy_from_slice(&tenths.to_le_bytes());
+    Ok(bytes)
+}
+
+fn decode_time(bytes: &[u8]) -> crate::Result<NaiveDateTime> {
+    let days = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let tenths = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    let date = DateTime::UNIX_EPOCH
+        .date_naive()
+        .checked_add_days(Days::new(u64::from(days)))
+        .ok_or_else(|| invalid_record(&format!("Invalid date {days}")))?;
+    let time =
+        NaiveTime::from_num_seconds_from_midnight_opt(tenths / 10, (tenths % 10) * 100_000_000)
+            .filter(|_| tenths < 864_000)
+            .ok_or_else(|| invalid_record(&format!("Invalid time {tenths}")))?;
+
+    Ok(date.and_time(time))
+}
+
+fn invalid_record(message: &str) -> Error {
+    Error::InvalidSirapRecord(message.to_string())
+}
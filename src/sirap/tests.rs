@@ -0,0 +1,167 @@
+#![allow(clippy::pedantic)]
+
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use tokio::net::TcpListener;
+use tokio::time::timeout;
+
+use crate::sirap::{SirapClient, SirapListener, SirapRecord};
+use crate::test_support::punch;
+use crate::CardType::Si10;
+use crate::DayOfWeek::{Saturday, Sunday};
+use crate::StartOrFinishPunch::{Normal, SubSecond};
+use crate::WeekCounter::First;
+use crate::{Card, CardPunch, CardReadout, Error, PunchSource, PunchTimeResolver, SubSecondPunch};
+
+fn time(date: u32, time: &str) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 6, date)
+        .unwrap()
+        .and_time(NaiveTime::parse_from_str(time, "%H:%M:%S%.f").unwrap())
+}
+
+fn resolver() -> PunchTimeResolver<Local> {
+    PunchTimeResolver::new(Local.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap())
+}
+
+fn punch_record() -> SirapRecord {
+    SirapRecord::Punch {
+        card_number: 7_001_234,
+        station_code: 513,
+        time: time(2, "00:10:04.5"),
+    }
+}
+
+fn readout_record() -> SirapRecord {
+    SirapRecord::Readout {
+        card_number: 7_001_234,
+        start: None,
+        finish: time(1, "10:12:30.2"),
+        punches: vec![(31, time(1, "10:04:10")), (32, time(1, "10:10:00"))],
+    }
+}
+
+#[tokio::test]
+async fn encode_and_read() {
+    let mut bytes = punch_record().encode().unwrap();
+    assert_eq!(
+        bytes,
+        [0, 0x01, 0x02, 0x92, 0xd4, 0x6a, 0x00, 0xa4, 0x4d, 0x00, 0x00, 0x9d, 0x17, 0x00, 0x00]
+    );
+    bytes.extend(readout_record().encode().unwrap());
+    assert_eq!(bytes.len(), 15 + 15 + 8 + 2 * 10);
+
+    let mut stream = bytes.as_slice();
+    assert_eq!(
+        SirapRecord::read(&mut stream).await.unwrap(),
+        Some(punch_record())
+    );
+    assert_eq!(
+        SirapRecord::read(&mut stream).await.unwrap(),
+        Some(readout_record())
+    );
+    assert_eq!(SirapRecord::read(&mut stream).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn read_invalid_record() {
+    let mut bytes = punch_record().encode().unwrap();
+    bytes[0] = 1;
+
+    assert!(matches!(
+        SirapRecord::read(&mut bytes.as_slice()).await,
+        Err(Error::InvalidSirapRecord(_))
+    ));
+    assert!(matches!(
+        SirapRecord::read(&mut &bytes[..10]).await,
+        Err(Error::IoError(_))
+    ));
+}
+
+#[test]
+fn records_have_absolute_times() {
+    let card_punch = CardPunch {
+        punch: SubSecondPunch {
+            time: NaiveTime::from_hms_milli_opt(0, 10, 4, 500).unwrap(),
+            day_of_week: Sunday,
+            week_counter: First,
+        },
+        card: Card::new(7_001_234).unwrap(),
+        station_code: 513,
+        source: PunchSource::Direct,
+        backup_address: None,
+    };
+    assert_eq!(
+        SirapRecord::from_punch(&card_punch, &resolver()),
+        punch_record()
+    );
+
+    let mut readout = CardReadout {
+        card_number: 7_001_234,
+        card_type: Si10,
+        start: None,
+        finish: Some(SubSecond(SubSecondPunch {
+            time: NaiveTime::from_hms_milli_opt(10, 12, 30, 200).unwrap(),
+            day_of_week: Saturday,
+            week_counter: First,
+        })),
+        check: None,
+        punches: vec![punch(31, "10:04:10"), punch(32, "10:10:00")],
+    };
+    assert_eq!(
+        SirapRecord::from_readout(&readout, &resolver()),
+        Some(readout_record())
+    );
+
+    readout.start = Some(Normal(punch(1, "10:00:00")));
+    readout.finish = None;
+    assert_eq!(SirapRecord::from_readout(&readout, &resolver()), None);
+}
+
+#[tokio::test]
+async fn client_to_listener() {
+    let mut listener = SirapListener::bind("127.0.0.1:0").await.unwrap();
+    let client = SirapClient::connect(listener.local_address().to_string());
+
+    client.send(punch_record());
+    client.send(readout_record());
+
+    assert_eq!(listener.next_record().await, Some(punch_record()));
+    assert_eq!(listener.next_record().await, Some(readout_record()));
+    assert!(client.close().await.is_empty());
+}
+
+#[tokio::test]
+async fn client_buffers_while_listener_is_down() {
+    let address = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let client = SirapClient::connect(address.to_string());
+    client.send(punch_record());
+    client.send(readout_record());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut listener = SirapListener::bind(address).await.unwrap();
+    let records = timeout(Duration::from_secs(10), async {
+        vec![listener.next_record().await, listener.next_record().await]
+    })
+    .await
+    .unwrap();
+    assert_eq!(records, vec![Some(punch_record()), Some(readout_record())]);
+    assert!(client.close().await.is_empty());
+}
+
+#[tokio::test]
+async fn close_returns_unsent_records() {
+    let address = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let client = SirapClient::connect(address.to_string());
+    client.send(punch_record());
+
+    assert_eq!(client.close().await, vec![punch_record()]);
+}
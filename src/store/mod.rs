@@ -0,0 +1,338 @@
+//! Readouts, punches and station configurations persisted in an `SQLite` database.
+//!
+//! Readouts, owner data, punches and configurations are stored as JSON next to the columns
+//! they are queried by, raw card blocks as a blob. Times are stored as milliseconds since the
+//! Unix epoch. The schema version is kept in `PRAGMA user_version`, and databases written by
+//! older versions are migrated when they are opened.
+use std::path::Path;
+
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{
+    CardImage, CardOwnerData, CardPunch, CardReadout, Error, Readout, SystemConfiguration,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The schema changes, applied in order. `PRAGMA user_version` counts the ones applied so far.
+///
+/// The first one creates the tables if needed, so databases created before the schema was
+/// versioned are migrated without losing data. Migrations are never edited once released:
+/// schema changes go in a new one.
+const MIGRATIONS: &[&str] = &["
+CREATE TABLE IF NOT EXISTS readouts (
+    id INTEGER PRIMARY KEY,
+    card_number INTEGER NOT NULL,
+    read_at INTEGER NOT NULL,
+    station_serial INTEGER NOT NULL,
+    station_code INTEGER NOT NULL,
+    readout TEXT NOT NULL,
+    data TEXT NOT NULL,
+    owner_data TEXT,
+    image BLOB,
+    UNIQUE (card_number, data)
+);
+CREATE INDEX IF NOT EXISTS readouts_read_at ON readouts (read_at);
+CREATE INDEX IF NOT EXISTS readouts_station_serial ON readouts (station_serial);
+
+CREATE TABLE IF NOT EXISTS punches (
+    id INTEGER PRIMARY KEY,
+    card_number INTEGER NOT NULL,
+    received_at INTEGER NOT NULL,
+    station_serial INTEGER NOT NULL,
+    station_code INTEGER NOT NULL,
+    punch TEXT NOT NULL,
+    UNIQUE (station_serial, punch)
+);
+CREATE INDEX IF NOT EXISTS punches_card_number ON punches (card_number);
+CREATE INDEX IF NOT EXISTS punches_received_at ON punches (received_at);
+
+CREATE TABLE IF NOT EXISTS stations (
+    id INTEGER PRIMARY KEY,
+    serial_number INTEGER NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    configuration TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS stations_serial_number ON stations (serial_number);
+"];
+
+/// Matches every row by `card_number`, `time` and `station_serial` unless the parameter is null.
+const FILTER: &str = "(?1 IS NULL OR card_number = ?1) AND (?2 IS NULL OR {time} >= ?2) \
+                      AND (?3 IS NULL OR {time} < ?3) AND (?4 IS NULL OR station_serial = ?4)";
+
+/// An `SQLite` database of readouts, punches and station configurations.
+#[derive(Debug)]
+pub struct Store {
+    connection: Connection,
+}
+
+/// A readout along with when and by which station it was read.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StoredReadout {
+    pub read_at: DateTime<Local>,
+    /// The serial number of the station which read the card.
+    pub station_serial: u32,
+    pub readout: Readout<CardReadout>,
+    pub owner_data: Option<CardOwnerData>,
+    /// The raw blocks the readout was decoded from.
+    pub image: Option<CardImage>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StoredPunch {
+    pub received_at: DateTime<Local>,
+    /// The serial number of the station which received the punch, e.g. an SRR dongle.
+    pub station_serial: u32,
+    pub punch: CardPunch,
+}
+
+/// The system configuration of a station at a point in time.
+#[derive(Debug, Clone)]
+pub struct StationSnapshot {
+    pub recorded_at: DateTime<Local>,
+    pub configuration: SystemConfiguration,
+}
+
+/// Which readouts or punches to return, all of them by default.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct StoreQuery {
+    card_number: Option<u32>,
+    from: Option<DateTime<Local>>,
+    until: Option<DateTime<Local>>,
+    station_serial: Option<u32>,
+}
+
+impl StoreQuery {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn card_number(mut self, card_number: u32) -> Self {
+        self.card_number = Some(card_number);
+        self
+    }
+
+    /// Only rows read or received at `from` or later.
+    #[must_use]
+    pub const fn from(mut self, from: DateTime<Local>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Only rows read or received before `until`.
+    #[must_use]
+    pub const fn until(mut self, until: DateTime<Local>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    #[must_use]
+    pub const fn station_serial(mut self, station_serial: u32) -> Self {
+        self.station_serial = Some(station_serial);
+        self
+    }
+
+    fn parameters(&self) -> [Option<i64>; 4] {
+        [
+            self.card_number.map(i64::from),
+            self.from.map(|from| from.timestamp_millis()),
+            self.until.map(|until| until.timestamp_millis()),
+            self.station_serial.map(i64::from),
+        ]
+    }
+}
+
+impl Store {
+    /// Opens a database file, creating it and its tables if needed.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> crate::Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(mut connection: Connection) -> crate::Result<Self> {
+        migrate(&mut connection)?;
+        Ok(Self { connection })
+    }
+
+    /// Stores a readout, returning `false` when the same card data was already stored.
+    pub fn insert_readout(&self, readout: &StoredReadout) -> crate::Result<bool> {
+        let inserted = self.connection.execute(
+            "INSERT OR IGNORE INTO readouts (card_number, read_at, station_serial, station_code, \
+             readout, data, owner_data, image) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                readout.readout.card.number,
+                readout.read_at.timestamp_millis(),
+                readout.station_serial,
+                readout.readout.station_code,
+                to_json(&readout.readout)?,
+                to_json(&readout.readout.data)?,
+                readout.owner_data.as_ref().map(to_json).transpose()?,
+                readout.image.as_ref().map(|image| &image.data),
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Stores a punch, returning `false` when the station already received the same punch.
+    pub fn insert_punch(&self, punch: &StoredPunch) -> crate::Result<bool> {
+        let inserted = self.connection.execute(
+            "INSERT OR IGNORE INTO punches (card_number, received_at, station_serial, \
+             station_code, punch) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                punch.punch.card.number,
+                punch.received_at.timestamp_millis(),
+                punch.station_serial,
+                punch.punch.station_code,
+                to_json(&punch.punch)?,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn insert_station(&self, snapshot: &StationSnapshot) -> crate::Result<()> {
+        self.connection.execute(
+            "INSERT INTO stations (serial_number, recorded_at, configuration) VALUES (?1, ?2, ?3)",
+            params![
+                snapshot.configuration.serial_number,
+                snapshot.recorded_at.timestamp_millis(),
+                to_json(&snapshot.configuration)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The matching readouts, in the order they were read.
+    pub fn readouts(&self, query: &StoreQuery) -> crate::Result<Vec<StoredReadout>> {
+        let sql = format!(
+            "SELECT read_at, station_serial, readout, owner_data, image FROM readouts WHERE {} \
+             ORDER BY read_at, id",
+            FILTER.replace("{time}", "read_at")
+        );
+        self.query(&sql, query, |row| {
+            let readout = from_json::<Readout<CardReadout>>(&row.get::<_, String>(2)?)?;
+            let image = row.get::<_, Option<Vec<u8>>>(4)?.map(|data| CardImage {
+                card_type: readout.card.card_type,
+                data,
+            });
+            Ok(StoredReadout {
+                read_at: from_millis(row.get(0)?)?,
+                station_serial: row.get(1)?,
+                owner_data: row
+                    .get::<_, Option<String>>(3)?
+                    .as_deref()
+                    .map(from_json)
+                    .transpose()?,
+                image,
+                readout,
+            })
+        })
+    }
+
+    /// The matching punches, in the order they were received.
+    pub fn punches(&self, query: &StoreQuery) -> crate::Result<Vec<StoredPunch>> {
+        let sql = format!(
+            "SELECT received_at, station_serial, punch FROM punches WHERE {} \
+             ORDER BY received_at, id",
+            FILTER.replace("{time}", "received_at")
+        );
+        self.query(&sql, query, |row| {
+            Ok(StoredPunch {
+                received_at: from_millis(row.get(0)?)?,
+                station_serial: row.get(1)?,
+                punch: from_json(&row.get::<_, String>(2)?)?,
+            })
+        })
+    }
+
+    /// The recorded configurations of a station, oldest first.
+    pub fn station_history(&self, serial_number: u32) -> crate::Result<Vec<StationSnapshot>> {
+        let mut statement = self.connection.prepare(
+            "SELECT recorded_at, configuration FROM stations WHERE serial_number = ?1 \
+             ORDER BY recorded_at, id",
+        )?;
+        let mut rows = statement.query([serial_number])?;
+        let mut snapshots = Vec::new();
+        while let Some(row) = rows.next()? {
+            snapshots.push(StationSnapshot {
+                recorded_at: from_millis(row.get(0)?)?,
+                configuration: from_json(&row.get::<_, String>(1)?)?,
+            });
+        }
+        Ok(snapshots)
+    }
+
+    /// The last recorded configuration of a station.
+    pub fn last_station(&self, serial_number: u32) -> crate::Result<Option<StationSnapshot>> {
+        let row = self
+            .connection
+            .query_row(
+                "SELECT recorded_at, configuration FROM stations WHERE serial_number = ?1 \
+                 ORDER BY recorded_at DESC, id DESC LIMIT 1",
+                [serial_number],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+        row.map(|(recorded_at, configuration)| {
+            Ok(StationSnapshot {
+                recorded_at: from_millis(recorded_at)?,
+                configuration: from_json(&configuration)?,
+            })
+        })
+        .transpose()
+    }
+
+    fn query<T>(
+        &self,
+        sql: &str,
+        query: &StoreQuery,
+        map: impl Fn(&Row) -> crate::Result<T>,
+    ) -> crate::Result<Vec<T>> {
+        let mut statement = self.connection.prepare(sql)?;
+        let mut rows = statement.query(query.parameters())?;
+        let mut values = Vec::new();
+        while let Some(row) = rows.next()? {
+            values.push(map(row)?);
+        }
+        Ok(values)
+    }
+}
+
+/// Applies the migrations the database has not seen yet, each in its own transaction.
+fn migrate(connection: &mut Connection) -> crate::Result<()> {
+    let version: u32 = connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version as usize > MIGRATIONS.len() {
+        return Err(Error::UnsupportedStoreVersion(version));
+    }
+
+    for (migration, applied) in MIGRATIONS.iter().zip(1u32..).skip(version as usize) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", applied)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+fn to_json(value: &impl Serialize) -> crate::Result<String> {
+    serde_json::to_string(value).map_err(|e| Error::InvalidStoreData(e.to_string()))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> crate::Result<T> {
+    serde_json::from_str(json).map_err(|e| Error::InvalidStoreData(e.to_string()))
+}
+
+fn from_millis(millis: i64) -> crate::Result<DateTime<Local>> {
+    Local
+        .timestamp_millis_opt(millis)
+        .single()
+        .ok_or_else(|| Error::InvalidStoreData(format!("Invalid time {millis}")))
+}
@@ -0,0 +1,306 @@
+#![allow(clippy::pedantic)]
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone};
+
+use crate::store::{StationSnapshot, Store, StoreQuery, StoredPunch, StoredReadout, MIGRATIONS};
+use crate::test_support::punch;
+use crate::CardType::Si10;
+use crate::DayOfWeek::Saturday;
+use crate::StartOrFinishPunch::Normal;
+use crate::WeekCounter::First;
+use crate::{
+    Card, CardImage, CardOwnerData, CardPunch, CardReadout, Error, Model, ProtocolConfiguration,
+    PunchFeedback, PunchSource, Readout, SI6CardBlocks, SRRChannel, SRRConfiguration, StationMode,
+    StationProgram, SubSecondPunch, SystemConfiguration,
+};
+
+fn at(hour: u32, minute: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2024, 6, 1, hour, minute, 0).unwrap()
+}
+
+fn readout(card_number: u32, finish: &str, read_at: DateTime<Local>) -> StoredReadout {
+    StoredReadout {
+        read_at,
+        station_serial: 501_234,
+        readout: Readout {
+            station_code: 10,
+            card: Card::new(card_number).unwrap(),
+            data: CardReadout {
+                card_number,
+                card_type: Si10,
+                start: Some(Normal(punch(1, "10:00:00"))),
+                finish: Some(Normal(punch(2, finish))),
+                check: None,
+                punches: vec![punch(31, "10:04:10"), punch(32, "10:10:00")],
+            },
+        },
+        owner_data: None,
+        image: None,
+    }
+}
+
+fn card_punch(card_number: u32, station_code: u16, time: &str) -> CardPunch {
+    CardPunch {
+        punch: SubSecondPunch {
+            time: NaiveTime::parse_from_str(time, "%H:%M:%S%.f").unwrap(),
+            day_of_week: Saturday,
+            week_counter: First,
+        },
+        card: Card::new(card_number).unwrap(),
+        station_code,
+        source: PunchSource::Radio(SRRChannel::Red),
+        backup_address: None,
+    }
+}
+
+fn configuration(station_code: u16) -> SystemConfiguration {
+    let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+    SystemConfiguration {
+        serial_number: 501_234,
+        srr_configuration: SRRConfiguration::RED_CHANNEL,
+        firmware: *b"656",
+        build_date: date,
+        model: Model::BSF8V2,
+        mem_kilobytes: 128,
+        battery_date: date,
+        battery_capacity_milliampere_hour: 1000,
+        backup_pointer_high: 0,
+        backup_pointer_low: 0x1f8,
+        si6_card_blocks: SI6CardBlocks::all(),
+        srr_channel: SRRChannel::Red,
+        used_battery_capacity_percentage: 12.5,
+        memory_overflow: false,
+        battery_voltage: 3.5,
+        station_program: StationProgram::Competition,
+        mode: StationMode::Readout,
+        station_code,
+        punch_feedback: PunchFeedback::OPTICAL | PunchFeedback::AUDIBLE,
+        protocol_configuration: ProtocolConfiguration::EXTENDED_PROTOCOL,
+        wakeup_date: date,
+        active_duration: TimeDelta::hours(2),
+    }
+}
+
+#[test]
+fn readouts() {
+    let store = Store::open_in_memory().unwrap();
+    let mut first = readout(7_001_234, "10:12:30", at(10, 13));
+    first.owner_data = Some(CardOwnerData {
+        first_name: "Åsa".to_string(),
+        last_name: "Lindström".to_string(),
+        gender: None,
+        birthday: None,
+        club: Some("Tisaren".to_string()),
+        email: None,
+        phone: None,
+        city: None,
+        street: None,
+        zip: None,
+        country: None,
+    });
+    first.image = Some(CardImage {
+        card_type: Si10,
+        data: vec![0xee; 128],
+    });
+    let mut second = readout(7_001_235, "10:20:00", at(10, 21));
+    second.station_serial = 501_235;
+
+    assert!(store.insert_readout(&first).unwrap());
+    assert!(store.insert_readout(&second).unwrap());
+
+    assert_eq!(
+        store.readouts(&StoreQuery::new()).unwrap(),
+        vec![first.clone(), second.clone()]
+    );
+    assert_eq!(
+        store
+            .readouts(&StoreQuery::new().card_number(7_001_235))
+            .unwrap(),
+        vec![second]
+    );
+    assert_eq!(
+        store
+            .readouts(&StoreQuery::new().station_serial(501_234))
+            .unwrap(),
+        vec![first.clone()]
+    );
+    assert_eq!(
+        store
+            .readouts(&StoreQuery::new().from(at(10, 13)).until(at(10, 21)))
+            .unwrap(),
+        vec![first]
+    );
+}
+
+#[test]
+fn identical_rereads_are_stored_once() {
+    let store = Store::open_in_memory().unwrap();
+
+    assert!(store
+        .insert_readout(&readout(7_001_234, "10:12:30", at(10, 13)))
+        .unwrap());
+    assert!(!store
+        .insert_readout(&readout(7_001_234, "10:12:30", at(10, 15)))
+        .unwrap());
+    assert!(store
+        .insert_readout(&readout(7_001_234, "11:30:00", at(11, 31)))
+        .unwrap());
+
+    let readouts = store
+        .readouts(&StoreQuery::new().card_number(7_001_234))
+        .unwrap();
+    assert_eq!(
+        readouts
+            .iter()
+            .map(|readout| readout.read_at)
+            .collect::<Vec<_>>(),
+        vec![at(10, 13), at(11, 31)]
+    );
+}
+
+#[test]
+fn punches() {
+    let store = Store::open_in_memory().unwrap();
+    let punches = [
+        StoredPunch {
+            received_at: at(10, 4),
+            station_serial: 700_001,
+            punch: card_punch(7_001_234, 31, "10:04:10.5"),
+        },
+        StoredPunch {
+            received_at: at(10, 5),
+            station_serial: 700_001,
+            punch: card_punch(7_001_235, 31, "10:05:00"),
+        },
+        StoredPunch {
+            received_at: at(10, 10),
+            station_serial: 700_002,
+            punch: card_punch(7_001_234, 32, "10:10:00"),
+        },
+    ];
+
+    for punch in &punches {
+        assert!(store.insert_punch(punch).unwrap());
+    }
+    let mut repeated = punches[0].clone();
+    repeated.received_at = at(10, 30);
+    assert!(!store.insert_punch(&repeated).unwrap());
+
+    assert_eq!(store.punches(&StoreQuery::new()).unwrap(), punches);
+    assert_eq!(
+        store
+            .punches(&StoreQuery::new().card_number(7_001_234))
+            .unwrap(),
+        vec![punches[0].clone(), punches[2].clone()]
+    );
+    assert_eq!(
+        store
+            .punches(&StoreQuery::new().station_serial(700_001).from(at(10, 5)))
+            .unwrap(),
+        vec![punches[1].clone()]
+    );
+}
+
+#[test]
+fn station_snapshots() {
+    let store = Store::open_in_memory().unwrap();
+    assert!(store.last_station(501_234).unwrap().is_none());
+
+    for (recorded_at, station_code) in [(at(9, 0), 10), (at(12, 0), 31)] {
+        store
+            .insert_station(&StationSnapshot {
+                recorded_at,
+                configuration: configuration(station_code),
+            })
+            .unwrap();
+    }
+
+    let history = store.station_history(501_234).unwrap();
+    assert_eq!(
+        history
+            .iter()
+            .map(|snapshot| (snapshot.recorded_at, snapshot.configuration.station_code))
+            .collect::<Vec<_>>(),
+        vec![(at(9, 0), 10), (at(12, 0), 31)]
+    );
+    let last = store.last_station(501_234).unwrap().unwrap();
+    assert_eq!(last.configuration.station_code, 31);
+    assert!(matches!(last.configuration.model, Model::BSF8V2));
+    assert!(store.station_history(501_235).unwrap().is_empty());
+}
+
+#[test]
+fn persisted_to_file() {
+    let path = std::env::temp_dir().join(format!("sportident-store-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let store = Store::open(&path).unwrap();
+    store
+        .insert_readout(&readout(7_001_234, "10:12:30", at(10, 13)))
+        .unwrap();
+    drop(store);
+
+    let store = Store::open(&path).unwrap();
+    assert_eq!(
+        store.readouts(&StoreQuery::new()).unwrap(),
+        vec![readout(7_001_234, "10:12:30", at(10, 13))]
+    );
+    assert!(!store
+        .insert_readout(&readout(7_001_234, "10:12:30", at(10, 14)))
+        .unwrap());
+    drop(store);
+    std::fs::remove_file(path).unwrap();
+}
+
+fn user_version(path: &std::path::Path) -> u32 {
+    rusqlite::Connection::open(path)
+        .unwrap()
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn unversioned_database_is_migrated() {
+    let path = std::env::temp_dir().join(format!(
+        "sportident-store-unversioned-{}.db",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    // A database with data, written before the schema was versioned.
+    Store::open(&path)
+        .unwrap()
+        .insert_readout(&readout(7_001_234, "10:12:30", at(10, 13)))
+        .unwrap();
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .pragma_update(None, "user_version", 0)
+        .unwrap();
+
+    let store = Store::open(&path).unwrap();
+    assert_eq!(user_version(&path), MIGRATIONS.len() as u32);
+    assert_eq!(
+        store.readouts(&StoreQuery::new()).unwrap(),
+        vec![readout(7_001_234, "10:12:30", at(10, 13))]
+    );
+    drop(store);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn newer_schema_is_rejected() {
+    let path =
+        std::env::temp_dir().join(format!("sportident-store-newer-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    drop(Store::open(&path).unwrap());
+    let newer = MIGRATIONS.len() as u32 + 1;
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .pragma_update(None, "user_version", newer)
+        .unwrap();
+
+    assert!(matches!(
+        Store::open(&path),
+        Err(Error::UnsupportedStoreVersion(version)) if version == newer
+    ));
+    std::fs::remove_file(path).unwrap();
+}
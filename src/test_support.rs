@@ -0,0 +1,19 @@
+#![allow(clippy::pedantic)]
+
+use std::str::FromStr;
+
+use chrono::NaiveTime;
+
+use crate::DayOfWeek::Saturday;
+use crate::Punch;
+use crate::WeekCounter::First;
+
+/// A punch on the first Saturday, at a `HH:MM:SS` time with optional fractional seconds.
+pub fn punch(code: u16, time: &str) -> Punch {
+    Punch {
+        time: NaiveTime::from_str(time).unwrap(),
+        day_of_week: Saturday,
+        week_counter: First,
+        code,
+    }
+}
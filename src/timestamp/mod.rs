@@ -0,0 +1,192 @@
+use chrono::{
+    DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone,
+    Weekday,
+};
+
+use crate::{DayOfWeek, Punch, StartOrFinishPunch, SubSecondPunch, WeekCounter};
+
+const TWELVE_HOURS: TimeDelta = TimeDelta::hours(12);
+const WEEKS_IN_COUNTER: i64 = 4;
+
+/// The time of a punch, as recorded by a station.
+pub trait PunchTime {
+    fn time(&self) -> NaiveTime;
+    fn day_of_week(&self) -> DayOfWeek;
+    fn week_counter(&self) -> WeekCounter;
+}
+
+impl PunchTime for Punch {
+    fn time(&self) -> NaiveTime {
+        self.time
+    }
+
+    fn day_of_week(&self) -> DayOfWeek {
+        self.day_of_week
+    }
+
+    fn week_counter(&self) -> WeekCounter {
+        self.week_counter
+    }
+}
+
+impl PunchTime for SubSecondPunch {
+    fn time(&self) -> NaiveTime {
+        self.time
+    }
+
+    fn day_of_week(&self) -> DayOfWeek {
+        self.day_of_week
+    }
+
+    fn week_counter(&self) -> WeekCounter {
+        self.week_counter
+    }
+}
+
+impl PunchTime for StartOrFinishPunch {
+    fn time(&self) -> NaiveTime {
+        match self {
+            Self::Normal(punch) => punch.time,
+            Self::SubSecond(punch) => punch.time,
+        }
+    }
+
+    fn day_of_week(&self) -> DayOfWeek {
+        match self {
+            Self::Normal(punch) => punch.day_of_week,
+            Self::SubSecond(punch) => punch.day_of_week,
+        }
+    }
+
+    fn week_counter(&self) -> WeekCounter {
+        match self {
+            Self::Normal(punch) => punch.week_counter,
+            Self::SubSecond(punch) => punch.week_counter,
+        }
+    }
+}
+
+impl From<DayOfWeek> for Weekday {
+    fn from(day_of_week: DayOfWeek) -> Self {
+        match day_of_week {
+            DayOfWeek::Monday => Self::Mon,
+            DayOfWeek::Tuesday => Self::Tue,
+            DayOfWeek::Wednesday => Self::Wed,
+            DayOfWeek::Thursday => Self::Thu,
+            DayOfWeek::Friday => Self::Fri,
+            DayOfWeek::Saturday => Self::Sat,
+            DayOfWeek::Sunday => Self::Sun,
+        }
+    }
+}
+
+/// Turns punch times into absolute date-times, around a reference date-time.
+///
+/// A punch resolves to the date-time closest to the reference that matches its time of day and
+/// day of week, so punches after midnight land on the next day. When the week counter of the
+/// stations at the reference is known, the 4-week counter of the punch is matched as well.
+#[derive(Debug, Clone)]
+pub struct PunchTimeResolver<Tz: TimeZone> {
+    reference: DateTime<Tz>,
+    reference_week_counter: Option<WeekCounter>,
+    twelve_hour: bool,
+}
+
+impl<Tz: TimeZone> PunchTimeResolver<Tz> {
+    #[must_use]
+    pub const fn new(reference: DateTime<Tz>) -> Self {
+        Self {
+            reference,
+            reference_week_counter: None,
+            twelve_hour: false,
+        }
+    }
+
+    /// Resolve punches of an event held on `date`, using noon as the reference.
+    #[must_use]
+    pub fn for_event_date(date: NaiveDate, time_zone: &Tz) -> Self {
+        Self::new(to_date_time(
+            time_zone,
+            date.and_time(NaiveTime::MIN) + TWELVE_HOURS,
+        ))
+    }
+
+    /// The week counter of the stations on the reference date.
+    #[must_use]
+    pub const fn with_week_counter(mut self, week_counter: WeekCounter) -> Self {
+        self.reference_week_counter = Some(week_counter);
+        self
+    }
+
+    /// Ignore the AM/PM bit of punches, for stations running in 12-hour mode.
+    #[must_use]
+    pub const fn twelve_hour(mut self) -> Self {
+        self.twelve_hour = true;
+        self
+    }
+
+    #[must_use]
+    pub fn resolve(&self, punch: &impl PunchTime) -> DateTime<Tz> {
+        let reference = self.reference.naive_local();
+        let reference_date = reference.date();
+        let weekday = Weekday::from(punch.day_of_week());
+        let times = if self.twelve_hour {
+            let noon = NaiveTime::MIN + TWELVE_HOURS;
+            let morning = if punch.time() >= noon {
+                punch.time() - TWELVE_HOURS
+            } else {
+                punch.time()
+            };
+            vec![morning, morning + TWELVE_HOURS]
+        } else {
+            vec![punch.time()]
+        };
+        let days = if self.reference_week_counter.is_some() {
+            WEEKS_IN_COUNTER * 7
+        } else {
+            7
+        };
+
+        let resolved = (-days..=days)
+            .map(|offset| reference_date + TimeDelta::days(offset))
+            .filter(|date| date.weekday() == weekday)
+            .filter(|date| {
+                self.reference_week_counter
+                    .is_none_or(|reference_week_counter| {
+                        let weeks = (date.signed_duration_since(monday_of(reference_date)))
+                            .num_days()
+                            .div_euclid(7);
+                        (i64::from(reference_week_counter as u8) + weeks)
+                            .rem_euclid(WEEKS_IN_COUNTER)
+                            == i64::from(punch.week_counter() as u8)
+                    })
+            })
+            .flat_map(|date| times.iter().map(move |time| date.and_time(*time)))
+            .min_by_key(|candidate| (*candidate - reference).abs())
+            .unwrap_or_else(|| reference_date.and_time(punch.time()));
+
+        to_date_time(&self.reference.timezone(), resolved)
+    }
+}
+
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - TimeDelta::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+/// Localize a date-time, taking the earlier instant when clocks are set back. A local time
+/// skipped because clocks are set forward is resolved with the offset in effect before the
+/// transition, as kept by a station whose clock was not moved forward.
+fn to_date_time<Tz: TimeZone>(time_zone: &Tz, date_time: NaiveDateTime) -> DateTime<Tz> {
+    let mut shift = TimeDelta::zero();
+    loop {
+        match time_zone.from_local_datetime(&(date_time - shift)) {
+            LocalResult::Single(resolved) | LocalResult::Ambiguous(resolved, _) => {
+                return resolved + shift
+            }
+            LocalResult::None => shift += TimeDelta::minutes(30),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,110 @@
+#![allow(clippy::pedantic)]
+
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Stockholm;
+
+use crate::timestamp::PunchTimeResolver;
+use crate::DayOfWeek::{Saturday, Sunday};
+use crate::WeekCounter::{First, Second, Third};
+use crate::{DayOfWeek, Punch, WeekCounter};
+
+fn punch(time: &str, day_of_week: DayOfWeek, week_counter: WeekCounter) -> Punch {
+    Punch {
+        time: NaiveTime::from_str(time).unwrap(),
+        day_of_week,
+        week_counter,
+        code: 31,
+    }
+}
+
+fn utc(date_time: &str) -> chrono::DateTime<Utc> {
+    Utc.from_utc_datetime(&NaiveDateTime::from_str(date_time).unwrap())
+}
+
+#[test]
+fn event_date() {
+    // 2024-06-01 is a Saturday.
+    let resolver =
+        PunchTimeResolver::for_event_date(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(), &Utc);
+
+    assert_eq!(
+        resolver.resolve(&punch("10:15:30", Saturday, First)),
+        utc("2024-06-01T10:15:30")
+    );
+}
+
+#[test]
+fn midnight_crossing() {
+    let resolver = PunchTimeResolver::new(utc("2024-06-01T23:30:00"));
+
+    assert_eq!(
+        resolver.resolve(&punch("00:10:00", Sunday, First)),
+        utc("2024-06-02T00:10:00")
+    );
+}
+
+#[test]
+fn week_counter() {
+    let resolver = PunchTimeResolver::new(utc("2024-06-01T12:00:00")).with_week_counter(Second);
+
+    assert_eq!(
+        resolver.resolve(&punch("12:00:00", Saturday, Third)),
+        utc("2024-06-08T12:00:00")
+    );
+    assert_eq!(
+        resolver.resolve(&punch("12:00:00", Saturday, First)),
+        utc("2024-05-25T12:00:00")
+    );
+}
+
+#[test]
+fn twelve_hour() {
+    let resolver = PunchTimeResolver::new(utc("2024-06-01T14:00:00")).twelve_hour();
+
+    assert_eq!(
+        resolver.resolve(&punch("02:30:00", Saturday, First)),
+        utc("2024-06-01T14:30:00")
+    );
+}
+
+#[test]
+fn daylight_saving_time() {
+    // Clocks were set back from 03:00 to 02:00 on 2024-10-27 in Stockholm.
+    let resolver = PunchTimeResolver::for_event_date(
+        NaiveDate::from_ymd_opt(2024, 10, 27).unwrap(),
+        &Stockholm,
+    );
+
+    assert_eq!(
+        resolver.resolve(&punch("02:30:00", Sunday, First)).to_utc(),
+        utc("2024-10-27T00:30:00")
+    );
+    assert_eq!(
+        resolver.resolve(&punch("12:00:00", Sunday, First)).to_utc(),
+        utc("2024-10-27T11:00:00")
+    );
+}
+
+#[test]
+fn skipped_local_time() {
+    // Clocks were set forward from 02:00 to 03:00 on 2024-03-31 in Stockholm.
+    let resolver = PunchTimeResolver::for_event_date(
+        NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        &Stockholm,
+    );
+
+    assert_eq!(
+        resolver.resolve(&punch("01:50:00", Sunday, First)).to_utc(),
+        utc("2024-03-31T00:50:00")
+    );
+    assert_eq!(
+        resolver.resolve(&punch("02:10:00", Sunday, First)).to_utc(),
+        utc("2024-03-31T01:10:00")
+    );
+    assert_eq!(
+        resolver.resolve(&punch("02:30:00", Sunday, First)).to_utc(),
+        utc("2024-03-31T01:30:00")
+    );
+}